@@ -0,0 +1,196 @@
+"""Aides pour écrire le code d'une équipe : `import simulator` fonctionne sans rien installer.
+
+Les constantes du terrain (FIELD_WIDTH, FIELD_DEPTH, ROBOT_RADIUS...) sont ajoutées par le simulateur
+avant ce code, avec les valeurs de infos.rs : voir simulator.pyi pour la liste.
+
+Les positions sont celles que reçoit `update`, dans le repère de l'équipe :
+on attaque le but de gauche (x < 0) et on défend celui de droite (x > 0).
+Les angles sont en degrés, dans la même convention que `my_orientation`.
+"""
+
+from __future__ import annotations
+
+import math
+from dataclasses import dataclass, field, fields
+
+Vector = tuple[float, float]
+
+
+# Vecteurs
+
+
+def add(a: Vector, b: Vector) -> Vector:
+    return (a[0] + b[0], a[1] + b[1])
+
+
+def sub(a: Vector, b: Vector) -> Vector:
+    return (a[0] - b[0], a[1] - b[1])
+
+
+def scale(v: Vector, k: float) -> Vector:
+    return (v[0] * k, v[1] * k)
+
+
+def norm(v: Vector) -> float:
+    return math.hypot(v[0], v[1])
+
+
+def distance(a: Vector, b: Vector) -> float:
+    return norm(sub(b, a))
+
+
+def normalize(v: Vector) -> Vector:
+    """Vecteur de même direction et de longueur 1, (0, 0) pour le vecteur nul"""
+    length = norm(v)
+    if length == 0:
+        return (0.0, 0.0)
+    return scale(v, 1 / length)
+
+
+def rotate(v: Vector, angle: float) -> Vector:
+    """Tourne `v` de `angle` degrés, dans le sens où croît `my_orientation`"""
+    cos, sin = math.cos(math.radians(angle)), math.sin(math.radians(angle))
+    return (v[0] * cos - v[1] * sin, v[0] * sin + v[1] * cos)
+
+
+# Angles
+
+
+def normalize_angle(angle: float) -> float:
+    """Ramène un angle entre -180 exclu et 180 inclus, comme `my_orientation`"""
+    angle = math.fmod(angle, 360)
+    if angle <= -180:
+        angle += 360
+    elif angle > 180:
+        angle -= 360
+    return angle
+
+
+def angle_of(v: Vector) -> float:
+    """Direction de `v`"""
+    return math.degrees(math.atan2(v[1], v[0]))
+
+
+def angle_to_target(position: Vector, target: Vector) -> float:
+    """Orientation à prendre en `position` pour que l'avant du robot regarde `target`"""
+    return angle_of(sub(target, position))
+
+
+def to_robot_frame(v: Vector, orientation: float) -> Vector:
+    """Un vecteur du terrain en (avant, gauche) pour un robot orienté selon `orientation`"""
+    return rotate(v, -orientation)
+
+
+def to_field_frame(v: Vector, orientation: float) -> Vector:
+    """Un vecteur (avant, gauche) d'un robot orienté selon `orientation` dans le repère du terrain"""
+    return rotate(v, orientation)
+
+
+# Terrain
+
+
+def own_goal() -> Vector:
+    """Centre du but que l'on défend, sur la ligne"""
+    return (FIELD_DEPTH / 2 - SPACE_BEFORE_LINE_SIDE, 0.0)
+
+
+def enemy_goal() -> Vector:
+    """Centre du but que l'on attaque, sur la ligne"""
+    return (-(FIELD_DEPTH / 2 - SPACE_BEFORE_LINE_SIDE), 0.0)
+
+
+def _in_goal_area(point: Vector, goal_x: float) -> bool:
+    # the rounded corners of the goal area are ignored
+    return (
+        abs(point[0]) >= abs(goal_x) - ENBUT_DEPTH
+        and point[0] * goal_x > 0
+        and abs(point[1]) <= ENBUT_WIDTH / 2
+    )
+
+
+def in_own_goal_area(point: Vector) -> bool:
+    """`point` est dans la surface de réparation devant notre but (ou derrière la ligne de but)"""
+    return _in_goal_area(point, own_goal()[0])
+
+
+def in_enemy_goal_area(point: Vector) -> bool:
+    """`point` est dans la surface de réparation devant le but adverse (ou derrière la ligne de but)"""
+    return _in_goal_area(point, enemy_goal()[0])
+
+
+# Entrée et actions
+
+
+@dataclass(frozen=True)
+class Input:
+    """Le dictionnaire reçu par `update`, avec des attributs : `Input.from_dict(data)`"""
+
+    my_position: Vector
+    my_orientation: float
+    friend_position: Vector
+    enemy1_position: Vector
+    enemy2_position: Vector
+    ball_position: Vector
+    orientation_error: float
+    # 1 ou 2
+    robot_number: int
+    profile: Profile
+
+    @staticmethod
+    def from_dict(data: dict) -> Input:
+        return Input(**{f.name: data[f.name] for f in fields(Input)})
+
+
+@dataclass
+class PositionAction:
+    """Action du mode "position" (par défaut), `target_orientation` entre 0 et 360.
+    Les valeurs sont transmises telles quelles : le simulateur signale celles qui sont hors limites"""
+
+    target_position: Vector
+    power: int = 255
+    target_orientation: float = 0.0
+    kick: bool | float = False
+    kick_angle: float = 0.0
+
+    def to_dict(self) -> dict:
+        return {
+            "target_position": self.target_position,
+            "power": self.power,
+            "target_orientation": self.target_orientation,
+            "kick": self.kick,
+            "kick_angle": self.kick_angle,
+        }
+
+
+@dataclass
+class VelocityAction:
+    """Action des modes "velocity_robot" (velocity en (avant, gauche)) et "velocity_field" """
+
+    velocity: Vector
+    angular_velocity: float = 0.0
+    kick: bool | float = False
+    kick_angle: float = 0.0
+
+    def to_dict(self) -> dict:
+        return {
+            "velocity": self.velocity,
+            "angular_velocity": self.angular_velocity,
+            "kick": self.kick,
+            "kick_angle": self.kick_angle,
+        }
+
+
+@dataclass
+class MotorsAction:
+    """Action du mode "motors" : puissances entre -255 et 255, dans l'ordre de MOTOR_ANGLES"""
+
+    motors: list[int] = field(default_factory=lambda: [0] * NB_MOTORS)
+    kick: bool | float = False
+    kick_angle: float = 0.0
+
+    def to_dict(self) -> dict:
+        return {
+            "motors": self.motors,
+            "kick": self.kick,
+            "kick_angle": self.kick_angle,
+        }
@@ -0,0 +1,139 @@
+# Types du module `simulator` fourni par le simulateur, pour la complétion dans l'éditeur.
+# Ajoutez ce dossier aux chemins de recherche de votre éditeur (par exemple `python.analysis.extraPaths`).
+
+from dataclasses import dataclass
+
+Vector = tuple[float, float]
+
+# Constantes de infos.rs, en cm
+FIELD_WIDTH: float
+FIELD_DEPTH: float
+SPACE_BEFORE_LINE_SIDE: float
+GOAL_WIDTH: float
+ENBUT_DEPTH: float
+ENBUT_WIDTH: float
+ROBOT_RADIUS: float
+BALL_RADIUS: float
+DISTANCE_MIN_KICKER_BALL: float
+# cm/s, plus grande `max_speed` autorisée par le règlement pour un profil ; les valeurs du profil chargé sont dans `Profile`
+RULE_MAX_ROBOT_SPEED: float
+# degrés
+MAX_KICK_ANGLE: float
+NB_MOTORS: int
+# degrés, par rapport à l'avant du robot
+MOTOR_ANGLES: list[float]
+
+def add(a: Vector, b: Vector) -> Vector: ...
+def sub(a: Vector, b: Vector) -> Vector: ...
+def scale(v: Vector, k: float) -> Vector: ...
+def norm(v: Vector) -> float: ...
+def distance(a: Vector, b: Vector) -> float: ...
+def normalize(v: Vector) -> Vector: ...
+def rotate(v: Vector, angle: float) -> Vector: ...
+def normalize_angle(angle: float) -> float: ...
+def angle_of(v: Vector) -> float: ...
+def angle_to_target(position: Vector, target: Vector) -> float: ...
+def to_robot_frame(v: Vector, orientation: float) -> Vector: ...
+def to_field_frame(v: Vector, orientation: float) -> Vector: ...
+def own_goal() -> Vector: ...
+def enemy_goal() -> Vector: ...
+def in_own_goal_area(point: Vector) -> bool: ...
+def in_enemy_goal_area(point: Vector) -> bool: ...
+
+@dataclass(frozen=True)
+class Input:
+    my_position: Vector
+    my_orientation: float
+    friend_position: Vector
+    enemy1_position: Vector
+    enemy2_position: Vector
+    ball_position: Vector
+    # degrés restant à tourner vers target_orientation, 0 hors du mode "position"
+    orientation_error: float
+    # 1 ou 2
+    robot_number: int
+    profile: Profile
+    @staticmethod
+    def from_dict(data: dict) -> Input: ...
+
+@dataclass
+class PositionAction:
+    target_position: Vector
+    power: int = 255
+    target_orientation: float = 0.0
+    kick: bool | float = False
+    kick_angle: float = 0.0
+    def to_dict(self) -> dict: ...
+
+@dataclass
+class VelocityAction:
+    velocity: Vector
+    angular_velocity: float = 0.0
+    kick: bool | float = False
+    kick_angle: float = 0.0
+    def to_dict(self) -> dict: ...
+
+@dataclass
+class MotorsAction:
+    motors: list[int] = ...
+    kick: bool | float = False
+    kick_angle: float = 0.0
+    def to_dict(self) -> dict: ...
+
+# API à base de classes : le script définit une classe `Robot` avec `setup()` et `update(state) -> Action`.
+# Un nom d'attribut mal orthographié lève une AttributeError qui propose le nom le plus proche.
+
+# Profil matériel des robots de l'équipe (`ROBOT_PROFILE`), dans `state.profile` ou `data["profile"]`
+class Profile:
+    name: str
+    # cm
+    radius: float
+    # kg
+    mass: float
+    kick_power: float
+    # en ticks
+    kick_cooldown: int
+    # degrés
+    kick_angle_range: float
+    # cm/s, vitesse maximale demandable en mode vitesse
+    max_speed: float
+    # cm/s, vitesse au-delà de laquelle le robot est freiné, None si le profil n'en donne pas
+    speed_limit: float | None
+
+class State:
+    my_position: Vector
+    my_orientation: float
+    friend_position: Vector
+    enemy1_position: Vector
+    enemy2_position: Vector
+    ball_position: Vector
+    # degrés restant à tourner vers target_orientation, 0 hors du mode "position"
+    orientation_error: float
+    # 1 ou 2
+    robot_number: int
+    profile: Profile
+
+class Action:
+    # mode "position"
+    target_position: Vector | None
+    power: int | None
+    target_orientation: float | None
+    # modes "velocity_robot" et "velocity_field"
+    velocity: Vector | None
+    angular_velocity: float | None
+    # mode "motors"
+    motors: list[int] | None
+    kick: float
+    kick_angle: float | None
+    def __init__(
+        self,
+        *,
+        target_position: Vector | None = None,
+        power: int | None = None,
+        target_orientation: float | None = None,
+        velocity: Vector | None = None,
+        angular_velocity: float | None = None,
+        motors: list[int] | None = None,
+        kick: bool | float = False,
+        kick_angle: float | None = None,
+    ) -> None: ...
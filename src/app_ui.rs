@@ -1,21 +1,54 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::path::Path;
+use std::rc::Rc;
 
-use nalgebra::vector;
 use rerun::external::egui::{Color32, RichText};
-use rerun::external::re_viewer::App;
-use rerun::{Boxes2D, LineStrips2D, RecordingStream, TextLog, TextLogLevel};
+use rerun::external::re_log_types::{StoreId, TimeReal};
+use rerun::external::re_viewer::{App, ViewerEventKind};
+use rerun::external::re_viewer_context::{
+    PlayState, RecordingOrTable, SystemCommand, SystemCommandSender as _, TimeControlCommand,
+};
+use rerun::{
+    Arrows2D, Boxes2D, Clear, DynamicArchetype, LineStrips2D, RecordingStream, TextLog,
+    TextLogLevel,
+};
 use rerun::{Color, Points2D, Radius};
+use serde::{Deserialize, Serialize};
 
 use rerun::external::{arrow, eframe, egui, re_crash_handler, re_grpc_server, re_log, re_viewer};
 
-use crate::player_action::{CodeValidationError, PlayerCode, PlayerCodePython, validate_path};
-use crate::robot::RobotBuilder;
-use crate::{infos, robot::RobotHandler, simulator::Simulator};
+use crate::bots::{BUILTIN_PREFIX, BuiltinBot, PlayerCodeBuiltin};
+use crate::field_interaction::FieldInteraction;
+use crate::game_event::{GameEvent, TimedEvent};
+use crate::manual_control::ManualControl;
+use crate::match_config::{DEFAULT_TEAM_COLORS, MatchConfig, MatchSettings, TeamEntry};
+use crate::overlays::Overlays;
+use crate::player_action::{
+    CodeValidationError, Movement, PlayerCode, PlayerCodePython, validate_path,
+};
+use crate::ratings::{self, RatingLedger};
+use crate::recording::{self, MatchMetadata};
+use crate::replay::{self, Replay};
+use crate::scenario_editor::ScenarioEditor;
+use crate::{
+    headless, infos,
+    robot::RobotHandler,
+    simulator::{Simulator, front_angle},
+};
 
 const PANEL_WIDTH: f32 = 300.0;
 
-const APP_ID: &str = "simulator";
+pub const APP_ID: &str = "simulator";
+
+/// Durée d'affichage de l'éclair quand un robot tire
+const KICK_FLASH_TICKS: u64 = 10;
+/// Longueur de la flèche de vitesse demandée : la distance parcourue en ce temps (s)
+const INTENT_VELOCITY_ARROW_TIME: f32 = 0.25;
+
+const PREFERENCES_KEY: &str = "simulator_preferences";
+const MAX_RECENT_SCRIPTS: usize = 10;
 
 #[derive(Debug)]
 pub enum AppState {
@@ -43,6 +76,7 @@ impl AppState {
 
 impl eframe::App for SimulatorApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, PREFERENCES_KEY, &self.preferences);
         self.rerun_app.save(storage);
     }
 
@@ -50,18 +84,45 @@ impl eframe::App for SimulatorApp {
         let signal = match &mut self.state {
             Configuration(config) => {
                 // We are still in configuration mode
-                config.ui_config(&ctx, &mut self.rerun_app, &mut self.rec)
+                config.ui_config(
+                    &ctx,
+                    &mut self.rerun_app,
+                    &mut self.rec,
+                    &mut self.preferences,
+                )
             }
             Running(running) => {
-                running.tick(&mut self.rec);
+                running.manual_control.apply(ctx, &mut running.simulation);
+                let moved_by_hand =
+                    running
+                        .field_interaction
+                        .viewer(ctx, &self.rerun_app, &mut running.simulation);
+                if !running.paused {
+                    running.tick(&mut self.rec);
+                } else if moved_by_hand {
+                    running.draw(&mut self.rec);
+                }
+                if running.simulation.is_finished() && running.rating_message.is_none() {
+                    running.rating_message =
+                        Some(self.preferences.record_rating(&running.simulation));
+                }
                 running.ui_running(&ctx, &mut self.rerun_app, &mut self.rec);
                 // Show rerun app
                 self.rerun_app.update(ctx, frame);
                 None
             }
             ReRunning(re_running) => {
-                re_running.ui_re_running(&ctx, &mut self.rerun_app, &mut self.rec);
+                let fork = re_running.ui_re_running(
+                    &ctx,
+                    &mut self.rerun_app,
+                    &mut self.rec,
+                    &self.preferences,
+                    &self.viewer_cursor,
+                );
                 self.rerun_app.update(ctx, frame);
+                if let Some(running) = fork {
+                    self.fork(running);
+                }
                 None
             } // | AppState::ReRunning => {
               //     if self.app_state == AppState::Running {
@@ -83,7 +144,14 @@ impl eframe::App for SimulatorApp {
             && let Configuration(conf) = &mut self.state
         {
             match signal {
-                AppStateMutateSignal::ToRun => self.state = conf.run(),
+                AppStateMutateSignal::ToRun => {
+                    self.preferences.remember_match(conf);
+                    self.state = conf.run();
+                    if let Running(running) = &mut self.state {
+                        running.recording_message =
+                            self.preferences.start_recording(&self.rec, running);
+                    }
+                }
                 AppStateMutateSignal::ToReRun => self.state = conf.re_run(),
             }
             self.state.init(&mut self.rec);
@@ -91,9 +159,37 @@ impl eframe::App for SimulatorApp {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct AppConfiguration {
     pub team_config: [TeamConfigState; 2],
+    /// Couleur de chaque équipe dans le viewer, en RGB
+    pub team_colors: [[u8; 3]; 2],
+    pub settings: MatchSettings,
+    pub config_path: String,
+    pub config_message: Option<String>,
+    /// Lance le match dès que les deux équipes sont valides (configuration donnée en ligne de commande)
+    pub autostart: bool,
+    /// Classement Elo affiché, chargé à l'ouverture de la section
+    pub ledger: Option<Result<RatingLedger, String>>,
+    /// Équipe dont l'historique est affiché sous le classement
+    pub history_team: Option<String>,
+    pub scenario_editor: ScenarioEditor,
+}
+
+impl Default for AppConfiguration {
+    fn default() -> Self {
+        AppConfiguration {
+            team_config: Default::default(),
+            team_colors: DEFAULT_TEAM_COLORS,
+            settings: Default::default(),
+            config_path: Default::default(),
+            config_message: None,
+            autostart: false,
+            ledger: None,
+            history_team: None,
+            scenario_editor: ScenarioEditor::default(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -114,9 +210,202 @@ impl Default for TeamConfigState {
     }
 }
 
+impl TeamConfigState {
+    pub fn from_path(path: &str) -> Self {
+        match validate_path(path) {
+            Ok(valid) => TeamConfigState::Valid(valid),
+            Err(CodeValidationError::Empty) => TeamConfigState::Config {
+                path: path.to_owned(),
+                err_message: None,
+            },
+            Err(err) => TeamConfigState::Config {
+                path: path.to_owned(),
+                err_message: Some(err),
+            },
+        }
+    }
+
+    /// Chemin d'une session précédente : le code python n'est exécuté que quand l'utilisateur le charge
+    pub fn restored(path: &str) -> Self {
+        if path.starts_with(BUILTIN_PREFIX) {
+            return TeamConfigState::from_path(path);
+        }
+        TeamConfigState::Config {
+            path: path.to_owned(),
+            err_message: None,
+        }
+    }
+
+    /// Chemin du code, qu'il soit valide ou non
+    pub fn path(&self) -> &str {
+        match self {
+            TeamConfigState::Config { path, .. } => path,
+            TeamConfigState::Valid(code) => code.path(),
+        }
+    }
+}
+
+/// Ce qui est gardé d'une session à l'autre grâce au stockage d'eframe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserPreferences {
+    /// Du plus récent au plus ancien
+    pub recent_scripts: Vec<String>,
+    pub last_team_paths: [String; 2],
+    pub last_team_colors: Option<[[u8; 3]; 2]>,
+    pub last_settings: MatchSettings,
+    pub last_config_path: String,
+    pub show_match_settings: bool,
+    /// Ajoute chaque match terminé au classement Elo
+    pub record_ratings: bool,
+    /// Registre du classement Elo, `ratings::DEFAULT_LEDGER_PATH` si vide
+    pub ledger_path: String,
+    pub show_ratings: bool,
+    /// Enregistre chaque match dans un fichier .rrd
+    pub record_matches: bool,
+    /// Dossier des enregistrements, `recording::DEFAULT_RECORDINGS_DIR` si vide
+    pub recordings_dir: String,
+    pub show_recording: bool,
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        UserPreferences {
+            recent_scripts: Vec::new(),
+            last_team_paths: Default::default(),
+            last_team_colors: None,
+            last_settings: MatchSettings::default(),
+            last_config_path: String::new(),
+            show_match_settings: false,
+            record_ratings: false,
+            ledger_path: String::new(),
+            show_ratings: false,
+            record_matches: true,
+            recordings_dir: String::new(),
+            show_recording: false,
+        }
+    }
+}
+
+impl UserPreferences {
+    pub fn add_recent_script(&mut self, path: &str) {
+        self.recent_scripts.retain(|recent| recent != path);
+        self.recent_scripts.insert(0, path.to_owned());
+        self.recent_scripts.truncate(MAX_RECENT_SCRIPTS);
+    }
+
+    fn remember_match(&mut self, config: &AppConfiguration) {
+        self.last_team_paths = [
+            config.team_config[0].path().to_owned(),
+            config.team_config[1].path().to_owned(),
+        ];
+        for team in &config.team_config {
+            self.add_recent_script(team.path());
+        }
+        self.last_team_colors = Some(config.team_colors);
+        self.last_settings = config.settings.clone();
+        self.last_config_path = config.config_path.clone();
+    }
+
+    pub fn ledger_path(&self) -> &str {
+        if self.ledger_path.is_empty() {
+            ratings::DEFAULT_LEDGER_PATH
+        } else {
+            &self.ledger_path
+        }
+    }
+
+    pub fn recordings_dir(&self) -> &str {
+        if self.recordings_dir.is_empty() {
+            recording::DEFAULT_RECORDINGS_DIR
+        } else {
+            &self.recordings_dir
+        }
+    }
+
+    /// Ajoute les sinks du match à `rec` : le viewer intégré et, si l'utilisateur l'a demandé, un fichier .rrd
+    /// accompagné du fichier que le mode « revoir » sait relire.
+    /// Renvoie le message à afficher pendant le match (vide si rien n'est enregistré).
+    fn start_recording(&self, rec: &RecordingStream, running: &mut AppRunning) -> String {
+        if !self.record_matches {
+            return String::new();
+        }
+        let dir = Path::new(self.recordings_dir());
+        let metadata = &running.metadata;
+        let team_names = [
+            metadata.team_names[0].as_str(),
+            metadata.team_names[1].as_str(),
+        ];
+        let path = recording::recording_path(dir, &metadata.date, team_names);
+        let file_sink = std::fs::create_dir_all(dir)
+            .map_err(|err| format!("{err}"))
+            .and_then(|()| rerun::sink::FileSink::new(&path).map_err(|err| format!("{err}")));
+        match file_sink {
+            Ok(file_sink) => {
+                rec.set_sinks((rerun::sink::GrpcSink::default(), file_sink));
+                running.log_snapshots = true;
+                format!("enregistré dans {}", path.display())
+            }
+            Err(err) => format!("impossible d'enregistrer dans {} : {}", path.display(), err),
+        }
+    }
+
+    /// Ajoute un match terminé au classement Elo si l'utilisateur l'a demandé.
+    /// Renvoie le message à afficher à la fin du match (vide si rien n'est enregistré).
+    fn record_rating(&self, simulation: &Simulator) -> String {
+        if !self.record_ratings {
+            return String::new();
+        }
+        let scores = [
+            simulation.game_referee.score_team_left,
+            simulation.game_referee.score_team_right,
+        ];
+        let [name1, name2] = &simulation.script_team_names;
+        let entry = RatingLedger::load(Path::new(self.ledger_path())).and_then(|mut ledger| {
+            ledger
+                .record_match(
+                    [name1.as_str(), name2.as_str()],
+                    simulation.team_scripts(),
+                    scores,
+                )
+                .cloned()
+        });
+        match entry {
+            Ok(entry) => (0..2)
+                .map(|side| {
+                    format!(
+                        "{} : {:.0} -> {:.0}",
+                        entry.teams[side], entry.ratings_before[side], entry.ratings_after[side]
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(err) => format!("{err}"),
+        }
+    }
+}
+
 pub struct AppRunning {
     pub simulation: Simulator,
     pub robot_handle_to_color: HashMap<RobotHandler, Color>,
+    pub events: crossbeam::channel::Receiver<TimedEvent>,
+    pub overlays: Overlays,
+    /// Tick jusqu'auquel l'éclair du tir reste affiché, par robot (nom du robot)
+    pub kick_flash_until: HashMap<String, u64>,
+    /// Résultat de l'enregistrement dans le classement Elo, une fois le match terminé
+    pub rating_message: Option<String>,
+    pub stats_message: Option<String>,
+    /// Dernières métadonnées envoyées, renvoyées quand le score change ou à la fin du match
+    pub metadata: MatchMetadata,
+    /// Fichier .rrd du match, ou erreur
+    pub recording_message: String,
+    pub team_colors: [[u8; 3]; 2],
+    /// Ajoute à l'enregistrement les instantanés pour reprendre le match, voir `replay::log_snapshot`
+    pub log_snapshots: bool,
+    pub manual_control: ManualControl,
+    /// Le match ne tourne plus, mais on peut toujours déplacer la balle et les robots
+    pub paused: bool,
+    pub field_interaction: FieldInteraction,
 }
 
 impl Debug for AppRunning {
@@ -127,7 +416,21 @@ impl Debug for AppRunning {
     }
 }
 
-pub struct AppReRunning {}
+#[derive(Default)]
+pub struct AppReRunning {
+    pub replay: Option<Replay>,
+    /// Enregistrement du viewer où le match revu a été envoyé
+    store_id: Option<StoreId>,
+    /// Le curseur du viewer a été placé sur la timeline des ticks
+    cursor_ready: bool,
+    /// Vitesse de lecture du viewer
+    pub speed: f32,
+    /// Affiche aussi les touches, chocs et murs dans la liste des évènements
+    pub show_all_events: bool,
+    /// Scripts utilisés pour reprendre la simulation, modifiables
+    pub fork_scripts: [String; 2],
+    pub message: Option<String>,
+}
 
 impl Debug for AppReRunning {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -167,40 +470,59 @@ impl AppRunning {
                         ))
                         .size(60.0),
                     ));
-
-                    ui.add_space(10.0);
-                    let first_team_name = self.simulation.player_code.keys().next().unwrap();
-                    let first_robot = RobotHandler::new(first_team_name, 1);
-                    if ui.button("Move Robot A1 Right").clicked() {
-                        self.simulation.rigid_body_set
-                            [self.simulation.robot_to_rigid_body_handle[&first_robot]]
-                            .apply_impulse(vector![100.0, 0.0], true);
-                    }
-                    if ui.button("Move Robot A1 Left").clicked() {
-                        self.simulation.rigid_body_set
-                            [self.simulation.robot_to_rigid_body_handle[&first_robot]]
-                            .apply_impulse(vector![-100.0, 0.0], true);
-                    }
-                    if ui.button("Move Robot A1 Up").clicked() {
-                        self.simulation.rigid_body_set
-                            [self.simulation.robot_to_rigid_body_handle[&first_robot]]
-                            .apply_impulse(vector![0.0, -100.0], true);
-                    }
-                    if ui.button("Move Robot A1 Down").clicked() {
-                        self.simulation.rigid_body_set
-                            [self.simulation.robot_to_rigid_body_handle[&first_robot]]
-                            .apply_impulse(vector![0.0, 100.0], true);
+                    let elapsed_time = self.simulation.elapsed_time() as u64;
+                    ui.label(format!("{}:{:02}", elapsed_time / 60, elapsed_time % 60));
+                    if self.simulation.is_finished() {
+                        ui.strong("Match terminé");
+                    } else if ui
+                        .button("Terminer le match")
+                        .on_hover_text(
+                            "Arrête le match avec le score actuel. Il est ajouté au classement Elo si l'option est cochée.",
+                        )
+                        .clicked()
+                    {
+                        self.simulation.stop();
+                        // no more ticks will mark the match as finished in the recording
+                        self.update_metadata(rec);
                     }
-                    if ui.button("Turn Robot A1 Left").clicked() {
-                        self.simulation.rigid_body_set
-                            [self.simulation.robot_to_rigid_body_handle[&first_robot]]
-                            .apply_torque_impulse(-100.0, true);
+                    if let Some(rating_message) = &self.rating_message
+                        && !rating_message.is_empty()
+                    {
+                        ui.label(format!("Classement Elo :\n{}", rating_message));
                     }
-                    if ui.button("Turn Robot A1 Right").clicked() {
-                        self.simulation.rigid_body_set
-                            [self.simulation.robot_to_rigid_body_handle[&first_robot]]
-                            .apply_torque_impulse(100.0, true);
+                    if !self.recording_message.is_empty() {
+                        ui.label(&self.recording_message);
                     }
+
+                    ui.add_space(10.0);
+                    egui::CollapsingHeader::new("Affichage")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            ui.checkbox(&mut self.overlays.show_trails, "traînées");
+                            ui.checkbox(
+                                &mut self.overlays.show_heatmap,
+                                "carte de chaleur des équipes",
+                            );
+                            ui.checkbox(
+                                &mut self.overlays.show_ball_velocity,
+                                "vitesse de la balle",
+                            );
+                        });
+
+                    egui::CollapsingHeader::new("Statistiques")
+                        .default_open(true)
+                        .show(ui, |ui| self.ui_stats(ui));
+
+                    egui::CollapsingHeader::new("Pilotage manuel")
+                        .show(ui, |ui| self.manual_control.ui(ui, &self.simulation));
+
+                    egui::CollapsingHeader::new("Déplacer à la souris").show(ui, |ui| {
+                        ui.checkbox(&mut self.paused, "pause");
+                        ui.checkbox(
+                            &mut self.field_interaction.enabled,
+                            "saisir la balle ou un robot dans le viewer",
+                        );
+                    });
                 });
                 ui.separator();
                 // if let Some(entity_database) = self.rerun_app.recording_db() {
@@ -249,13 +571,224 @@ impl AppRunning {
     }
 }
 
+impl AppRunning {
+    fn ui_stats(&mut self, ui: &mut egui::Ui) {
+        let stats = &self.simulation.stats;
+        let team_names = self.simulation.team_names();
+        let possession = stats.possession_share();
+        egui::Grid::new("team_stats").striped(true).show(ui, |ui| {
+            ui.label("");
+            ui.strong(team_names[0]);
+            ui.strong(team_names[1]);
+            ui.end_row();
+            ui.label("possession");
+            ui.label(format!("{:.0} %", possession[0] * 100.0));
+            ui.label(format!("{:.0} %", possession[1] * 100.0));
+            ui.end_row();
+            ui.label("tirs cadrés");
+            ui.label(format!("{}", stats.teams[0].shots_on_goal));
+            ui.label(format!("{}", stats.teams[1].shots_on_goal));
+            ui.end_row();
+            ui.label("tirs réussis / tentés");
+            for team in &stats.teams {
+                ui.label(format!(
+                    "{} / {}",
+                    team.kicks_effective, team.kicks_attempted
+                ));
+            }
+            ui.end_row();
+        });
+
+        ui.add_space(5.0);
+        egui::Grid::new("robot_stats").striped(true).show(ui, |ui| {
+            for title in ["robot", "distance (m)", "touches", "chocs"] {
+                ui.strong(title);
+            }
+            ui.end_row();
+            for robot in &stats.robots {
+                ui.label(&robot.name);
+                ui.label(format!("{:.1}", robot.distance / 100.0));
+                ui.label(format!("{}", robot.touches));
+                ui.label(format!("{}", robot.robot_collisions));
+                ui.end_row();
+            }
+        });
+
+        let elapsed_time = self.simulation.elapsed_time().max(f32::EPSILON);
+        ui.label(format!(
+            "balle dans les tiers gauche / central / droit : {:.0} / {:.0} / {:.0} %",
+            stats.ball_in_thirds[0] / elapsed_time * 100.0,
+            stats.ball_in_thirds[1] / elapsed_time * 100.0,
+            stats.ball_in_thirds[2] / elapsed_time * 100.0
+        ));
+        ui.label(format!("chocs entre robots : {}", stats.robot_collisions));
+
+        if ui.button("Exporter les statistiques").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("json", &["json"])
+                .set_file_name("statistiques.json")
+                .save_file()
+        {
+            let export = serde_json::to_string_pretty(stats)
+                .map_err(|err| format!("{err}"))
+                .and_then(|json| std::fs::write(&path, json).map_err(|err| format!("{err}")));
+            self.stats_message = Some(match export {
+                Ok(()) => format!("statistiques enregistrées dans {}", path.display()),
+                Err(err) => format!("Impossible d'enregistrer les statistiques : {err}"),
+            });
+        }
+        if let Some(stats_message) = &self.stats_message {
+            ui.label(stats_message);
+        }
+    }
+}
+
+impl AppRunning {
+    /// Chaque évènement est écrit dans le journal, et ceux qui ont lieu quelque part
+    /// sont aussi marqués sur le terrain sous `events/<type>`
+    fn log_events(&mut self, rec: &mut RecordingStream) {
+        let events: Vec<TimedEvent> = self.events.try_iter().collect();
+        for timed_event in events {
+            let description = format!("{}", timed_event.event);
+            let team_of = |robot: &str| {
+                self.simulation
+                    .robots
+                    .iter()
+                    .find(|robot_handle| robot_handle.to_string() == robot)
+                    .map(|robot_handle| robot_handle.team_name().to_owned())
+            };
+            rec.log(
+                format!("events/{}", timed_event.event.kind()),
+                &event_archetype(&timed_event, team_of),
+            )
+            .unwrap();
+            let level = match timed_event.event {
+                GameEvent::Goal { .. } => TextLogLevel::WARN,
+                _ => TextLogLevel::INFO,
+            };
+            rec.log(
+                "simulator_logs/events",
+                &TextLog::new(description.clone()).with_level(level),
+            )
+            .unwrap();
+            if let GameEvent::Kick {
+                robot,
+                effective,
+                position: (x, y),
+                ..
+            } = &timed_event.event
+            {
+                let color = if *effective {
+                    Color::from_rgb(255, 255, 0)
+                } else {
+                    Color::from_rgb(128, 128, 128)
+                };
+                rec.log(
+                    format!("Robot_{robot}/intent/kick"),
+                    &Points2D::new([[*x, *y]])
+                        .with_colors([color])
+                        .with_radii([Radius::new_scene_units(3.0)])
+                        .with_draw_order(70.0),
+                )
+                .unwrap();
+                self.kick_flash_until
+                    .insert(robot.clone(), timed_event.tick + KICK_FLASH_TICKS);
+            }
+            if let Some((x, y)) = timed_event.event.position() {
+                rec.log(
+                    format!("events/{}", timed_event.event.kind()),
+                    &Points2D::new([[x, y]])
+                        .with_labels([description])
+                        .with_radii([Radius::new_scene_units(1.5)])
+                        .with_colors([Color::from_rgb(255, 255, 0)]),
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+/// Nom de l'archétype des évènements dans l'enregistrement, voir `event_archetype`
+pub const EVENT_ARCHETYPE: &str = "simulator.GameEvent";
+
+/// Champs d'un évènement, pour les lire dans la vue tableau du viewer ou depuis un fichier .rrd :
+/// `kind`, `tick`, `time`, et selon le type `position`, `robot`, `team`, `wall`, `power`, `effective`, `score`
+fn event_archetype(
+    timed_event: &TimedEvent,
+    team_of: impl Fn(&str) -> Option<String>,
+) -> DynamicArchetype {
+    use arrow::array::{
+        ArrayRef, BooleanArray, Float32Array, StringArray, UInt32Array, UInt64Array,
+    };
+    use std::sync::Arc;
+
+    let strings = |values: Vec<String>| -> ArrayRef { Arc::new(StringArray::from(values)) };
+    let event = &timed_event.event;
+    let mut archetype = DynamicArchetype::new(EVENT_ARCHETYPE)
+        .with_component_from_data("kind", strings(vec![event.kind().to_owned()]))
+        .with_component_from_data("tick", Arc::new(UInt64Array::from(vec![timed_event.tick])))
+        .with_component_from_data("time", Arc::new(Float32Array::from(vec![timed_event.time])));
+    if let Some((x, y)) = event.position() {
+        archetype = archetype
+            .with_component_from_data("position", Arc::new(Float32Array::from(vec![x, y])));
+    }
+
+    let robots: Vec<String> = match event {
+        GameEvent::Touch { robot, .. }
+        | GameEvent::Kick { robot, .. }
+        | GameEvent::WallHit {
+            robot: Some(robot), ..
+        } => vec![robot.clone()],
+        GameEvent::RobotCollision { robot1, robot2, .. } => vec![robot1.clone(), robot2.clone()],
+        _ => Vec::new(),
+    };
+    if !robots.is_empty() {
+        let teams = robots
+            .iter()
+            .map(|robot| team_of(robot).unwrap_or_default())
+            .collect();
+        archetype = archetype
+            .with_component_from_data("robot", strings(robots))
+            .with_component_from_data("team", strings(teams));
+    }
+
+    match event {
+        GameEvent::WallHit { wall, .. } => {
+            archetype =
+                archetype.with_component_from_data("wall", strings(vec![format!("{:?}", wall)]));
+        }
+        GameEvent::Kick {
+            power, effective, ..
+        } => {
+            archetype = archetype
+                .with_component_from_data("power", Arc::new(Float32Array::from(vec![*power])))
+                .with_component_from_data(
+                    "effective",
+                    Arc::new(BooleanArray::from(vec![*effective])),
+                );
+        }
+        GameEvent::Goal { team, score, .. } => {
+            archetype = archetype
+                .with_component_from_data("team", strings(vec![team.clone()]))
+                .with_component_from_data("score", Arc::new(UInt32Array::from(score.to_vec())));
+        }
+        _ => {}
+    }
+    archetype
+}
+
+/// Évènements qu'on peut atteindre avec les boutons précédent / suivant : (type, nom affiché)
+const REPLAY_JUMPS: [(&str, &str); 2] = [("goal", "but"), ("kick", "tir")];
+
 impl AppReRunning {
     pub fn ui_re_running(
         &mut self,
         ctx: &egui::Context,
         rerun_app: &mut re_viewer::App,
         rec: &mut RecordingStream,
-    ) {
+        preferences: &UserPreferences,
+        viewer_cursor: &ViewerCursor,
+    ) -> Option<AppRunning> {
         egui::SidePanel::left("SIMULATOR - REVOIR")
             .default_width(PANEL_WIDTH)
             .show(ctx, |ui| {
@@ -264,8 +797,378 @@ impl AppReRunning {
                     ui.strong("SIMULATOR - revoir");
                 });
                 ui.separator();
-                ui.label("Sélectionnez un fichier depuis le menu rerun ci-contre (logo R).")
+                if ui.button("Ouvrir un match enregistré…").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("match", &["rrd"])
+                        .set_directory(preferences.recordings_dir())
+                        .pick_file()
+                {
+                    self.open(&path, rerun_app, rec);
+                }
+                if let Some(message) = &self.message {
+                    ui.label(RichText::new(message).color(Color32::ORANGE));
+                }
+                if self.replay.is_none() {
+                    ui.label("Choisissez un fichier .rrd enregistré par le simulateur : le viewer ci-contre l'affiche, et ce panneau permet de s'y déplacer de but en but ou de reprendre le match.");
+                    return None;
+                }
+                self.prepare_viewer(rerun_app);
+                let cursor = self
+                    .store_id
+                    .as_ref()
+                    .map(|store_id| viewer_cursor.state(store_id))
+                    .unwrap_or_default();
+                let tick = cursor.tick.unwrap_or(0);
+                let fork = egui::CollapsingHeader::new("Reprendre la simulation")
+                    .show(ui, |ui| self.ui_fork(ui, rerun_app, tick))
+                    .body_returned
+                    .flatten();
+                ui.separator();
+                self.ui_replay(ui, rerun_app, tick, cursor.playing);
+                fork
+            })
+            .inner
+    }
+
+    /// Reprend le match au dernier instantané avant le moment affiché, avec les scripts choisis
+    fn ui_fork(
+        &mut self,
+        ui: &mut egui::Ui,
+        rerun_app: &re_viewer::App,
+        tick: u64,
+    ) -> Option<AppRunning> {
+        let replay = self.replay.as_ref()?;
+        let Some(config) = &replay.config else {
+            ui.label(
+                "Ce match a été enregistré sans sa configuration, il ne peut pas être repris.",
+            );
+            return None;
+        };
+        let Some(snapshot) = replay.snapshot_before(tick) else {
+            if replay.snapshots.is_empty() {
+                ui.label("Ce match a été enregistré sans instantanés, il ne peut pas être repris.");
+            } else {
+                ui.label(
+                    "Pas d'instantané avant ce moment : relancez plutôt le match depuis le début.",
+                );
+            }
+            return None;
+        };
+        // the match resumes at the snapshot, up to `SNAPSHOT_PERIOD_TICKS` before the cursor
+        let minutes_seconds = |tick: u64| {
+            let time = (tick as f32 * replay.tick_duration) as u64;
+            format!("{}:{:02}", time / 60, time % 60)
+        };
+        let fork_time = minutes_seconds(snapshot.tick_nb);
+        if snapshot.tick_nb < tick {
+            ui.label(format!(
+                "Le dernier instantané est à {}, {:.1} s avant le moment affiché ({}) : le match reprendra à {}.",
+                fork_time,
+                (tick - snapshot.tick_nb) as f32 * replay.tick_duration,
+                minutes_seconds(tick),
+                fork_time
+            ));
+            if ui.button(format!("Aller à {fork_time}")).clicked() {
+                self.send_time_commands(rerun_app, seek_commands(snapshot.tick_nb));
+            }
+        }
+        ui.label("Les scripts peuvent être modifiés ou remplacés avant de reprendre.");
+        for (n, script) in self.fork_scripts.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("équipe {} :", n + 1));
+                ui.text_edit_singleline(script);
+                if ui.button("…").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("python", &["py"])
+                        .pick_file()
+                {
+                    *script = path.display().to_string();
+                }
+            });
+        }
+        if !ui.button(format!("Reprendre à {fork_time}")).clicked() {
+            return None;
+        }
+
+        let mut config = config.clone();
+        for (team_entry, script) in config.teams.iter_mut().zip(&self.fork_scripts) {
+            team_entry.script = script.clone();
+        }
+        match headless::load_teams(&config) {
+            Ok([team1, team2]) => {
+                let mut simulation = Simulator::from_teams(team1, team2, config.settings.clone());
+                match simulation.restore(snapshot.clone()) {
+                    Ok(()) => Some(AppRunning::new(simulation, config.team_colors())),
+                    Err(err) => {
+                        self.message = Some(format!("{err}"));
+                        None
+                    }
+                }
+            }
+            Err(err) => {
+                self.message = Some(format!("{err}"));
+                None
+            }
+        }
+    }
+
+    /// Le match est envoyé au viewer dans un enregistrement à lui,
+    /// pour ne pas se mélanger avec le dernier match joué ni avec un autre match revu
+    fn open(&mut self, path: &Path, rerun_app: &re_viewer::App, rec: &mut RecordingStream) {
+        let mut replay = match Replay::load(path) {
+            Ok(replay) => replay,
+            Err(err) => {
+                self.message = Some(format!("{err}"));
+                return;
+            }
+        };
+        let replay_rec = match rerun::RecordingStreamBuilder::new(APP_ID).spawn() {
+            Ok(replay_rec) => replay_rec,
+            Err(err) => {
+                self.message = Some(format!("{err}"));
+                return;
+            }
+        };
+        replay_rec.send_chunks(std::mem::take(&mut replay.chunks));
+        let store_id = replay_rec.store_info().map(|info| info.store_id);
+        if let Some(store_id) = &store_id {
+            rerun_app
+                .command_sender
+                .send_system(SystemCommand::ActivateRecordingOrTable(
+                    RecordingOrTable::Recording {
+                        store_id: store_id.clone(),
+                    },
+                ));
+        }
+        *rec = replay_rec;
+
+        let fork_scripts = match &replay.config {
+            Some(config) => [0, 1].map(|n| {
+                config
+                    .teams
+                    .get(n)
+                    .map(|team| team.script.clone())
+                    .unwrap_or_default()
+            }),
+            None => Default::default(),
+        };
+        *self = AppReRunning {
+            replay: Some(replay),
+            store_id,
+            speed: 1.0,
+            fork_scripts,
+            ..Default::default()
+        };
+    }
+
+    /// Une fois l'enregistrement arrivé dans le viewer, met son curseur au début de la timeline des ticks
+    fn prepare_viewer(&mut self, rerun_app: &re_viewer::App) {
+        let Some(store_id) = &self.store_id else {
+            return;
+        };
+        if self.cursor_ready
+            || rerun_app
+                .recording_db()
+                .is_none_or(|recording| recording.store_id() != store_id)
+        {
+            return;
+        }
+        self.cursor_ready = true;
+        let mut commands = vec![
+            TimeControlCommand::SetActiveTimeline(replay::TICK_TIMELINE.into()),
+            TimeControlCommand::SetSpeed(self.speed),
+        ];
+        commands.extend(seek_commands(0));
+        self.send_time_commands(rerun_app, commands);
+    }
+
+    fn send_time_commands(
+        &self,
+        rerun_app: &re_viewer::App,
+        time_commands: Vec<TimeControlCommand>,
+    ) {
+        if let Some(store_id) = &self.store_id
+            && !time_commands.is_empty()
+        {
+            rerun_app
+                .command_sender
+                .send_system(SystemCommand::TimeControlCommands {
+                    store_id: store_id.clone(),
+                    time_commands,
+                });
+        }
+    }
+
+    /// `tick` et `playing` : ce que montre le viewer, qu'on commande en retour
+    fn ui_replay(
+        &mut self,
+        ui: &mut egui::Ui,
+        rerun_app: &re_viewer::App,
+        tick: u64,
+        playing: bool,
+    ) {
+        let AppReRunning {
+            replay: Some(replay),
+            speed,
+            show_all_events,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let mut commands = Vec::new();
+
+        let metadata = &replay.metadata;
+        ui.label(format!(
+            "{} contre {}",
+            metadata.team_names[0], metadata.team_names[1]
+        ));
+        ui.label(format!("{} - graine {}", metadata.date, metadata.seed));
+        let score = replay.score_at(tick);
+        ui.add(egui::Label::new(
+            egui::RichText::new(format!("{} : {}", score[0], score[1])).size(60.0),
+        ));
+        let time = (tick as f32 * replay.tick_duration) as u64;
+        ui.label(format!("{}:{:02}", time / 60, time % 60));
+
+        let mut slider_tick = tick;
+        if ui
+            .add(egui::Slider::new(&mut slider_tick, 0..=replay.last_tick).show_value(false))
+            .changed()
+        {
+            commands.extend(seek_commands(slider_tick));
+        }
+        ui.horizontal(|ui| {
+            if ui.button(if playing { "⏸" } else { "▶" }).clicked() {
+                if playing {
+                    commands.push(TimeControlCommand::SetPlayState(PlayState::Paused));
+                } else {
+                    if tick >= replay.last_tick {
+                        commands.push(TimeControlCommand::SetTime(TimeReal::from(0_i64)));
+                    }
+                    commands.push(TimeControlCommand::SetPlayState(PlayState::Playing));
+                }
+            }
+            if ui.button("⏮").clicked() {
+                commands.extend(seek_commands(0));
+            }
+            if ui
+                .add(
+                    egui::DragValue::new(speed)
+                        .range(0.1..=8.0)
+                        .speed(0.05)
+                        .suffix(" ×"),
+                )
+                .changed()
+            {
+                commands.push(TimeControlCommand::SetSpeed(*speed));
+            }
+        });
+
+        let mut jump_to = None;
+        for (kind, name) in REPLAY_JUMPS {
+            ui.horizontal(|ui| {
+                let previous = replay.previous_event(tick, kind);
+                if ui
+                    .add_enabled(previous.is_some(), egui::Button::new(format!("◀ {name}")))
+                    .clicked()
+                {
+                    jump_to = previous.map(|timed_event| timed_event.tick);
+                }
+                let next = replay.next_event(tick, kind);
+                if ui
+                    .add_enabled(next.is_some(), egui::Button::new(format!("{name} ▶")))
+                    .clicked()
+                {
+                    jump_to = next.map(|timed_event| timed_event.tick);
+                }
             });
+        }
+
+        ui.separator();
+        ui.checkbox(show_all_events, "toutes les touches, chocs et murs");
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for timed_event in &replay.events {
+                let minor = matches!(
+                    timed_event.event,
+                    GameEvent::Touch { .. }
+                        | GameEvent::RobotCollision { .. }
+                        | GameEvent::WallHit { .. }
+                );
+                if minor && !*show_all_events {
+                    continue;
+                }
+                let time = timed_event.time as u64;
+                let label = format!("{}:{:02} {}", time / 60, time % 60, timed_event.event);
+                if ui
+                    .selectable_label(timed_event.tick == tick, label)
+                    .clicked()
+                {
+                    jump_to = Some(timed_event.tick);
+                }
+            }
+        });
+
+        if let Some(tick) = jump_to {
+            commands.extend(seek_commands(tick));
+        }
+        self.send_time_commands(rerun_app, commands);
+    }
+}
+
+/// Met le viewer en pause sur `tick`
+fn seek_commands(tick: u64) -> Vec<TimeControlCommand> {
+    vec![
+        TimeControlCommand::SetPlayState(PlayState::Paused),
+        TimeControlCommand::SetTime(TimeReal::from(tick as i64)),
+    ]
+}
+
+/// Curseur de temps du viewer pour chaque enregistrement, tenu à jour par ses évènements :
+/// c'est le moment du match revu qui est affiché
+#[derive(Clone, Default)]
+pub struct ViewerCursor(Rc<RefCell<HashMap<String, CursorState>>>);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CursorState {
+    /// `None` tant que le viewer n'a rien signalé, ou s'il affiche une autre timeline que les ticks
+    pub tick: Option<u64>,
+    pub playing: bool,
+    /// Le viewer affiche une autre timeline, dont les temps ne sont pas des ticks
+    other_timeline: bool,
+}
+
+impl ViewerCursor {
+    pub fn state(&self, store_id: &StoreId) -> CursorState {
+        self.0
+            .borrow()
+            .get(store_id.recording_id().as_str())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// À brancher sur `re_viewer::StartupOptions::on_event`
+    pub fn on_event(&self, event: re_viewer::ViewerEvent) {
+        let mut states = self.0.borrow_mut();
+        let state = states
+            .entry(event.recording_id.as_str().to_owned())
+            .or_default();
+        let to_tick = |time: TimeReal| time.floor().as_i64().max(0) as u64;
+        match event.kind {
+            ViewerEventKind::Play => state.playing = true,
+            ViewerEventKind::Pause => state.playing = false,
+            ViewerEventKind::TimelineChange {
+                timeline_name,
+                time,
+            } => {
+                state.other_timeline = timeline_name.as_str() != replay::TICK_TIMELINE;
+                state.tick = (!state.other_timeline).then(|| to_tick(time));
+            }
+            ViewerEventKind::TimeUpdate { time } if !state.other_timeline => {
+                state.tick = Some(to_tick(time));
+            }
+            _ => {}
+        }
     }
 }
 
@@ -281,120 +1184,438 @@ impl AppConfiguration {
         ctx: &egui::Context,
         rerun_app: &mut re_viewer::App,
         rec: &mut RecordingStream,
+        preferences: &mut UserPreferences,
     ) -> Option<AppStateMutateSignal> {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Bienvenue sur le simulateur !");
             ui.label("Sélectionnez l'emplacement du code source des deux équipes");
             ui.label(RichText::new("Attention! le code sélectionné sera executé sur votre machine. N'entrez que du code auquel vous faites confiance.").color(Color32::RED));
             ui.add_space(20.0);
+            let hovering_files = ctx.input(|i| !i.raw.hovered_files.is_empty());
             let mut new_states = Vec::new();
+            let mut slot_rects = [egui::Rect::NOTHING; 2];
             for (n, team_config_state) in self.team_config.iter_mut().enumerate() {
-                match team_config_state {
+                let slot = ui.group(|ui| match team_config_state {
                     TeamConfigState::Config{ path, err_message } => {
                         ui.heading(format!("Equipe {} :", n+1));
-                        let response = ui.text_edit_singleline( path);
-                        if response.changed() {
-                            let validation = validate_path(&path);
-                            new_states.push((n, match validation {
-                                Ok(valid) => TeamConfigState::Valid(valid),
-                                Err(CodeValidationError::Empty) => TeamConfigState::Config { path: path.to_string(), err_message: None },
-                                Err(err) => TeamConfigState::Config { path: path.to_string(), err_message: Some(err) },
-                            }));
+                        ui.horizontal(|ui| {
+                            let response = ui.text_edit_singleline( path);
+                            // Only validate a committed path : half-typed paths could import unrelated python modules
+                            // a path restored from the last session is only run once confirmed
+                            let load_clicked = !path.is_empty() && ui.button("Charger").clicked();
+                            if response.lost_focus() || load_clicked {
+                                new_states.push((n, TeamConfigState::from_path(path)));
+                            }
+                            if ui.button("Parcourir...").clicked()
+                                && let Some(picked) = rfd::FileDialog::new()
+                                    .add_filter("python", &["py"])
+                                    .pick_file()
+                            {
+                                new_states.push((n, TeamConfigState::from_path(&picked.to_string_lossy())));
+                            }
+                            egui::ComboBox::from_id_salt(("recent_scripts", n))
+                                .selected_text("récents")
+                                .show_ui(ui, |ui| {
+                                    for recent in &preferences.recent_scripts {
+                                        if ui.selectable_label(false, recent).clicked() {
+                                            new_states.push((n, TeamConfigState::from_path(recent)));
+                                        }
+                                    }
+                                });
+                            // no file needed : opponents of graded difficulty shipped with the simulator
+                            egui::ComboBox::from_id_salt(("builtin_bots", n))
+                                .selected_text("équipes fournies")
+                                .show_ui(ui, |ui| {
+                                    for bot in BuiltinBot::ALL {
+                                        let label = format!("niveau {} : {}", bot.difficulty(), bot.team_name());
+                                        if ui.selectable_label(false, label).on_hover_text(bot.description()).clicked() {
+                                            new_states.push((n, TeamConfigState::from_path(&bot.path())));
+                                        }
+                                    }
+                                });
+                        });
+                        if hovering_files {
+                            ui.label("Déposez le fichier .py ici");
                         }
                         if let Some(err_message) = err_message {
                             ui.label(RichText::new(format!("{err_message}")).color(Color32::ORANGE));
                         }
                     },
-                    TeamConfigState::Valid(PlayerCode::Python(PlayerCodePython { name, path, ..})) => {
+                    TeamConfigState::Valid(PlayerCode::Python(PlayerCodePython { name, path, mode, robot_profile, ..})) => {
                         ui.heading(format!("Equipe {name} :"));
                         ui.label(format!("code source : {}", path));
+                        ui.label(format!("mode : {}", mode.script_name()));
+                        ui.label(format!(
+                            "robot : {} ({} cm de diamètre, {} kg)",
+                            robot_profile.name,
+                            2.0 * robot_profile.radius,
+                            robot_profile.mass
+                        ));
                         if ui.button(format!("enlever {}", name)).clicked() {
                             new_states.push((n, TeamConfigState::default()));
                         }
                     }
-                }
+                    TeamConfigState::Valid(PlayerCode::Builtin(PlayerCodeBuiltin { name, bot, robot_profile, .. })) => {
+                        ui.heading(format!("Equipe {name} :"));
+                        ui.label(format!("équipe fournie, niveau {} : {}", bot.difficulty(), bot.description()));
+                        ui.label(format!(
+                            "robot : {} ({} cm de diamètre, {} kg)",
+                            robot_profile.name,
+                            2.0 * robot_profile.radius,
+                            robot_profile.mass
+                        ));
+                        if ui.button(format!("enlever {}", name)).clicked() {
+                            new_states.push((n, TeamConfigState::default()));
+                        }
+                    }
+                });
+                slot_rects[n] = slot.response.rect;
+                ui.horizontal(|ui| {
+                    ui.label(format!("couleur de l'équipe {} :", n + 1));
+                    ui.color_edit_button_srgb(&mut self.team_colors[n]);
+                });
             }
+
+            // Files dropped from the file explorer go to the slot under the pointer,
+            // or to the first team without code if the pointer position is unknown
+            let (dropped_files, pointer_pos) =
+                ctx.input(|i| (i.raw.dropped_files.clone(), i.pointer.latest_pos()));
+            for dropped_file in dropped_files {
+                let Some(dropped_path) = dropped_file.path else {
+                    continue;
+                };
+                let slot = pointer_pos
+                    .and_then(|pos| slot_rects.iter().position(|rect| rect.contains(pos)))
+                    .or_else(|| {
+                        self.team_config
+                            .iter()
+                            .position(|tcs| matches!(tcs, TeamConfigState::Config { .. }))
+                    });
+                let Some(n) = slot else {
+                    continue;
+                };
+                let dropped_path = dropped_path.to_string_lossy().into_owned();
+                new_states.push((n, if dropped_path.ends_with(".py") {
+                    TeamConfigState::from_path(&dropped_path)
+                } else {
+                    TeamConfigState::Config { path: dropped_path, err_message: Some(CodeValidationError::NotAPythonFile) }
+                }));
+            }
+
             // Apply new states
             for (n, new_state) in new_states {
+                if let TeamConfigState::Valid(code) = &new_state {
+                    preferences.add_recent_script(code.path());
+                }
                 self.team_config[n] = new_state;
             }
 
+            ui.separator();
+            let match_settings = egui::CollapsingHeader::new("Réglages du match")
+                .default_open(preferences.show_match_settings)
+                .show(ui, |ui| self.ui_match_settings(ui));
+            preferences.show_match_settings = match_settings.openness > 0.5;
+
+            egui::CollapsingHeader::new("Scénario de départ").show(ui, |ui| {
+                self.scenario_editor.ui(
+                    ui,
+                    &mut self.settings.scenario,
+                    &self.settings.kickoff,
+                    self.team_colors,
+                )
+            });
+
+            let ratings_header = egui::CollapsingHeader::new("Classement Elo")
+                .default_open(preferences.show_ratings)
+                .show(ui, |ui| self.ui_ratings(ui, preferences));
+            preferences.show_ratings = ratings_header.openness > 0.5;
+
+            let recording_header = egui::CollapsingHeader::new("Enregistrement")
+                .default_open(preferences.show_recording)
+                .show(ui, |ui| {
+                    ui.checkbox(&mut preferences.record_matches, "enregistrer chaque match (.rrd)");
+                    ui.horizontal(|ui| {
+                        ui.label("dossier :");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut preferences.recordings_dir)
+                                .hint_text(recording::DEFAULT_RECORDINGS_DIR),
+                        );
+                        if ui.button("…").clicked()
+                            && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                        {
+                            preferences.recordings_dir = dir.display().to_string();
+                        }
+                    });
+                });
+            preferences.show_recording = recording_header.openness > 0.5;
+
             ui.separator();
             if self
                 .team_config
                 .iter()
                 .all(|tcs| matches!(tcs, TeamConfigState::Valid(..))) {
-                if ui.button("Lancer la simulation !").clicked() {
+                if self.autostart {
+                    self.autostart = false;
                     return Some(AppStateMutateSignal::ToRun);
                 }
-            } else {
-                if ui.button("Démarrer une session sans équipes").clicked() {
-                    return Some(AppStateMutateSignal::ToReRun);
+                if ui.button("Lancer la simulation !").clicked() {
+                    return Some(AppStateMutateSignal::ToRun);
                 }
-                ui.label("cela permet par exemple d'importer le fichier d'un match enregistré pour le revoir.");
+            }
+            if ui.button("Revoir un match enregistré").clicked() {
+                return Some(AppStateMutateSignal::ToReRun);
             }
             None
         }).inner
     }
 
+    fn ui_match_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("fichier de configuration :");
+            ui.text_edit_singleline(&mut self.config_path);
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Charger la configuration").clicked() {
+                self.load_match_config();
+            }
+            if ui.button("Enregistrer la configuration").clicked() {
+                self.save_match_config();
+            }
+        });
+        if let Some(config_message) = &self.config_message {
+            ui.label(RichText::new(config_message).color(Color32::ORANGE));
+        }
+
+        let rules = &mut self.settings.rules;
+        ui.horizontal(|ui| {
+            ui.label("graine :");
+            ui.add(egui::DragValue::new(&mut self.settings.seed));
+        });
+        ui.horizontal(|ui| {
+            let mut limited = rules.duration.is_some();
+            ui.checkbox(&mut limited, "durée limitée (s) :");
+            match (limited, &mut rules.duration) {
+                (true, Some(duration)) => {
+                    ui.add(egui::DragValue::new(duration).range(1..=3600));
+                }
+                (true, None) => rules.duration = Some(infos::DEFAULT_MATCH_DURATION),
+                (false, _) => rules.duration = None,
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut limited = rules.max_score.is_some();
+            ui.checkbox(&mut limited, "score maximal :");
+            match (limited, &mut rules.max_score) {
+                (true, Some(max_score)) => {
+                    ui.add(egui::DragValue::new(max_score).range(1..=100));
+                }
+                (true, None) => rules.max_score = Some(infos::DEFAULT_MAX_SCORE),
+                (false, _) => rules.max_score = None,
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("décalage aléatoire à l'engagement (cm) :");
+            ui.add(egui::DragValue::new(&mut rules.kickoff_jitter).range(0.0..=20.0));
+        });
+    }
+
+    fn ui_ratings(&mut self, ui: &mut egui::Ui, preferences: &mut UserPreferences) {
+        ui.checkbox(
+            &mut preferences.record_ratings,
+            "ajouter les matchs terminés au classement",
+        );
+        ui.horizontal(|ui| {
+            ui.label("fichier du classement :");
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut preferences.ledger_path)
+                        .hint_text(ratings::DEFAULT_LEDGER_PATH),
+                )
+                .lost_focus()
+            {
+                self.ledger = None;
+            }
+            if ui.button("Actualiser").clicked() {
+                self.ledger = None;
+            }
+        });
+
+        let ledger = self.ledger.get_or_insert_with(|| {
+            RatingLedger::load(Path::new(preferences.ledger_path())).map_err(|err| format!("{err}"))
+        });
+        let ledger = match ledger {
+            Ok(ledger) => ledger,
+            Err(err) => {
+                ui.label(RichText::new(err.as_str()).color(Color32::ORANGE));
+                return;
+            }
+        };
+        if ledger.entries.is_empty() {
+            ui.label("aucun match enregistré");
+            return;
+        }
+
+        egui::Grid::new("leaderboard").striped(true).show(ui, |ui| {
+            for title in ["#", "équipe", "version", "Elo", "J", "G", "N", "P"] {
+                ui.strong(title);
+            }
+            ui.end_row();
+            for (rank, row) in ledger.leaderboard().iter().enumerate() {
+                ui.label(format!("{}", rank + 1));
+                let selected = self.history_team.as_ref() == Some(&row.version.team_name);
+                if ui
+                    .selectable_label(selected, &row.version.team_name)
+                    .clicked()
+                {
+                    self.history_team = (!selected).then(|| row.version.team_name.clone());
+                }
+                ui.label(row.version.short_hash());
+                ui.label(format!("{:.0}", row.rating));
+                ui.label(format!("{}", row.played));
+                ui.label(format!("{}", row.wins));
+                ui.label(format!("{}", row.draws));
+                ui.label(format!("{}", row.losses));
+                ui.end_row();
+            }
+        });
+
+        if let Some(team_name) = &self.history_team {
+            ui.add_space(10.0);
+            ui.strong(format!("Historique de {}", team_name));
+            egui::Grid::new("rating_history")
+                .striped(true)
+                .show(ui, |ui| {
+                    for title in ["version", "adversaire", "score", "Elo"] {
+                        ui.strong(title);
+                    }
+                    ui.end_row();
+                    for (entry, side) in ledger.history(team_name) {
+                        ui.label(entry.teams[side].short_hash());
+                        ui.label(format!("{}", entry.teams[1 - side]));
+                        ui.label(format!(
+                            "{} - {}",
+                            entry.scores[side],
+                            entry.scores[1 - side]
+                        ));
+                        ui.label(format!(
+                            "{:.0} -> {:.0}",
+                            entry.ratings_before[side], entry.ratings_after[side]
+                        ));
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+
+    /// Reprend la configuration de la session précédente
+    pub fn from_preferences(preferences: &UserPreferences) -> Self {
+        AppConfiguration {
+            team_config: [
+                TeamConfigState::restored(&preferences.last_team_paths[0]),
+                TeamConfigState::restored(&preferences.last_team_paths[1]),
+            ],
+            team_colors: preferences.last_team_colors.unwrap_or(DEFAULT_TEAM_COLORS),
+            settings: preferences.last_settings.clone(),
+            config_path: preferences.last_config_path.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Configuration lancée depuis la ligne de commande
+    pub fn from_match_config_file(path: &str) -> Self {
+        let mut config = AppConfiguration {
+            config_path: path.to_owned(),
+            autostart: true,
+            ..Default::default()
+        };
+        config.load_match_config();
+        config
+    }
+
+    fn load_match_config(&mut self) {
+        let match_config = match MatchConfig::load(Path::new(&self.config_path)) {
+            Ok(match_config) => match_config,
+            Err(err) => {
+                self.config_message = Some(format!("{err}"));
+                return;
+            }
+        };
+        for (team_config_state, team_entry) in self.team_config.iter_mut().zip(match_config.teams) {
+            *team_config_state = match validate_path(&team_entry.script) {
+                Err(err) => TeamConfigState::Config {
+                    path: team_entry.script,
+                    err_message: Some(err),
+                },
+                Ok(mut code) => match team_entry.robot_profile {
+                    None => TeamConfigState::Valid(code),
+                    Some(profile_path) => match code.set_robot_profile(&profile_path) {
+                        Ok(()) => TeamConfigState::Valid(code),
+                        Err(err) => TeamConfigState::Config {
+                            path: team_entry.script,
+                            err_message: Some(CodeValidationError::RobotProfileIncorrect(err)),
+                        },
+                    },
+                },
+            };
+        }
+        self.team_colors = match_config.team_colors();
+        self.settings = match_config.settings;
+        self.config_message = Some(format!("configuration {} chargée", self.config_path));
+    }
+
+    fn save_match_config(&mut self) {
+        let match_config = MatchConfig {
+            teams: self
+                .team_config
+                .iter()
+                .zip(self.team_colors)
+                .map(|(team_config_state, color)| match team_config_state {
+                    TeamConfigState::Config { path, .. } => TeamEntry {
+                        script: path.clone(),
+                        robot_profile: None,
+                        color: Some(color),
+                    },
+                    TeamConfigState::Valid(code) => TeamEntry {
+                        script: code.path().to_owned(),
+                        robot_profile: code.robot_profile_path().map(str::to_owned),
+                        color: Some(color),
+                    },
+                })
+                .collect(),
+            settings: self.settings.clone(),
+        };
+        self.config_message = Some(match match_config.save(Path::new(&self.config_path)) {
+            Ok(()) => format!("configuration enregistrée dans {}", self.config_path),
+            Err(err) => format!("{err}"),
+        });
+    }
+
     /// Mutate the app to run mode
     fn run(&mut self) -> AppState {
         // mem::take replaces the value in self with its default. Usefull as TeamConfigState is not Copy
         let local_configs = std::mem::take(&mut self.team_config);
-        let [
-            TeamConfigState::Valid(mut team1),
-            TeamConfigState::Valid(mut team2),
-        ] = local_configs
-        else {
+        let [TeamConfigState::Valid(team1), TeamConfigState::Valid(team2)] = local_configs else {
             panic!("Cannot mutate to state run with config {:?}", self);
         };
 
-        // ensure unique team name
-        let mut name1 = team1.name().to_owned();
-        let mut name2 = team2.name().to_owned();
-        if name1 == name2 {
-            name1 += "_1";
-            name2 += "_2";
-            team1._set_name(&name1);
-            team2._set_name(&name2);
-        }
-
-        let mut teams = HashMap::with_capacity(2);
-        teams.insert(name1.clone(), team1);
-        teams.insert(name2.clone(), team2);
-
-        // CRUCIAL : robots from the same team _must_ be at (0 and 1) or (2 and 3)
-        let simulation = Simulator::new(
-            [
-                RobotBuilder::from_basic_robot(&name1, 1),
-                RobotBuilder::from_basic_robot(&name1, 2),
-                RobotBuilder::from_basic_robot(&name2, 1),
-                RobotBuilder::from_basic_robot(&name2, 2),
-            ],
-            teams,
-        );
-        let mut robot_handle_to_color = HashMap::new();
-        robot_handle_to_color.insert(simulation.robots[0].clone(), Color::from_rgb(0, 0, 255));
-        robot_handle_to_color.insert(simulation.robots[1].clone(), Color::from_rgb(255, 255, 255));
-        robot_handle_to_color.insert(simulation.robots[2].clone(), Color::from_rgb(255, 0, 0));
-        robot_handle_to_color.insert(simulation.robots[3].clone(), Color::from_rgb(0, 255, 0));
-
-        Running(AppRunning {
-            simulation,
-            robot_handle_to_color,
-        })
+        Running(AppRunning::new(
+            Simulator::from_teams(team1, team2, self.settings.clone()),
+            self.team_colors,
+        ))
     }
 
     /// Mutate the app to rerun mode
     fn re_run(&mut self) -> AppState {
-        ReRunning(AppReRunning {})
+        ReRunning(AppReRunning::default())
     }
 }
 
 pub struct SimulatorApp {
     pub state: AppState,
+    pub preferences: UserPreferences,
     pub rerun_app: re_viewer::App,
     pub rec: RecordingStream,
+    pub viewer_cursor: ViewerCursor,
 }
 
 impl Debug for SimulatorApp {
@@ -406,15 +1627,38 @@ impl Debug for SimulatorApp {
 }
 
 impl SimulatorApp {
-    pub fn new(rerun_app: App, rec: RecordingStream) -> Self {
+    pub fn new(
+        rerun_app: App,
+        rec: RecordingStream,
+        state: AppState,
+        preferences: UserPreferences,
+        viewer_cursor: ViewerCursor,
+    ) -> Self {
         Self {
-            state: AppState::default(),
+            state,
+            preferences,
             rerun_app,
             rec,
+            viewer_cursor,
         }
     }
 
-    pub async fn start() -> Result<(), Box<dyn std::error::Error>> {
+    /// Lance un match repris depuis un instantané.
+    /// Il part dans un nouvel enregistrement pour ne pas se mélanger avec le match revu.
+    fn fork(&mut self, mut running: AppRunning) {
+        self.rec = rerun::RecordingStreamBuilder::new(APP_ID)
+            .recording_id(format!("{APP_ID}_reprise_{}", running.simulation.tick_nb))
+            .spawn()
+            .unwrap();
+        running.recording_message = self.preferences.start_recording(&self.rec, &mut running);
+        self.state = Running(running);
+        self.state.init(&mut self.rec);
+    }
+
+    /// `match_config_path` : fichier de configuration passé en ligne de commande, le match est alors lancé directement
+    pub async fn start(
+        match_config_path: Option<String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // Direct calls using the `log` crate to stderr. Control with `RUST_LOG=debug` etc.
         re_log::setup_logging();
         // Install handlers for panics and crashes that prints to stderr and send
@@ -436,10 +1680,16 @@ impl SimulatorApp {
         let mut native_options = re_viewer::native::eframe_options(None);
         native_options.viewport = native_options.viewport.with_app_id(APP_ID);
 
+        // the replay mode follows the time cursor of the viewer
+        let viewer_cursor = ViewerCursor::default();
+        let on_event: Rc<dyn Fn(re_viewer::ViewerEvent)> = {
+            let viewer_cursor = viewer_cursor.clone();
+            Rc::new(move |event| viewer_cursor.on_event(event))
+        };
         let startup_options = re_viewer::StartupOptions {
             hide_welcome_screen: true,
             // panel_state_overrides: PanelStateOverrides { top: (), blueprint: (), selection: (), time: () },
-            // on_event HERE
+            on_event: Some(on_event),
             ..Default::default()
         };
 
@@ -467,7 +1717,21 @@ impl SimulatorApp {
                     .spawn()
                     .unwrap();
                 rerun_app.add_log_receiver(rx);
-                Ok(Box::new(Self::new(rerun_app, rec)))
+                let preferences: UserPreferences = cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, PREFERENCES_KEY))
+                    .unwrap_or_default();
+                let state = match match_config_path {
+                    Some(path) => Configuration(AppConfiguration::from_match_config_file(&path)),
+                    None => Configuration(AppConfiguration::from_preferences(&preferences)),
+                };
+                Ok(Box::new(Self::new(
+                    rerun_app,
+                    rec,
+                    state,
+                    preferences,
+                    viewer_cursor,
+                )))
             }),
         )?;
 
@@ -494,27 +1758,93 @@ fn format_arrow(array: &dyn arrow::array::Array) -> String {
 
 // Simulation calls
 impl AppRunning {
-    fn init(&mut self, rec: &mut RecordingStream) {
-        self.simulation.rigid_body_set[self.simulation.ball_rigid_body_handle]
-            .apply_impulse(vector![-100.0, 0.0], true);
-        self.draw_field(rec);
+    /// `team_colors` : couleur des robots de chaque équipe, en RGB
+    pub fn new(mut simulation: Simulator, team_colors: [[u8; 3]; 2]) -> Self {
+        let events = simulation.subscribe();
+        // robots 0 and 1 are in the first team, 2 and 3 in the second
+        let robot_colors = [0, 0, 1, 1].map(|team| team_colors[team]);
+        let mut robot_handle_to_color = HashMap::new();
+        for (robot_handle, [r, g, b]) in simulation.robots.iter().zip(robot_colors) {
+            robot_handle_to_color.insert(robot_handle.clone(), Color::from_rgb(r, g, b));
+        }
+
+        AppRunning {
+            metadata: MatchMetadata::from_simulation(&simulation, &recording::now_utc()),
+            simulation,
+            robot_handle_to_color,
+            events,
+            overlays: Overlays::new(robot_colors),
+            kick_flash_until: HashMap::new(),
+            rating_message: None,
+            stats_message: None,
+            recording_message: String::new(),
+            team_colors,
+            log_snapshots: false,
+            manual_control: ManualControl::default(),
+            paused: false,
+            field_interaction: FieldInteraction::default(),
+        }
     }
 
-    fn tick(&mut self, rec: &mut RecordingStream) {
+    pub fn init(&mut self, rec: &mut RecordingStream) {
+        // a match resumed from a snapshot is already under way
+        if self.simulation.tick_nb == 0 {
+            self.simulation.start();
+        }
+        AppRunning::draw_field(rec);
+        self.metadata.log(rec);
+        replay::log_config(rec, &self.simulation.match_config(self.team_colors));
+    }
+
+    /// Les métadonnées sont statiques : on les renvoie en entier à chaque but et à la fin du match
+    fn update_metadata(&mut self, rec: &mut RecordingStream) {
+        let metadata = MatchMetadata::from_simulation(&self.simulation, &self.metadata.date);
+        if metadata.score != self.metadata.score || metadata.finished != self.metadata.finished {
+            metadata.log(rec);
+            self.metadata = metadata;
+        }
+    }
+
+    /// Renvoie le nombre d'erreurs produites par le code des équipes pendant ce tick
+    pub fn tick(&mut self, rec: &mut RecordingStream) -> usize {
+        if self.simulation.is_finished() {
+            return 0;
+        }
         let errors = self.simulation.tick();
+        rec.set_time_sequence(replay::TICK_TIMELINE, self.simulation.tick_nb as i64);
         if !errors.is_empty() {
             let logs: Vec<TextLog> = errors
                 .iter()
                 .map(|(robot, error)| {
-                    TextLog::new(format!(
-                        "{} : {}",
-                        robot, error
-                    ))
-                    .with_level(TextLogLevel::ERROR)
+                    TextLog::new(format!("{} : {}", robot, error)).with_level(TextLogLevel::ERROR)
                 })
                 .collect();
             rec.log("simulator_logs/player_code_error", &logs).unwrap();
         }
+        if self.log_snapshots && self.simulation.tick_nb % replay::SNAPSHOT_PERIOD_TICKS == 0 {
+            replay::log_snapshot(rec, &self.simulation.snapshot());
+        }
+        self.log_events(rec);
+        self.draw(rec);
+        let tick_nb = self.simulation.tick_nb;
+        self.kick_flash_until.retain(|robot, until| {
+            let keep = tick_nb < *until;
+            if !keep {
+                rec.log(format!("Robot_{robot}/intent/kick"), &Clear::flat())
+                    .unwrap();
+            }
+            keep
+        });
+
+        self.overlays.update(&self.simulation);
+        self.overlays.log(rec, &self.simulation);
+        self.update_metadata(rec);
+
+        errors.len()
+    }
+
+    /// Dessine la balle et les robots là où ils sont, aussi pendant une pause quand on les déplace à la main
+    fn draw(&self, rec: &mut RecordingStream) {
         // draw ball
         let ball_position = self.simulation.position_of_ball();
         rec.log(
@@ -528,6 +1858,7 @@ impl AppRunning {
         // We accept the performance cost of clone to avoid putting lifetimes everywhere
         for robot_handle in self.simulation.robots.clone() {
             self.draw_robot(rec, &robot_handle);
+            self.draw_intent(rec, &robot_handle);
         }
     }
 }
@@ -537,28 +1868,58 @@ impl AppRunning {
     fn draw_robot(&self, rec: &mut RecordingStream, robot_handle: &RobotHandler) {
         let robot_position = self.simulation.position_of(&robot_handle);
         let robot_position = [robot_position.x, robot_position.y];
+        let robot_radius = self.simulation.robot_builders[robot_handle].radius;
         rec.log(
             format!("Robot_{robot_handle}/structure"),
             &Points2D::new([robot_position])
                 .with_colors([self.robot_handle_to_color[&robot_handle]])
-                .with_radii([Radius::new_scene_units(infos::ROBOT_RADIUS)]),
+                .with_radii([Radius::new_scene_units(robot_radius)])
+                .with_labels([format!("{}", robot_handle.robot_number())])
+                .with_show_labels(true),
+        )
+        .unwrap();
+
+        // heading : where the front (and the kicker) is
+        let heading = front_angle(self.simulation.rotation_of(robot_handle).angle());
+        rec.log(
+            format!("Robot_{robot_handle}/heading"),
+            &Arrows2D::from_vectors([[
+                1.4 * robot_radius * heading.cos(),
+                1.4 * robot_radius * heading.sin(),
+            ]])
+            .with_origins([robot_position])
+            .with_colors([Color::from_rgb(0, 0, 0)])
+            .with_radii([Radius::new_scene_units(0.5)])
+            .with_draw_order(61.0),
         )
         .unwrap();
 
+        // halo when the ball is in the kicker
+        if self.simulation.has_ball(robot_handle) {
+            rec.log(
+                format!("Robot_{robot_handle}/halo"),
+                &Points2D::new([robot_position])
+                    .with_colors([Color::from_unmultiplied_rgba(255, 255, 0, 90)])
+                    .with_radii([Radius::new_scene_units(robot_radius + 3.0)])
+                    .with_draw_order(29.0),
+            )
+            .unwrap();
+        } else {
+            rec.log(format!("Robot_{robot_handle}/halo"), &Clear::flat())
+                .unwrap();
+        }
+
         // dribbler
         let robot_angle = *self.simulation.rotation_of(&robot_handle);
-        let dribbler_length = infos::ROBOT_RADIUS * 60.0 / 100.0;
-        let dribbler_width = infos::ROBOT_RADIUS * 20.0 / 100.0;
+        let dribbler_length = robot_radius * 60.0 / 100.0;
+        let dribbler_width = robot_radius * 20.0 / 100.0;
 
-        let p1 = nalgebra::Complex::new(
-            -dribbler_length,
-            -infos::ROBOT_RADIUS + dribbler_width / 2.0,
-        ) * robot_angle;
+        let p1 = nalgebra::Complex::new(-dribbler_length, -robot_radius + dribbler_width / 2.0)
+            * robot_angle;
         let p1 = [p1.re + robot_position[0], p1.im + robot_position[1]];
 
-        let p2 =
-            nalgebra::Complex::new(dribbler_length, -infos::ROBOT_RADIUS + dribbler_width / 2.0)
-                * robot_angle;
+        let p2 = nalgebra::Complex::new(dribbler_length, -robot_radius + dribbler_width / 2.0)
+            * robot_angle;
         let p2 = [p2.re + robot_position[0], p2.im + robot_position[1]];
 
         rec.log(
@@ -570,7 +1931,97 @@ impl AppRunning {
         .unwrap();
     }
 
-    fn draw_field(&self, rec: &mut RecordingStream) {
+    /// Ce que le code du robot a demandé au dernier tick, pour le comparer avec ce que fait le robot
+    fn draw_intent(&self, rec: &mut RecordingStream, robot_handle: &RobotHandler) {
+        let Some(action) = self.simulation.last_actions.get(robot_handle) else {
+            // no valid action this tick : the script crashed or returned garbage
+            rec.log(
+                format!("Robot_{robot_handle}/intent/command"),
+                &Clear::flat(),
+            )
+            .unwrap();
+            rec.log(
+                format!("Robot_{robot_handle}/intent/heading"),
+                &Clear::flat(),
+            )
+            .unwrap();
+            return;
+        };
+        let position = self.simulation.position_of(robot_handle);
+        let origin = [position.x, position.y];
+        let color = self.robot_handle_to_color[robot_handle];
+
+        match &action.movement {
+            Movement::Target {
+                target_position,
+                power,
+                target_orientation,
+            } => {
+                // the arrow gets wider with the power
+                rec.log(
+                    format!("Robot_{robot_handle}/intent/command"),
+                    &Arrows2D::from_vectors([[
+                        target_position.0 - position.x,
+                        target_position.1 - position.y,
+                    ]])
+                    .with_origins([origin])
+                    .with_colors([color])
+                    .with_radii([Radius::new_scene_units(0.3 + 1.2 * *power as f32 / 255.0)])
+                    .with_labels([format!("puissance {}", power)])
+                    .with_draw_order(65.0),
+                )
+                .unwrap();
+                let heading = front_angle(*target_orientation);
+                let heading_length = 2.0 * self.simulation.robot_builders[robot_handle].radius;
+                rec.log(
+                    format!("Robot_{robot_handle}/intent/heading"),
+                    &Arrows2D::from_vectors([[
+                        heading_length * heading.cos(),
+                        heading_length * heading.sin(),
+                    ]])
+                    .with_origins([origin])
+                    .with_colors([Color::from_rgb(255, 0, 255)])
+                    .with_radii([Radius::new_scene_units(0.4)])
+                    .with_draw_order(66.0),
+                )
+                .unwrap();
+            }
+            Movement::Velocity {
+                velocity,
+                angular_velocity,
+            } => {
+                rec.log(
+                    format!("Robot_{robot_handle}/intent/command"),
+                    &Arrows2D::from_vectors([[
+                        velocity.0 * INTENT_VELOCITY_ARROW_TIME,
+                        velocity.1 * INTENT_VELOCITY_ARROW_TIME,
+                    ]])
+                    .with_origins([origin])
+                    .with_colors([color])
+                    .with_radii([Radius::new_scene_units(0.6)])
+                    .with_labels([format!(
+                        "{:.0} cm/s, {:.0}°/s",
+                        (velocity.0.powi(2) + velocity.1.powi(2)).sqrt(),
+                        angular_velocity.to_degrees()
+                    )])
+                    .with_draw_order(65.0),
+                )
+                .unwrap();
+            }
+            Movement::MotorPowers(powers) => {
+                rec.log(
+                    format!("Robot_{robot_handle}/intent/command"),
+                    &Points2D::new([origin])
+                        .with_radii([Radius::new_scene_units(0.0)])
+                        .with_labels([format!("moteurs {:?}", powers)])
+                        .with_draw_order(65.0),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn draw_field(rec: &mut RecordingStream) {
         // Remember, (0, 0) at center of field
         // Field rect (filled green)
         let field_rect = Boxes2D::from_mins_and_sizes(
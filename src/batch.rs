@@ -0,0 +1,315 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    headless::{HeadlessError, MatchJob, MatchResult, record_result, run_in_workers},
+    match_config::MatchConfig,
+    player_action::validate_path,
+    ratings::RatingLedger,
+};
+
+/// Quantile de la loi normale pour un intervalle de confiance à 95 %
+const Z_95: f64 = 1.96;
+
+/// Décalage des positions de départ (cm) quand ni la configuration ni `--jitter` n'en donnent :
+/// sans décalage, des équipes déterministes joueraient le même match à chaque fois
+pub const DEFAULT_KICKOFF_JITTER: f32 = 5.0;
+
+/// Paramètres d'une évaluation : la première équipe de `config` est évaluée contre la seconde
+pub struct BatchOptions {
+    pub config: MatchConfig,
+    pub nb_matches: usize,
+    pub nb_workers: usize,
+    pub output_dir: PathBuf,
+    /// Remplace `rules.kickoff_jitter` de la configuration si présent, doit être strictement positif.
+    /// Sinon, `DEFAULT_KICKOFF_JITTER` remplace un décalage nul de la configuration
+    pub kickoff_jitter: Option<f32>,
+    /// Enregistre chaque match dans un fichier .rrd, ce qui ralentit beaucoup l'évaluation
+    pub record: bool,
+}
+
+/// Intervalle de confiance à 95 %
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Interval {
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Une proportion (victoires, nuls, défaites) avec son intervalle de Wilson
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Rate {
+    pub count: usize,
+    pub rate: f64,
+    pub interval: Interval,
+}
+
+impl Rate {
+    fn new(count: usize, total: usize) -> Rate {
+        let n = total as f64;
+        let p = count as f64 / n;
+        let z2 = Z_95 * Z_95;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let half_width = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+        Rate {
+            count,
+            rate: p,
+            interval: Interval {
+                low: (center - half_width).max(0.0),
+                high: (center + half_width).min(1.0),
+            },
+        }
+    }
+}
+
+/// Moyenne d'une valeur par match, avec l'intervalle de confiance de la moyenne
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Mean {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub interval: Interval,
+}
+
+impl Mean {
+    fn new(values: &[f64]) -> Mean {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std_dev = if values.len() > 1 {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        let half_width = Z_95 * std_dev / n.sqrt();
+        Mean {
+            mean,
+            std_dev,
+            interval: Interval {
+                low: mean - half_width,
+                high: mean + half_width,
+            },
+        }
+    }
+}
+
+/// Résultats agrégés, du point de vue de l'équipe évaluée
+#[derive(Debug, Serialize)]
+pub struct BatchSummary {
+    pub team: String,
+    pub opponent: String,
+    pub nb_matches: usize,
+    pub nb_failed: usize,
+    /// Décalage des positions de départ utilisé pour tous les matchs
+    pub kickoff_jitter: f32,
+    pub wins: Rate,
+    pub draws: Rate,
+    pub losses: Rate,
+    pub goals_for: Mean,
+    pub goals_against: Mean,
+    pub goal_difference: Mean,
+    pub matches: Vec<MatchResult>,
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    NoMatch,
+    NoKickoffJitter(f32),
+    InvalidTeam(HeadlessError),
+    AllMatchesFailed(HeadlessError),
+    Io(String),
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchError::NoMatch => write!(f, "Il faut jouer au moins un match"),
+            BatchError::NoKickoffJitter(jitter) => write!(
+                f,
+                "--jitter doit être strictement positif, pas {} : sans décalage des positions de départ, tous les matchs seraient identiques",
+                jitter
+            ),
+            BatchError::InvalidTeam(err) => write!(f, "{}", err),
+            BatchError::AllMatchesFailed(err) => {
+                write!(f, "Aucun match n'a pu être joué, dernière erreur : {}", err)
+            }
+            BatchError::Io(err_str) => {
+                write!(
+                    f,
+                    "Impossible d'écrire les résultats de l'évaluation : {}",
+                    err_str
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// Joue `nb_matches` matchs indépendants en parallèle, un processus par match en cours.
+/// Chaque match a sa propre graine (`seed + numéro du match`) et les équipes changent de côté
+/// un match sur deux, pour que l'avantage éventuel d'un côté du terrain ne fausse pas le résultat.
+/// Si `ledger` est donné, tous les matchs réussis y sont ajoutés.
+pub fn run_batch(
+    options: &BatchOptions,
+    mut ledger: Option<&mut RatingLedger>,
+) -> Result<BatchSummary, BatchError> {
+    if options.nb_matches == 0 {
+        return Err(BatchError::NoMatch);
+    }
+    let kickoff_jitter = match options.kickoff_jitter {
+        Some(jitter) if jitter > 0.0 => jitter,
+        Some(jitter) => return Err(BatchError::NoKickoffJitter(jitter)),
+        None if options.config.settings.rules.kickoff_jitter > 0.0 => {
+            options.config.settings.rules.kickoff_jitter
+        }
+        None => DEFAULT_KICKOFF_JITTER,
+    };
+
+    // Check both teams once here instead of getting the same error from every worker
+    let mut names = Vec::with_capacity(2);
+    for team_entry in &options.config.teams {
+        let code = validate_path(&team_entry.script).map_err(|err| {
+            BatchError::InvalidTeam(HeadlessError::InvalidTeam {
+                script: team_entry.script.clone(),
+                err,
+            })
+        })?;
+        names.push(code.name().to_owned());
+    }
+
+    let matchs_dir = options.output_dir.join("matchs");
+    std::fs::create_dir_all(&matchs_dir).map_err(|err| BatchError::Io(format!("{}", err)))?;
+
+    let jobs: Vec<MatchJob> = (0..options.nb_matches)
+        .map(|match_nb| {
+            let mut config = options.config.clone();
+            config.settings.seed = options.config.settings.seed.wrapping_add(match_nb as u64);
+            config.settings.rules.kickoff_jitter = kickoff_jitter;
+            if is_swapped(match_nb) {
+                config.teams.swap(0, 1);
+            }
+            let file_stem = format!("{:04}", match_nb + 1);
+            MatchJob {
+                config,
+                config_path: matchs_dir.join(format!("{}.toml", file_stem)),
+                result_path: matchs_dir.join(format!("{}.json", file_stem)),
+                record_path: options
+                    .record
+                    .then(|| matchs_dir.join(format!("{}.rrd", file_stem))),
+            }
+        })
+        .collect();
+
+    let mut matches = Vec::with_capacity(options.nb_matches);
+    let mut goals_for = Vec::with_capacity(options.nb_matches);
+    let mut goals_against = Vec::with_capacity(options.nb_matches);
+    let mut nb_failed = 0;
+    let mut last_error = None;
+    for (match_nb, result) in run_in_workers(&jobs, options.nb_workers)
+        .into_iter()
+        .enumerate()
+    {
+        match result {
+            Ok(result) => {
+                if let Some(ledger) = ledger.as_deref_mut() {
+                    record_result(ledger, &result, &jobs[match_nb].config);
+                }
+                let team_side = if is_swapped(match_nb) { 1 } else { 0 };
+                goals_for.push(result.scores[team_side] as f64);
+                goals_against.push(result.scores[1 - team_side] as f64);
+                matches.push(result);
+            }
+            Err(err) => {
+                eprintln!("Match {} : {}", match_nb + 1, err);
+                nb_failed += 1;
+                last_error = Some(err);
+            }
+        }
+    }
+    if matches.is_empty() {
+        return Err(BatchError::AllMatchesFailed(last_error.unwrap()));
+    }
+
+    let nb_played = matches.len();
+    let count = |predicate: fn(f64, f64) -> bool| {
+        goals_for
+            .iter()
+            .zip(&goals_against)
+            .filter(|&(&scored, &conceded)| predicate(scored, conceded))
+            .count()
+    };
+    let goal_difference: Vec<f64> = goals_for
+        .iter()
+        .zip(&goals_against)
+        .map(|(scored, conceded)| scored - conceded)
+        .collect();
+
+    Ok(BatchSummary {
+        team: names[0].clone(),
+        opponent: names[1].clone(),
+        nb_matches: nb_played,
+        nb_failed,
+        kickoff_jitter,
+        wins: Rate::new(count(|scored, conceded| scored > conceded), nb_played),
+        draws: Rate::new(count(|scored, conceded| scored == conceded), nb_played),
+        losses: Rate::new(count(|scored, conceded| scored < conceded), nb_played),
+        goals_for: Mean::new(&goals_for),
+        goals_against: Mean::new(&goals_against),
+        goal_difference: Mean::new(&goal_difference),
+        matches,
+    })
+}
+
+#[inline]
+fn is_swapped(match_nb: usize) -> bool {
+    match_nb % 2 == 1
+}
+
+impl BatchSummary {
+    /// Résumé lisible, pour le terminal
+    pub fn report(&self) -> String {
+        let rate = |name: &str, rate: &Rate| {
+            format!(
+                "{:<12}{:>5} ({:>5.1} %, IC 95 % : {:.1} - {:.1} %)\n",
+                name,
+                rate.count,
+                rate.rate * 100.0,
+                rate.interval.low * 100.0,
+                rate.interval.high * 100.0
+            )
+        };
+        let mean = |name: &str, mean: &Mean| {
+            format!(
+                "{:<12}{:>8.2} par match (IC 95 % : {:.2} - {:.2})\n",
+                name, mean.mean, mean.interval.low, mean.interval.high
+            )
+        };
+        let mut report = format!(
+            "{} contre {} : {} matchs joués",
+            self.team, self.opponent, self.nb_matches
+        );
+        if self.nb_failed > 0 {
+            report += &format!(", {} en échec", self.nb_failed);
+        }
+        report += &format!(
+            ", positions de départ décalées de {} cm au plus\n",
+            self.kickoff_jitter
+        );
+        report += &rate("Victoires", &self.wins);
+        report += &rate("Nuls", &self.draws);
+        report += &rate("Défaites", &self.losses);
+        report += &mean("Buts pour", &self.goals_for);
+        report += &mean("Buts contre", &self.goals_against);
+        report += &mean("Différence", &self.goal_difference);
+        report
+    }
+
+    pub fn export(&self, output_dir: &Path) -> Result<(), BatchError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|err| BatchError::Io(format!("{}", err)))?;
+        std::fs::write(output_dir.join("evaluation.json"), json)
+            .map_err(|err| BatchError::Io(format!("{}", err)))
+    }
+}
@@ -0,0 +1,646 @@
+use core::f32;
+
+use nalgebra::Vector2;
+
+use crate::{
+    infos,
+    orientation_controller::normalize_angle,
+    player_action::{Movement, PlayerAction, PlayerInformation},
+    robot_profile::RobotProfile,
+    simulator::front_angle,
+};
+
+/// Un script d'équipe qui commence par ce préfixe désigne une équipe fournie avec le simulateur,
+/// par exemple `builtin:idle`
+pub const BUILTIN_PREFIX: &str = "builtin:";
+
+/// Vitesse (1/s) à laquelle un robot corrige l'écart à sa cible : à 10 cm, il va à 40 cm/s
+const POSITION_GAIN: f32 = 4.0;
+/// Vitesse angulaire (1/s) demandée par radian d'écart d'orientation
+const ORIENTATION_GAIN: f32 = 4.0;
+/// Le robot tire quand il est orienté vers sa cible à cet angle près
+const KICK_TOLERANCE: f32 = 15.0_f32.to_radians();
+/// Un adversaire à moins de cette distance (cm) de la trajectoire de la balle la bloque
+const LANE_MARGIN: f32 = 2.0 * infos::ROBOT_RADIUS;
+
+/// Équipes écrites en Rust, utilisables partout où l'on attend un script python.
+/// Elles sont rangées par difficulté croissante, pour servir d'adversaires aux débutants puis aux plus avancés.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinBot {
+    /// Les robots restent immobiles là où ils sont
+    Idle,
+    /// Les deux robots se placent devant leur but et n'en bougent plus
+    Wall,
+    /// Un gardien suit la balle devant le but, l'autre robot ne bouge pas
+    Goalie,
+    /// Les deux robots foncent sur la balle et tirent droit devant eux
+    Chaser,
+    /// Un attaquant vise le but ou fait la passe à son coéquipier, qui se démarque
+    Passing,
+    /// Un attaquant qui cherche l'angle de tir libre et un défenseur qui couvre le but
+    Strong,
+}
+
+impl BuiltinBot {
+    pub const ALL: [BuiltinBot; 6] = [
+        BuiltinBot::Idle,
+        BuiltinBot::Wall,
+        BuiltinBot::Goalie,
+        BuiltinBot::Chaser,
+        BuiltinBot::Passing,
+        BuiltinBot::Strong,
+    ];
+
+    /// Nom à mettre après `builtin:` dans le chemin du script
+    pub fn script_name(&self) -> &'static str {
+        match self {
+            BuiltinBot::Idle => "idle",
+            BuiltinBot::Wall => "wall",
+            BuiltinBot::Goalie => "goalie",
+            BuiltinBot::Chaser => "chaser",
+            BuiltinBot::Passing => "passing",
+            BuiltinBot::Strong => "strong",
+        }
+    }
+
+    /// Nom de l'équipe dans les matchs
+    pub fn team_name(&self) -> &'static str {
+        match self {
+            BuiltinBot::Idle => "Immobiles",
+            BuiltinBot::Wall => "Mur",
+            BuiltinBot::Goalie => "Gardien",
+            BuiltinBot::Chaser => "Chasseurs",
+            BuiltinBot::Passing => "Passeurs",
+            BuiltinBot::Strong => "Experts",
+        }
+    }
+
+    /// De 0 (immobile) à 5
+    pub fn difficulty(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            BuiltinBot::Idle => "les robots restent immobiles",
+            BuiltinBot::Wall => "les deux robots bloquent le but sans bouger",
+            BuiltinBot::Goalie => "un gardien suit la balle, l'autre robot ne bouge pas",
+            BuiltinBot::Chaser => "les deux robots foncent sur la balle",
+            BuiltinBot::Passing => "un attaquant tire ou passe, son coéquipier se démarque",
+            BuiltinBot::Strong => "un attaquant cherche l'angle libre, un défenseur couvre le but",
+        }
+    }
+
+    /// `builtin:idle` -> `BuiltinBot::Idle`
+    pub fn from_path(path: &str) -> Option<BuiltinBot> {
+        let name = path.strip_prefix(BUILTIN_PREFIX)?;
+        BuiltinBot::ALL
+            .into_iter()
+            .find(|bot| bot.script_name() == name)
+    }
+
+    pub fn path(&self) -> String {
+        format!("{}{}", BUILTIN_PREFIX, self.script_name())
+    }
+}
+
+/// Équipe fournie avec le simulateur
+#[derive(Debug)]
+pub struct PlayerCodeBuiltin {
+    pub name: String,
+    /// `builtin:<nom>`, à la place du chemin d'un script
+    pub path: String,
+    pub bot: BuiltinBot,
+    pub robot_profile: RobotProfile,
+    pub robot_profile_path: Option<String>,
+}
+
+impl PlayerCodeBuiltin {
+    pub fn new(bot: BuiltinBot) -> PlayerCodeBuiltin {
+        PlayerCodeBuiltin {
+            name: bot.team_name().to_owned(),
+            path: bot.path(),
+            bot,
+            robot_profile: RobotProfile::default(),
+            robot_profile_path: None,
+        }
+    }
+
+    /// `player_info` est dans le repère du terrain, l'action renvoyée aussi
+    pub fn tick(&self, player_info: PlayerInformation) -> PlayerAction {
+        let view = TeamView::new(&player_info, self.robot_profile.radius);
+        let max_speed = self.robot_profile.command_max_speed();
+        let order = match self.bot {
+            BuiltinBot::Idle => Order::stop(),
+            BuiltinBot::Wall => view.wall(max_speed),
+            BuiltinBot::Goalie => {
+                if view.is_closer_to(view.own_goal()) {
+                    view.goalie(max_speed)
+                } else {
+                    Order::stop()
+                }
+            }
+            BuiltinBot::Chaser => view.chase(0.6 * max_speed),
+            BuiltinBot::Passing => {
+                if view.is_closer_to(view.ball) {
+                    view.attack(view.pass_or_shoot(), 0.8 * max_speed)
+                } else {
+                    view.support(0.8 * max_speed)
+                }
+            }
+            BuiltinBot::Strong => {
+                if view.is_closer_to(view.ball) {
+                    view.attack(view.best_shot(), max_speed)
+                } else {
+                    view.defend(max_speed)
+                }
+            }
+        };
+        view.to_action(order)
+    }
+}
+
+/// Ce que veut faire un robot, dans le repère de son équipe
+struct Order {
+    velocity: Vector2<f32>,
+    angular_velocity: f32,
+    kick: bool,
+}
+
+impl Order {
+    fn stop() -> Order {
+        Order {
+            velocity: Vector2::zeros(),
+            angular_velocity: 0.0,
+            kick: false,
+        }
+    }
+}
+
+/// Le terrain vu par l'équipe, comme pour le code python : elle attaque toujours le but des x négatifs.
+/// `heading` est la direction de l'avant du robot (radians, sens trigonométrique)
+struct TeamView {
+    switch_coordinates: bool,
+    /// Rayon des robots de l'équipe, selon leur profil
+    radius: f32,
+    me: Vector2<f32>,
+    heading: f32,
+    friend: Vector2<f32>,
+    enemies: [Vector2<f32>; 2],
+    ball: Vector2<f32>,
+}
+
+impl TeamView {
+    fn new(player_info: &PlayerInformation, radius: f32) -> TeamView {
+        let sign = if player_info.switch_coordinates {
+            -1.0
+        } else {
+            1.0
+        };
+        let point = |(x, y): (f32, f32)| Vector2::new(x, y) * sign;
+        let mut heading = front_angle(player_info.my_orientation);
+        if player_info.switch_coordinates {
+            heading += f32::consts::PI;
+        }
+        TeamView {
+            switch_coordinates: player_info.switch_coordinates,
+            radius,
+            me: point(player_info.my_position),
+            heading: normalize_angle(heading),
+            friend: point(player_info.friend_position),
+            enemies: [
+                point(player_info.enemy1_position),
+                point(player_info.enemy2_position),
+            ],
+            ball: point(player_info.ball_position),
+        }
+    }
+
+    fn to_action(&self, order: Order) -> PlayerAction {
+        // a rotation of pi does not change the direction of the angular velocity
+        let velocity = if self.switch_coordinates {
+            -order.velocity
+        } else {
+            order.velocity
+        };
+        PlayerAction {
+            movement: Movement::Velocity {
+                velocity: (velocity.x, velocity.y),
+                angular_velocity: order.angular_velocity,
+            },
+            kick: if order.kick { 1.0 } else { 0.0 },
+            kick_angle: 0.0,
+        }
+    }
+
+    fn opponent_goal(&self) -> Vector2<f32> {
+        Vector2::new(-infos::FIELD_DEPTH / 2.0, 0.0)
+    }
+
+    fn own_goal(&self) -> Vector2<f32> {
+        Vector2::new(infos::FIELD_DEPTH / 2.0, 0.0)
+    }
+
+    /// Ce robot est plus près de `point` que son coéquipier (à égalité, celui du haut)
+    fn is_closer_to(&self, point: Vector2<f32>) -> bool {
+        let mine = self.me.metric_distance(&point);
+        let friend = self.friend.metric_distance(&point);
+        mine < friend || (mine == friend && self.me.y < self.friend.y)
+    }
+
+    /// La balle est à portée du kicker, même règle que `Simulator::has_ball` :
+    /// un tir demandé sans elle serait ignoré mais compterait dans `kick_cooldown`
+    fn has_ball(&self) -> bool {
+        let kicker = self.me + self.radius * Vector2::new(self.heading.cos(), self.heading.sin());
+        kicker.metric_distance(&self.ball) <= infos::DISTANCE_MIN_KICKER_BALL
+    }
+
+    /// Va vers `target` en s'y arrêtant, tourné vers `heading`
+    fn go_to(&self, target: Vector2<f32>, heading: f32, max_speed: f32) -> Order {
+        Order {
+            velocity: ((target - self.me) * POSITION_GAIN).cap_magnitude(max_speed),
+            angular_velocity: normalize_angle(heading - self.heading) * ORIENTATION_GAIN,
+            kick: false,
+        }
+    }
+
+    /// Les deux robots se postent devant leur but, l'un au-dessus de l'autre
+    fn wall(&self, max_speed: f32) -> Order {
+        let x = infos::FIELD_DEPTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE - 2.0 * self.radius;
+        let y = if self.me.y <= self.friend.y {
+            -self.radius
+        } else {
+            self.radius
+        };
+        self.go_to(Vector2::new(x, y), f32::consts::PI, max_speed)
+    }
+
+    /// Suit la balle sur la largeur du but, et la dégage quand elle arrive tout près
+    fn goalie(&self, max_speed: f32) -> Order {
+        let line_x = infos::FIELD_DEPTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+        if self.ball.x > line_x - 40.0 && self.ball.x < line_x {
+            return self.attack(self.opponent_goal(), max_speed);
+        }
+        let y = self
+            .ball
+            .y
+            .clamp(-infos::GOAL_WIDTH / 2.0, infos::GOAL_WIDTH / 2.0);
+        self.go_to(
+            Vector2::new(line_x - self.radius - 4.0, y),
+            f32::consts::PI,
+            max_speed,
+        )
+    }
+
+    /// Fonce sur la balle face à elle, et tire dès qu'elle est devant le kicker
+    fn chase(&self, max_speed: f32) -> Order {
+        let to_ball = self.ball - self.me;
+        let mut order = self.go_to(self.ball, to_ball.y.atan2(to_ball.x), max_speed);
+        // go_to slows down near its target : keep pushing the ball
+        if let Some(direction) = to_ball.try_normalize(f32::EPSILON) {
+            order.velocity = direction * max_speed * 0.5 + order.velocity * 0.5;
+        }
+        order.kick = self.has_ball();
+        order
+    }
+
+    /// Se place derrière la balle dans l'axe de `aim`, puis la pousse et tire une fois aligné
+    fn attack(&self, aim: Vector2<f32>, max_speed: f32) -> Order {
+        let direction = (aim - self.ball)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vector2::new(-1.0, 0.0));
+        let aim_heading = direction.y.atan2(direction.x);
+        let contact_distance = self.radius + infos::BALL_RADIUS;
+        let behind = self.ball - direction * (contact_distance + 4.0);
+
+        let from_ball = self.me - self.ball;
+        let along = from_ball.dot(&direction);
+        let across = from_ball - direction * along;
+        let aligned = normalize_angle(aim_heading - self.heading).abs() < KICK_TOLERANCE;
+
+        if along < 0.0 && across.norm() < self.radius / 2.0 && aligned {
+            // behind the ball and facing the aim : push through the ball
+            let mut order = self.go_to(self.ball + direction * 10.0, aim_heading, max_speed);
+            order.velocity = direction * max_speed;
+            order.kick = self.has_ball();
+            return order;
+        }
+        if along > -contact_distance {
+            // in front of the ball : go around it on the side where the robot already is
+            let side = across
+                .try_normalize(f32::EPSILON)
+                .unwrap_or(Vector2::new(-direction.y, direction.x));
+            let detour = behind + side * (contact_distance + 8.0);
+            return self.go_to(detour, aim_heading, max_speed);
+        }
+        self.go_to(behind, aim_heading, max_speed)
+    }
+
+    /// Aucun adversaire ne se trouve près du segment entre la balle et `target`
+    fn lane_is_clear(&self, target: Vector2<f32>) -> bool {
+        self.enemies
+            .iter()
+            .all(|enemy| distance_to_segment(*enemy, self.ball, target) > LANE_MARGIN)
+    }
+
+    /// Tire si le chemin est libre ou le but proche, sinon passe au coéquipier s'il est mieux placé
+    fn pass_or_shoot(&self) -> Vector2<f32> {
+        let goal = self.opponent_goal();
+        let close_to_goal = self.ball.metric_distance(&goal) < 80.0;
+        let friend_ahead = self.friend.x < self.ball.x - 20.0;
+        if !close_to_goal
+            && !self.lane_is_clear(goal)
+            && friend_ahead
+            && self.lane_is_clear(self.friend)
+        {
+            self.friend
+        } else {
+            goal
+        }
+    }
+
+    /// Le point du but adverse le plus éloigné des adversaires qui bloquent le tir
+    fn best_shot(&self) -> Vector2<f32> {
+        let goal = self.opponent_goal();
+        [0.0, -0.35, 0.35]
+            .map(|part| goal + Vector2::new(0.0, part * infos::GOAL_WIDTH))
+            .into_iter()
+            .max_by(|a, b| {
+                let clearance = |target: &Vector2<f32>| {
+                    self.enemies
+                        .iter()
+                        .map(|enemy| distance_to_segment(*enemy, self.ball, *target))
+                        .fold(f32::INFINITY, f32::min)
+                };
+                clearance(a).total_cmp(&clearance(b))
+            })
+            .unwrap_or(goal)
+    }
+
+    /// Se démarque devant la balle, du côté opposé, tourné vers le but adverse
+    fn support(&self, max_speed: f32) -> Order {
+        let line_x = infos::FIELD_DEPTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+        let x = (self.ball.x - 70.0).clamp(-line_x + 30.0, line_x - 30.0);
+        let y = if self.ball.y > 0.0 { -35.0 } else { 35.0 };
+        self.go_to(Vector2::new(x, y), f32::consts::PI, max_speed)
+    }
+
+    /// Se place entre la balle et son but, au tiers du chemin, et garde le but quand la balle approche
+    fn defend(&self, max_speed: f32) -> Order {
+        if self.ball.x > 0.0 {
+            return self.goalie(max_speed);
+        }
+        let goal = self.own_goal();
+        let target = goal + (self.ball - goal) * 0.35;
+        let to_ball = self.ball - self.me;
+        self.go_to(target, to_ball.y.atan2(to_ball.x), max_speed)
+    }
+}
+
+fn distance_to_segment(point: Vector2<f32>, start: Vector2<f32>, end: Vector2<f32>) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.norm_squared();
+    if length_squared == 0.0 {
+        return point.metric_distance(&start);
+    }
+    let t = ((point - start).dot(&segment) / length_squared).clamp(0.0, 1.0);
+    point.metric_distance(&(start + segment * t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    /// Un robot de la première équipe en `me`, orienté vers `heading`, dans le repère du terrain
+    fn player_info(
+        me: (f32, f32),
+        heading: f32,
+        friend: (f32, f32),
+        enemies: [(f32, f32); 2],
+        ball: (f32, f32),
+    ) -> PlayerInformation {
+        PlayerInformation {
+            switch_coordinates: false,
+            robot_number: 1,
+            my_position: me,
+            // rapier angle of the robot, see `front_angle`
+            my_orientation: normalize_angle(heading + f32::consts::FRAC_PI_2),
+            friend_position: friend,
+            enemy1_position: enemies[0],
+            enemy2_position: enemies[1],
+            ball_position: ball,
+            orientation_error: 0.0,
+        }
+    }
+
+    /// La même situation vue par la seconde équipe : le terrain a tourné d'un demi-tour
+    fn mirrored(info: &PlayerInformation) -> PlayerInformation {
+        let flip = |(x, y): (f32, f32)| (-x, -y);
+        PlayerInformation {
+            switch_coordinates: true,
+            robot_number: info.robot_number,
+            my_position: flip(info.my_position),
+            my_orientation: normalize_angle(info.my_orientation + f32::consts::PI),
+            friend_position: flip(info.friend_position),
+            enemy1_position: flip(info.enemy1_position),
+            enemy2_position: flip(info.enemy2_position),
+            ball_position: flip(info.ball_position),
+            orientation_error: info.orientation_error,
+        }
+    }
+
+    fn velocity(action: &PlayerAction) -> (Vector2<f32>, f32) {
+        match action.movement {
+            Movement::Velocity {
+                velocity: (x, y),
+                angular_velocity,
+            } => (Vector2::new(x, y), angular_velocity),
+            _ => panic!("les équipes fournies sont en mode vitesse"),
+        }
+    }
+
+    #[test]
+    fn second_team_plays_the_mirrored_game() {
+        let situations = [
+            player_info(
+                (30.0, 20.0),
+                0.5,
+                (60.0, -30.0),
+                [(-40.0, 10.0), (-90.0, 0.0)],
+                (10.0, 15.0),
+            ),
+            player_info(
+                (-50.0, -40.0),
+                -2.0,
+                (80.0, 0.0),
+                [(-70.0, -20.0), (20.0, 30.0)],
+                (-60.0, -35.0),
+            ),
+            player_info(
+                (95.0, 5.0),
+                f32::consts::PI,
+                (10.0, 40.0),
+                [(60.0, 0.0), (0.0, -50.0)],
+                (85.0, 10.0),
+            ),
+        ];
+        for bot in BuiltinBot::ALL {
+            let code = PlayerCodeBuiltin::new(bot);
+            for info in &situations {
+                let action = code.tick(player_info(
+                    info.my_position,
+                    front_angle(info.my_orientation),
+                    info.friend_position,
+                    [info.enemy1_position, info.enemy2_position],
+                    info.ball_position,
+                ));
+                let mirrored_action = code.tick(mirrored(info));
+                let (v, w) = velocity(&action);
+                let (mirrored_v, mirrored_w) = velocity(&mirrored_action);
+                assert!(
+                    (v + mirrored_v).norm() < EPSILON,
+                    "{:?} : {} et {}",
+                    bot,
+                    v,
+                    mirrored_v
+                );
+                assert!(
+                    (w - mirrored_w).abs() < EPSILON,
+                    "{:?} : {} et {}",
+                    bot,
+                    w,
+                    mirrored_w
+                );
+                assert_eq!(action.kick, mirrored_action.kick, "{:?}", bot);
+            }
+        }
+    }
+
+    /// Différence de buts de `bot` contre `opponent` sur quelques matchs courts, en changeant de côté
+    fn goal_difference(bot: BuiltinBot, opponent: BuiltinBot) -> i64 {
+        use crate::match_config::{MatchConfig, MatchRules};
+
+        let mut difference = 0;
+        for seed in 0..4 {
+            let swapped = seed % 2 == 1;
+            let teams = if swapped {
+                [opponent, bot]
+            } else {
+                [bot, opponent]
+            };
+            let mut config = MatchConfig::builtin(teams, seed);
+            config.settings.rules = MatchRules {
+                duration: Some(60),
+                max_score: None,
+                kickoff_jitter: 5.0,
+            };
+            let result = crate::headless::play_match(&config, None, None).unwrap();
+            let side = if swapped { 1 } else { 0 };
+            difference += result.scores[side] as i64 - result.scores[1 - side] as i64;
+        }
+        difference
+    }
+
+    /// Les niveaux 1 et 2 ne font que défendre : ils ne peuvent pas battre le niveau précédent,
+    /// seulement ne pas perdre. À partir des Chasseurs, chaque niveau doit gagner.
+    #[test]
+    fn each_level_beats_the_previous_one() {
+        for pair in BuiltinBot::ALL.windows(2) {
+            let (previous, bot) = (pair[0], pair[1]);
+            let difference = goal_difference(bot, previous);
+            if bot.difficulty() < BuiltinBot::Chaser.difficulty() {
+                assert!(difference >= 0, "{:?} perd contre {:?}", bot, previous);
+            } else {
+                assert!(
+                    difference > 0,
+                    "{:?} ne bat pas {:?} ({} buts d'écart)",
+                    bot,
+                    previous,
+                    difference
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn distance_to_segment_cases() {
+        let start = Vector2::new(0.0, 0.0);
+        let end = Vector2::new(10.0, 0.0);
+        // projection inside the segment
+        assert!((distance_to_segment(Vector2::new(4.0, 3.0), start, end) - 3.0).abs() < EPSILON);
+        // beyond each end : distance to the end
+        assert!((distance_to_segment(Vector2::new(-3.0, 4.0), start, end) - 5.0).abs() < EPSILON);
+        assert!((distance_to_segment(Vector2::new(13.0, -4.0), start, end) - 5.0).abs() < EPSILON);
+        // on the segment
+        assert!(distance_to_segment(Vector2::new(7.0, 0.0), start, end) < EPSILON);
+        // empty segment : distance to the point
+        assert!((distance_to_segment(Vector2::new(3.0, 4.0), start, start) - 5.0).abs() < EPSILON);
+    }
+
+    fn view(enemies: [Vector2<f32>; 2], ball: Vector2<f32>) -> TeamView {
+        TeamView {
+            switch_coordinates: false,
+            radius: infos::ROBOT_RADIUS,
+            me: ball + Vector2::new(15.0, 0.0),
+            heading: f32::consts::PI,
+            friend: Vector2::new(80.0, 0.0),
+            enemies,
+            ball,
+        }
+    }
+
+    #[test]
+    fn has_ball_uses_the_kicker_range_of_the_simulator() {
+        let ball = Vector2::new(-40.0, 0.0);
+        // `view` puts the robot 15 cm from the ball, facing it
+        let facing = view([Vector2::new(100.0, 80.0); 2], ball);
+        let kicker =
+            facing.me + facing.radius * Vector2::new(facing.heading.cos(), facing.heading.sin());
+        let at = |distance: f32, angle: f32| TeamView {
+            ball: kicker + distance * Vector2::new(angle.cos(), angle.sin()),
+            ..view([Vector2::new(100.0, 80.0); 2], ball)
+        };
+        assert!(at(0.0, 0.0).has_ball());
+        assert!(at(infos::DISTANCE_MIN_KICKER_BALL - 0.1, 1.0).has_ball());
+        assert!(!at(infos::DISTANCE_MIN_KICKER_BALL + 0.1, f32::consts::PI).has_ball());
+
+        // the ball 16 cm from the centre of the robot, 30° off its heading : out of the kicker's reach
+        let off_axis = facing.heading + 30.0_f32.to_radians();
+        let loose = TeamView {
+            ball: facing.me
+                + (facing.radius + infos::BALL_RADIUS + infos::DISTANCE_MIN_KICKER_BALL)
+                    * Vector2::new(off_axis.cos(), off_axis.sin()),
+            ..view([Vector2::new(100.0, 80.0); 2], ball)
+        };
+        assert!(!loose.has_ball());
+    }
+
+    #[test]
+    fn best_shot_avoids_the_blocking_enemy() {
+        let goal = Vector2::new(-infos::FIELD_DEPTH / 2.0, 0.0);
+        let ball = Vector2::new(-40.0, 0.0);
+        let far_away = Vector2::new(100.0, 80.0);
+
+        // enemies in front of both posts : the centre of the goal
+        let posts = [
+            Vector2::new(-100.0, -0.3 * infos::GOAL_WIDTH),
+            Vector2::new(-100.0, 0.3 * infos::GOAL_WIDTH),
+        ];
+        assert_eq!(view(posts, ball).best_shot(), goal);
+
+        // an enemy in front of the centre : one of the sides
+        let shot = view([Vector2::new(-90.0, 0.0), far_away], ball).best_shot();
+        assert!((shot.y.abs() - 0.35 * infos::GOAL_WIDTH).abs() < EPSILON);
+
+        // an enemy covering the top of the goal : the bottom side
+        let shot = view(
+            [Vector2::new(-100.0, -0.3 * infos::GOAL_WIDTH), far_away],
+            ball,
+        )
+        .best_shot();
+        assert!((shot.y - 0.35 * infos::GOAL_WIDTH).abs() < EPSILON);
+        assert_eq!(shot.x, goal.x);
+    }
+}
@@ -0,0 +1,374 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    batch::{self, BatchOptions},
+    headless::{self, HeadlessError},
+    match_config::{MatchConfig, MatchSettings},
+    ratings::{self, RatingLedger},
+    scenario::Scenario,
+    scenario_test::{self, ScenarioTestError},
+    tournament::{self, TournamentFormat, TournamentOptions},
+};
+
+const USAGE: &str = "Utilisation :
+  simulator [match.toml]
+      ouvre l'interface, avec une configuration de match optionnelle
+  simulator match <match.toml> [--result resultat.json] [--record match.rrd] [--events evenements.jsonl] [--scenario scenario.toml] [--ledger classement.jsonl]
+      joue un match sans interface et affiche le score, éventuellement depuis un scénario
+  simulator tournament [--knockout] [--jobs N] [--output DOSSIER] [--settings match.toml] [--ledger classement.jsonl] <equipe1.py> <equipe2.py> ...
+      joue un tournoi entre toutes les équipes (championnat par défaut)
+  simulator batch <match.toml> [-n NB_MATCHS] [--jobs N] [--output DOSSIER] [--jitter CM] [--record] [--ledger classement.jsonl]
+      évalue la première équipe contre la seconde sur de nombreux matchs
+  simulator ratings [--ledger classement.jsonl] [--history EQUIPE]
+      affiche le classement Elo des versions de chaque équipe, ou l'historique d'une équipe
+  simulator test <tests.toml> [--junit rapport.xml]
+      joue les scénarios de test d'une équipe et vérifie leurs assertions, rapport JUnit optionnel
+--ledger ajoute les matchs terminés au classement Elo (fichier par défaut : classement_elo.jsonl)";
+
+/// Ce que l'utilisateur a demandé sur la ligne de commande.
+/// `ledger_path` : registre du classement Elo où ajouter les matchs, aucun si absent
+pub enum Command {
+    Ui {
+        match_config_path: Option<String>,
+    },
+    Match {
+        config_path: PathBuf,
+        result_path: Option<PathBuf>,
+        record_path: Option<PathBuf>,
+        events_path: Option<PathBuf>,
+        scenario_path: Option<PathBuf>,
+        ledger_path: Option<PathBuf>,
+    },
+    Tournament {
+        options: TournamentOptions,
+        ledger_path: Option<PathBuf>,
+    },
+    Batch {
+        options: BatchOptions,
+        ledger_path: Option<PathBuf>,
+    },
+    Ratings {
+        ledger_path: PathBuf,
+        history: Option<String>,
+    },
+    Test {
+        suite_path: PathBuf,
+        junit_path: Option<PathBuf>,
+        /// Un seul test et le fichier où écrire son rapport, pour `scenario_test::run_suite`
+        single_case: Option<(usize, PathBuf)>,
+    },
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        Some("match") => {
+            let mut config_path = None;
+            let mut result_path = None;
+            let mut record_path = None;
+            let mut events_path = None;
+            let mut scenario_path = None;
+            let mut ledger_path = None;
+            let mut args = args[1..].iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--result" => result_path = Some(PathBuf::from(option_value(&mut args, arg)?)),
+                    "--record" => record_path = Some(PathBuf::from(option_value(&mut args, arg)?)),
+                    "--events" => events_path = Some(PathBuf::from(option_value(&mut args, arg)?)),
+                    "--scenario" => {
+                        scenario_path = Some(PathBuf::from(option_value(&mut args, arg)?))
+                    }
+                    "--ledger" => ledger_path = Some(PathBuf::from(option_value(&mut args, arg)?)),
+                    _ if config_path.is_none() => config_path = Some(PathBuf::from(arg)),
+                    _ => return Err(format!("Argument inattendu : {}", arg)),
+                }
+            }
+            Ok(Command::Match {
+                config_path: config_path
+                    .ok_or_else(|| "Il manque le fichier de configuration du match".to_owned())?,
+                result_path,
+                record_path,
+                events_path,
+                scenario_path,
+                ledger_path,
+            })
+        }
+        Some("tournament") => {
+            let mut format = TournamentFormat::RoundRobin;
+            let mut nb_workers = default_nb_workers();
+            let mut output_dir = PathBuf::from("tournoi");
+            let mut settings = MatchSettings::default();
+            let mut scripts = Vec::new();
+            let mut ledger_path = None;
+            let mut args = args[1..].iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--knockout" => format = TournamentFormat::Knockout,
+                    "--jobs" => nb_workers = parse_jobs(option_value(&mut args, arg)?)?,
+                    "--output" => output_dir = PathBuf::from(option_value(&mut args, arg)?),
+                    "--settings" => {
+                        let value = option_value(&mut args, arg)?;
+                        settings = MatchSettings::load(Path::new(value))
+                            .map_err(|err| format!("{}", err))?;
+                    }
+                    "--ledger" => ledger_path = Some(PathBuf::from(option_value(&mut args, arg)?)),
+                    _ if arg.starts_with("--") => {
+                        return Err(format!("Argument inattendu : {}", arg));
+                    }
+                    _ => scripts.push(arg.clone()),
+                }
+            }
+            Ok(Command::Tournament {
+                options: TournamentOptions {
+                    format,
+                    scripts,
+                    settings,
+                    output_dir,
+                    nb_workers,
+                },
+                ledger_path,
+            })
+        }
+        Some("batch") => {
+            let mut config_path = None;
+            let mut nb_matches = 100;
+            let mut nb_workers = default_nb_workers();
+            let mut output_dir = PathBuf::from("evaluation");
+            let mut kickoff_jitter = None;
+            let mut record = false;
+            let mut ledger_path = None;
+            let mut args = args[1..].iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "-n" => {
+                        let value = option_value(&mut args, arg)?;
+                        nb_matches = value
+                            .parse()
+                            .map_err(|_| format!("-n attend un nombre, pas {}", value))?;
+                    }
+                    "--jobs" => nb_workers = parse_jobs(option_value(&mut args, arg)?)?,
+                    "--output" => output_dir = PathBuf::from(option_value(&mut args, arg)?),
+                    "--jitter" => {
+                        let value = option_value(&mut args, arg)?;
+                        kickoff_jitter =
+                            Some(value.parse().map_err(|_| {
+                                format!("--jitter attend un nombre, pas {}", value)
+                            })?);
+                    }
+                    "--record" => record = true,
+                    "--ledger" => ledger_path = Some(PathBuf::from(option_value(&mut args, arg)?)),
+                    _ if config_path.is_none() => config_path = Some(PathBuf::from(arg)),
+                    _ => return Err(format!("Argument inattendu : {}", arg)),
+                }
+            }
+            let config_path = config_path
+                .ok_or_else(|| "Il manque le fichier de configuration du match".to_owned())?;
+            Ok(Command::Batch {
+                options: BatchOptions {
+                    config: MatchConfig::load(&config_path).map_err(|err| format!("{}", err))?,
+                    nb_matches,
+                    nb_workers,
+                    output_dir,
+                    kickoff_jitter,
+                    record,
+                },
+                ledger_path,
+            })
+        }
+        Some("ratings") => {
+            let mut ledger_path = PathBuf::from(ratings::DEFAULT_LEDGER_PATH);
+            let mut history = None;
+            let mut args = args[1..].iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--ledger" => ledger_path = PathBuf::from(option_value(&mut args, arg)?),
+                    "--history" => history = Some(option_value(&mut args, arg)?.clone()),
+                    _ => return Err(format!("Argument inattendu : {}", arg)),
+                }
+            }
+            Ok(Command::Ratings {
+                ledger_path,
+                history,
+            })
+        }
+        Some("test") => {
+            let mut suite_path = None;
+            let mut junit_path = None;
+            let mut case = None;
+            let mut case_report_path = None;
+            let mut args = args[1..].iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--junit" => junit_path = Some(PathBuf::from(option_value(&mut args, arg)?)),
+                    // internal options, each test of a suite is played by its own process
+                    "--case" => {
+                        let value = option_value(&mut args, arg)?;
+                        case = Some(
+                            value
+                                .parse()
+                                .map_err(|_| format!("--case attend un nombre, pas {}", value))?,
+                        );
+                    }
+                    "--case-report" => {
+                        case_report_path = Some(PathBuf::from(option_value(&mut args, arg)?))
+                    }
+                    _ if suite_path.is_none() => suite_path = Some(PathBuf::from(arg)),
+                    _ => return Err(format!("Argument inattendu : {}", arg)),
+                }
+            }
+            let single_case = match (case, case_report_path) {
+                (Some(case), Some(case_report_path)) => Some((case, case_report_path)),
+                (None, None) => None,
+                _ => return Err("--case et --case-report vont ensemble".to_owned()),
+            };
+            Ok(Command::Test {
+                suite_path: suite_path.ok_or_else(|| "Il manque le fichier de tests".to_owned())?,
+                junit_path,
+                single_case,
+            })
+        }
+        Some("--help" | "-h") => Err(USAGE.to_owned()),
+        match_config_path => Ok(Command::Ui {
+            match_config_path: match_config_path.map(str::to_owned),
+        }),
+    }
+}
+
+fn option_value<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+    option: &str,
+) -> Result<&'a String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} attend une valeur\n\n{}", option, USAGE))
+}
+
+/// Un processus par coeur
+fn default_nb_workers() -> usize {
+    std::thread::available_parallelism().map_or(1, |nb| nb.get())
+}
+
+fn parse_jobs(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("--jobs attend un nombre, pas {}", value))
+}
+
+fn load_ledger(ledger_path: Option<&Path>) -> Result<Option<RatingLedger>, ratings::RatingError> {
+    ledger_path.map(RatingLedger::load).transpose()
+}
+
+pub fn run_match(
+    config_path: &Path,
+    result_path: Option<&Path>,
+    record_path: Option<&Path>,
+    events_path: Option<&Path>,
+    scenario_path: Option<&Path>,
+    ledger_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ledger = load_ledger(ledger_path)?;
+    let mut config = MatchConfig::load(config_path).map_err(HeadlessError::Config)?;
+    if let Some(scenario_path) = scenario_path {
+        config.settings.scenario = Some(Scenario::load(scenario_path)?);
+    }
+    let result = headless::play_match(&config, record_path, events_path)?;
+    if let Some(result_path) = result_path {
+        headless::write_result(&result, result_path)?;
+    }
+    if let Some(ledger) = &mut ledger {
+        headless::record_result(ledger, &result, &config);
+    }
+    println!(
+        "{} {} - {} {}",
+        result.team_names[0], result.scores[0], result.scores[1], result.team_names[1]
+    );
+    Ok(())
+}
+
+pub fn run_tournament(
+    options: &TournamentOptions,
+    ledger_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ledger = load_ledger(ledger_path)?;
+    let tournament =
+        tournament::run_tournament(options, ledger.as_mut(), &mut |tournament, played| {
+            let names = played
+                .teams
+                .map(|team| tournament.participants[team].name.as_str());
+            match (&played.result, &played.error) {
+                (Some(result), _) => println!(
+                    "Round {} : {} {} - {} {}",
+                    played.round + 1,
+                    names[0],
+                    result.scores[0],
+                    result.scores[1],
+                    names[1]
+                ),
+                (None, error) => eprintln!(
+                    "Round {} : {} - {} n'a pas pu être joué : {}",
+                    played.round + 1,
+                    names[0],
+                    names[1],
+                    error.as_deref().unwrap_or_default()
+                ),
+            }
+        })?;
+    tournament.export(&options.output_dir)?;
+    println!("\n{}", tournament.standings_table());
+    println!(
+        "Résultats enregistrés dans {}",
+        options.output_dir.display()
+    );
+    Ok(())
+}
+
+pub fn run_batch(
+    options: &BatchOptions,
+    ledger_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ledger = load_ledger(ledger_path)?;
+    let summary = batch::run_batch(options, ledger.as_mut())?;
+    summary.export(&options.output_dir)?;
+    println!("\n{}", summary.report());
+    println!(
+        "Résultats enregistrés dans {}",
+        options.output_dir.display()
+    );
+    Ok(())
+}
+
+pub fn show_ratings(
+    ledger_path: &Path,
+    history: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ledger = RatingLedger::load(ledger_path)?;
+    match history {
+        Some(team_name) => print!("{}", ledger.history_table(team_name)),
+        None => print!("{}", ledger.leaderboard_table()),
+    }
+    Ok(())
+}
+
+/// Échoue si un test échoue, pour que l'intégration continue le remarque
+pub fn run_tests(
+    suite_path: &Path,
+    junit_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = scenario_test::run_suite(suite_path)?;
+    if let Some(junit_path) = junit_path {
+        report.export_junit(junit_path)?;
+    }
+    println!("{}", report.summary());
+    match report.nb_failed() {
+        0 => Ok(()),
+        nb_failed => Err(ScenarioTestError::TestsFailed(nb_failed, report.cases.len()).into()),
+    }
+}
+
+/// Joue un seul test pour `scenario_test::run_suite` et écrit son rapport
+pub fn run_test_case(
+    suite_path: &Path,
+    case: usize,
+    case_report_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    scenario_test::run_single_case(suite_path, case)?.write(case_report_path)?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use nalgebra::Vector2;
+use rerun::external::egui;
+use rerun::external::re_types::blueprint::{archetypes, components};
+use rerun::external::re_viewer;
+
+use crate::{field_view, infos, simulator::Simulator, vector_converter::FieldTransform};
+
+/// Saisie de la balle ou d'un robot à la souris, directement dans la vue 2D du viewer.
+/// Le viewer ne donne pas sa caméra : on retrouve le rectangle de la vue parmi les widgets egui
+/// et la partie du terrain qu'elle montre dans le blueprint, pour en déduire un `FieldTransform`.
+/// L'objet lâché garde la vitesse du geste : on peut lancer la balle.
+#[derive(Debug, Default)]
+pub struct FieldInteraction {
+    pub enabled: bool,
+    /// Objet tenu à la souris : les robots (0 à 3) puis la balle (4), et la vue où il a été saisi
+    dragged: Option<(usize, FieldTransform)>,
+    /// Dernière vue trouvée sous la souris : la zone de saisie d'un objet la cache ensuite à egui
+    view: Option<(egui::Rect, FieldTransform)>,
+}
+
+impl FieldInteraction {
+    /// Renvoie `true` si un objet a été déplacé pendant cette image.
+    /// A appeler avant d'afficher le viewer, pour prendre la souris avant la vue (qui se déplacerait sinon avec elle).
+    pub fn viewer(
+        &mut self,
+        ctx: &egui::Context,
+        rerun_app: &re_viewer::App,
+        simulation: &mut Simulator,
+    ) -> bool {
+        if !self.enabled {
+            self.dragged = None;
+            return false;
+        }
+        let Some(pointer) = ctx.pointer_latest_pos() else {
+            return false;
+        };
+        if let Some(rect) = view_rect_under(ctx, pointer) {
+            self.view = Some((
+                rect,
+                FieldTransform::letterbox(rect, visual_bounds(rerun_app)),
+            ));
+        }
+        let (target, transform) = match self.dragged {
+            Some(dragged) => dragged,
+            None => {
+                let Some((rect, transform)) = self.view else {
+                    return false;
+                };
+                if !rect.contains(pointer) {
+                    return false;
+                }
+                let Some(target) =
+                    field_view::pick(transform.to_field(pointer), objects(simulation))
+                else {
+                    return false;
+                };
+                (target, transform)
+            }
+        };
+
+        // a widget above the viewer, on the object, takes the drag from the view
+        let (position, radius) = objects(simulation).nth(target).unwrap();
+        let grab_size = 2.0 * (radius + field_view::GRAB_MARGIN) * transform.scale;
+        let grab_rect = egui::Rect::from_center_size(
+            transform.to_screen(position),
+            egui::Vec2::splat(grab_size),
+        );
+        let response = egui::Area::new(egui::Id::new("field_interaction"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(grab_rect.min)
+            .show(ctx, |ui| {
+                ui.allocate_exact_size(grab_rect.size(), egui::Sense::drag())
+                    .1
+            })
+            .inner
+            .on_hover_cursor(egui::CursorIcon::Grab);
+
+        if response.drag_started() {
+            self.dragged = Some((target, transform));
+        }
+        let mut moved = false;
+        if self.dragged.is_some()
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            let position = field_view::clamp_to_field(transform.to_field(pointer));
+            // the body follows the pointer, and keeps its speed when released
+            let velocity = transform.velocity_to_field(ctx.input(|input| input.pointer.velocity()));
+            if target < simulation.robots.len() {
+                simulation.move_robot_by_hand(target, position, velocity);
+            } else {
+                simulation.move_ball_by_hand(position, velocity);
+            }
+            moved = true;
+        }
+        if !response.dragged() {
+            self.dragged = None;
+        }
+        moved
+    }
+}
+
+/// Position et rayon des robots puis de la balle
+fn objects(simulation: &Simulator) -> impl Iterator<Item = (Vector2<f32>, f32)> + '_ {
+    simulation
+        .robots
+        .iter()
+        .map(|robot_handle| {
+            (
+                simulation.position_of(robot_handle),
+                simulation.robot_builders[robot_handle].radius,
+            )
+        })
+        .chain([(simulation.position_of_ball(), infos::BALL_RADIUS)])
+}
+
+/// Une vue 2D du viewer est le plus grand widget qui se clique et se saisit sous la souris
+fn view_rect_under(ctx: &egui::Context, pointer: egui::Pos2) -> Option<egui::Rect> {
+    let ids: Vec<egui::Id> =
+        ctx.interaction_snapshot(|snapshot| snapshot.contains_pointer.iter().copied().collect());
+    ids.into_iter()
+        .filter_map(|id| ctx.read_response(id))
+        .filter(|response| {
+            response.sense == egui::Sense::click_and_drag() && response.rect.contains(pointer)
+        })
+        .map(|response| response.rect)
+        .max_by(|rect1, rect2| rect1.area().total_cmp(&rect2.area()))
+}
+
+/// Partie du terrain que montre la vue : celle du blueprint si on a déplacé ou zoomé la vue,
+/// sinon tout ce qui est dessiné, c'est-à-dire le terrain murs compris
+fn visual_bounds(rerun_app: &re_viewer::App) -> egui::Rect {
+    let field = egui::Rect::from_center_size(
+        egui::Pos2::ZERO,
+        egui::vec2(infos::FIELD_DEPTH, infos::FIELD_WIDTH),
+    );
+    let Some(store_id) = rerun_app.recording_db().map(|db| db.store_id().clone()) else {
+        return field;
+    };
+    let Some(blueprint_ctx) = rerun_app.blueprint_ctx(&store_id) else {
+        return field;
+    };
+    let blueprint = blueprint_ctx.current_blueprint;
+    let descriptor = archetypes::VisualBounds2D::descriptor_range();
+    // `/view/<id>/VisualBounds2D`, written by the viewer once the view has been panned or zoomed
+    blueprint
+        .entity_paths()
+        .into_iter()
+        .filter(|path| {
+            path.last()
+                .is_some_and(|part| part.unescaped_str() == "VisualBounds2D")
+        })
+        .find_map(|path| {
+            blueprint.latest_at_component::<components::VisualBounds2D>(
+                path,
+                &blueprint_ctx.blueprint_query,
+                &descriptor,
+            )
+        })
+        .map_or(field, |(_, bounds)| bounds.into())
+}
@@ -0,0 +1,108 @@
+use nalgebra::Vector2;
+use rerun::external::egui::{self, Align2, Color32, FontId, Stroke, Vec2};
+
+use crate::{infos, vector_converter::FieldTransform};
+
+pub const BALL_COLOR: Color32 = Color32::from_rgb(255, 128, 0);
+/// Un clic à moins de cette distance (cm) du bord d'un objet le sélectionne
+pub const GRAB_MARGIN: f32 = 3.0;
+
+/// Réserve la place d'un terrain vu de dessus, d'au plus `max_width` points de large, qu'on peut saisir à la souris
+pub fn allocate_field(
+    ui: &mut egui::Ui,
+    max_width: f32,
+) -> (egui::Response, egui::Painter, FieldTransform) {
+    let width = ui.available_width().min(max_width);
+    let scale = width / infos::FIELD_DEPTH;
+    let (response, painter) = ui.allocate_painter(
+        Vec2::new(width, infos::FIELD_WIDTH * scale),
+        egui::Sense::drag(),
+    );
+    let transform = FieldTransform::fit(response.rect, infos::FIELD_DEPTH);
+    (response, painter, transform)
+}
+
+/// Pelouse, lignes et buts, dans le même sens que le viewer (y vers le bas)
+pub fn paint_field(painter: &egui::Painter, transform: &FieldTransform) {
+    let field_rect = egui::Rect::from_center_size(
+        transform.center,
+        Vec2::new(infos::FIELD_DEPTH, infos::FIELD_WIDTH) * transform.scale,
+    );
+    painter.rect_filled(field_rect, 0.0, Color32::from_rgb(0, 160, 0));
+    let line_x = infos::FIELD_DEPTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+    let line_y = infos::FIELD_WIDTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+    let to_screen = |x: f32, y: f32| transform.to_screen(Vector2::new(x, y));
+    painter.add(egui::Shape::closed_line(
+        vec![
+            to_screen(-line_x, -line_y),
+            to_screen(line_x, -line_y),
+            to_screen(line_x, line_y),
+            to_screen(-line_x, line_y),
+        ],
+        Stroke::new(1.5, Color32::WHITE),
+    ));
+    for side in [-1.0, 1.0] {
+        painter.line_segment(
+            [
+                to_screen(side * line_x, -infos::GOAL_WIDTH / 2.0),
+                to_screen(side * line_x, infos::GOAL_WIDTH / 2.0),
+            ],
+            Stroke::new(4.0, Color32::from_gray(40)),
+        );
+    }
+}
+
+/// Robot vu de dessus : un disque, un trait vers l'avant (`front_angle`, voir `simulator::front_angle`) et son numéro
+pub fn paint_robot(
+    painter: &egui::Painter,
+    transform: &FieldTransform,
+    position: Vector2<f32>,
+    radius: f32,
+    front_angle: f32,
+    color: Color32,
+    label: &str,
+) {
+    let center = transform.to_screen(position);
+    painter.circle_filled(center, radius * transform.scale, color);
+    painter.line_segment(
+        [
+            center,
+            center
+                + Vec2::new(front_angle.cos(), front_angle.sin()) * 1.4 * radius * transform.scale,
+        ],
+        Stroke::new(2.0, Color32::BLACK),
+    );
+    painter.text(
+        center,
+        Align2::CENTER_CENTER,
+        label,
+        FontId::proportional(12.0),
+        Color32::WHITE,
+    );
+}
+
+/// Index de l'objet (position, rayon) sous `pointer`, le plus proche s'il y en a plusieurs
+pub fn pick(
+    pointer: Vector2<f32>,
+    objects: impl IntoIterator<Item = (Vector2<f32>, f32)>,
+) -> Option<usize> {
+    objects
+        .into_iter()
+        .map(|(position, radius)| (position.metric_distance(&pointer), radius))
+        .enumerate()
+        .filter(|(_, (distance, radius))| *distance <= radius + GRAB_MARGIN)
+        .min_by(|(_, (d1, _)), (_, (d2, _))| d1.total_cmp(d2))
+        .map(|(n, _)| n)
+}
+
+/// Garde un point sur le terrain, murs compris
+pub fn clamp_to_field(position: Vector2<f32>) -> Vector2<f32> {
+    Vector2::new(
+        position
+            .x
+            .clamp(-infos::FIELD_DEPTH / 2.0, infos::FIELD_DEPTH / 2.0),
+        position
+            .y
+            .clamp(-infos::FIELD_WIDTH / 2.0, infos::FIELD_WIDTH / 2.0),
+    )
+}
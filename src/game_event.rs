@@ -0,0 +1,141 @@
+use std::fmt::Display;
+
+use crossbeam::channel::{Receiver, Sender};
+use serde::{Deserialize, Serialize};
+
+use crate::simulator::FieldWallKind;
+
+/// Ce qui se passe pendant un match.
+/// Le format JSON (`type` + champs) est stable : de nouveaux types peuvent être ajoutés,
+/// mais les champs existants ne changent pas de nom ni de sens.
+/// Les robots sont désignés par leur nom (`equipe_robot1`), les positions sont dans le repère du terrain.
+/// Il n'y a pas d'évènement de pénalité : le simulateur n'en donne aucune. La seule faute qu'il pourrait constater,
+/// un robot entièrement sorti des lignes, est impossible (les murs sont à `SPACE_BEFORE_LINE_SIDE` des lignes,
+/// moins que le diamètre d'un robot), et les autres (poussée, manque de progression) demandent un arbitre.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// La balle touche un robot
+    Touch { robot: String, position: (f32, f32) },
+    /// Deux robots se touchent
+    RobotCollision {
+        robot1: String,
+        robot2: String,
+        position: (f32, f32),
+    },
+    /// Un robot (`robot`) ou la balle (`robot` absent) touche un mur
+    WallHit {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        robot: Option<String>,
+        wall: FieldWallKind,
+        position: (f32, f32),
+    },
+    /// Un robot déclenche son kicker. `effective` : la balle était à portée
+    Kick {
+        robot: String,
+        power: f32,
+        effective: bool,
+        position: (f32, f32),
+    },
+    /// `team` vient de marquer, `score` est le score après le but
+    Goal {
+        team: String,
+        score: [u32; 2],
+        position: (f32, f32),
+    },
+    /// La balle franchit une ligne du terrain ailleurs que dans un but
+    OutOfBounds { position: (f32, f32) },
+    /// Les robots et la balle sont replacés
+    KickOff,
+}
+
+/// Un évènement et le moment où il s'est produit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimedEvent {
+    pub tick: u64,
+    pub time: f32, // secondes
+    #[serde(flatten)]
+    pub event: GameEvent,
+}
+
+impl GameEvent {
+    /// Nom du type, le même que dans le JSON
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GameEvent::Touch { .. } => "touch",
+            GameEvent::RobotCollision { .. } => "robot_collision",
+            GameEvent::WallHit { .. } => "wall_hit",
+            GameEvent::Kick { .. } => "kick",
+            GameEvent::Goal { .. } => "goal",
+            GameEvent::OutOfBounds { .. } => "out_of_bounds",
+            GameEvent::KickOff => "kick_off",
+        }
+    }
+
+    pub fn position(&self) -> Option<(f32, f32)> {
+        match self {
+            GameEvent::Touch { position, .. }
+            | GameEvent::RobotCollision { position, .. }
+            | GameEvent::WallHit { position, .. }
+            | GameEvent::Kick { position, .. }
+            | GameEvent::Goal { position, .. }
+            | GameEvent::OutOfBounds { position } => Some(*position),
+            GameEvent::KickOff => None,
+        }
+    }
+}
+
+impl Display for GameEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameEvent::Touch { robot, .. } => write!(f, "{} touche la balle", robot),
+            GameEvent::RobotCollision { robot1, robot2, .. } => {
+                write!(f, "{} percute {}", robot1, robot2)
+            }
+            GameEvent::WallHit {
+                robot: Some(robot),
+                wall,
+                ..
+            } => write!(f, "{} touche le mur {:?}", robot, wall),
+            GameEvent::WallHit {
+                robot: None, wall, ..
+            } => write!(f, "la balle touche le mur {:?}", wall),
+            GameEvent::Kick {
+                robot,
+                power,
+                effective: true,
+                ..
+            } => write!(f, "{} tire ({:.0} %)", robot, power * 100.0),
+            GameEvent::Kick {
+                robot,
+                effective: false,
+                ..
+            } => write!(f, "{} tire dans le vide", robot),
+            GameEvent::Goal { team, score, .. } => {
+                write!(f, "BUT de {} ! {} - {}", team, score[0], score[1])
+            }
+            GameEvent::OutOfBounds { .. } => write!(f, "la balle sort du terrain"),
+            GameEvent::KickOff => write!(f, "engagement"),
+        }
+    }
+}
+
+/// Diffuse les évènements à tous les abonnés.
+/// Un abonné qui a lâché son `Receiver` est oublié au prochain évènement.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Sender<TimedEvent>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self) -> Receiver<TimedEvent> {
+        let (sender, recv) = crossbeam::channel::unbounded();
+        self.subscribers.push(sender);
+        recv
+    }
+
+    pub fn publish(&mut self, event: TimedEvent) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
 
 use crate::infos;
 use crate::simulator::FieldWallKind;
@@ -10,7 +11,7 @@ pub enum RefereeAction {
 }
 
 /// Stocke les données de la partie mais ne modifie pas directement l'action de la partie
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GameReferee {
     pub score_team_left: u32,
     pub score_team_right: u32,
@@ -32,7 +33,6 @@ impl GameReferee {
                 FieldWallKind::Right => self.score_team_right += 1,
                 _ => panic!("maybe_goal called with unexpected values"),
             }
-            println!("GOAL");
             return RefereeAction::NewRound;
         }
         return RefereeAction::ContinueMatch;
@@ -0,0 +1,274 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_ui::{APP_ID, AppRunning},
+    infos,
+    match_config::{MatchConfig, MatchConfigError},
+    match_stats::MatchStats,
+    player_action::{CodeValidationError, PlayerCode, validate_path},
+    ratings::RatingLedger,
+    robot_profile::RobotProfileError,
+    simulator::Simulator,
+};
+
+/// Résultat d'un match joué sans interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub team_names: [String; 2],
+    /// `TEAM_NAME` des scripts, avant les suffixes `_1` et `_2` ajoutés quand ils sont identiques
+    pub script_team_names: [String; 2],
+    pub scores: [u32; 2],
+    pub duration: f32, // secondes de jeu
+    pub seed: u64,
+    pub player_code_errors: u64,
+    #[serde(default)]
+    pub stats: MatchStats,
+}
+
+impl MatchResult {
+    /// Index de l'équipe gagnante, `None` en cas de match nul
+    pub fn winner(&self) -> Option<usize> {
+        match self.scores[0].cmp(&self.scores[1]) {
+            std::cmp::Ordering::Greater => Some(0),
+            std::cmp::Ordering::Less => Some(1),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    Config(MatchConfigError),
+    InvalidTeam {
+        script: String,
+        err: CodeValidationError,
+    },
+    InvalidRobotProfile {
+        path: String,
+        err: RobotProfileError,
+    },
+    Recording(String),
+    Io(String),
+    Worker(String),
+}
+
+impl Display for HeadlessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeadlessError::Config(err) => write!(f, "{}", err),
+            HeadlessError::InvalidTeam { script, err } => {
+                write!(f, "Le code de l'équipe {} est invalide : {}", script, err)
+            }
+            HeadlessError::InvalidRobotProfile { path, err } => {
+                write!(f, "Le profil {} est invalide : {}", path, err)
+            }
+            HeadlessError::Recording(err_str) => {
+                write!(f, "Impossible d'enregistrer le match : {}", err_str)
+            }
+            HeadlessError::Io(err_str) => write!(f, "Erreur de fichier : {}", err_str),
+            HeadlessError::Worker(err_str) => {
+                write!(f, "Le processus qui jouait le match a échoué : {}", err_str)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+/// Charge le code des deux équipes d'une configuration
+pub fn load_teams(config: &MatchConfig) -> Result<[PlayerCode; 2], HeadlessError> {
+    let mut teams = Vec::with_capacity(2);
+    for team_entry in &config.teams {
+        let mut code =
+            validate_path(&team_entry.script).map_err(|err| HeadlessError::InvalidTeam {
+                script: team_entry.script.clone(),
+                err,
+            })?;
+        if let Some(profile_path) = &team_entry.robot_profile {
+            code.set_robot_profile(profile_path).map_err(|err| {
+                HeadlessError::InvalidRobotProfile {
+                    path: profile_path.clone(),
+                    err,
+                }
+            })?;
+        }
+        teams.push(code);
+    }
+    teams.try_into().map_err(|teams: Vec<PlayerCode>| {
+        HeadlessError::Config(MatchConfigError::WrongNumberOfTeams(teams.len()))
+    })
+}
+
+/// Joue un match jusqu'au bout sans interface.
+/// Un match sans durée ni score maximal est limité à `infos::DEFAULT_MATCH_DURATION`.
+/// `events` : fichier JSON Lines où écrire les évènements du match, un par ligne
+pub fn play_match(
+    config: &MatchConfig,
+    record: Option<&Path>,
+    events: Option<&Path>,
+) -> Result<MatchResult, HeadlessError> {
+    let [team1, team2] = load_teams(config)?;
+    let mut settings = config.settings.clone();
+    if settings.rules.duration.is_none() && settings.rules.max_score.is_none() {
+        settings.rules.duration = Some(infos::DEFAULT_MATCH_DURATION);
+    }
+    let seed = settings.seed;
+    let mut simulation = Simulator::from_teams(team1, team2, settings);
+    let event_recv = events.map(|_| simulation.subscribe());
+
+    let mut player_code_errors = 0;
+    let simulation = match record {
+        None => {
+            simulation.start();
+            while !simulation.is_finished() {
+                player_code_errors += simulation.tick().len() as u64;
+            }
+            simulation
+        }
+        Some(record_path) => {
+            // the drawing code of the UI is reused to produce the same recording
+            let mut rec = rerun::RecordingStreamBuilder::new(APP_ID)
+                .save(record_path)
+                .map_err(|err| HeadlessError::Recording(format!("{}", err)))?;
+            let mut running = AppRunning::new(simulation, config.team_colors());
+            running.log_snapshots = true;
+            running.init(&mut rec);
+            while !running.simulation.is_finished() {
+                player_code_errors += running.tick(&mut rec) as u64;
+            }
+            // the file is flushed when rec is dropped
+            running.simulation
+        }
+    };
+
+    if let (Some(events_path), Some(event_recv)) = (events, event_recv) {
+        let mut content = String::new();
+        for timed_event in event_recv.try_iter() {
+            content += &serde_json::to_string(&timed_event)
+                .map_err(|err| HeadlessError::Io(format!("{}", err)))?;
+            content.push('\n');
+        }
+        std::fs::write(events_path, content)
+            .map_err(|err| HeadlessError::Io(format!("{}", err)))?;
+    }
+
+    let [name1, name2] = simulation.team_names();
+    Ok(MatchResult {
+        team_names: [name1.to_owned(), name2.to_owned()],
+        script_team_names: simulation.script_team_names.clone(),
+        scores: [
+            simulation.game_referee.score_team_left,
+            simulation.game_referee.score_team_right,
+        ],
+        duration: simulation.elapsed_time(),
+        seed,
+        player_code_errors,
+        stats: simulation.stats.clone(),
+    })
+}
+
+/// Ajoute un match au registre du classement Elo, sous le `TEAM_NAME` et l'empreinte de chaque script.
+/// Une erreur d'écriture est seulement signalée : elle ne doit pas faire perdre le résultat du match.
+pub fn record_result(ledger: &mut RatingLedger, result: &MatchResult, config: &MatchConfig) {
+    let scripts = [
+        config.teams[0].script.as_str(),
+        config.teams[1].script.as_str(),
+    ];
+    let team_names = [
+        result.script_team_names[0].as_str(),
+        result.script_team_names[1].as_str(),
+    ];
+    if let Err(err) = ledger.record_match(team_names, scripts, result.scores) {
+        eprintln!("{}", err);
+    }
+}
+
+/// Un match à faire jouer par un processus séparé
+#[derive(Debug, Clone)]
+pub struct MatchJob {
+    pub config: MatchConfig,
+    pub config_path: PathBuf,
+    pub result_path: PathBuf,
+    pub record_path: Option<PathBuf>,
+}
+
+/// Joue les matchs dans `nb_workers` processus en parallèle.
+/// Chaque processus a son propre interpréteur python : le GIL empêche de faire tourner
+/// plusieurs codes python en même temps dans un seul processus.
+/// Les résultats sont dans le même ordre que `jobs`.
+pub fn run_in_workers(
+    jobs: &[MatchJob],
+    nb_workers: usize,
+) -> Vec<Result<MatchResult, HeadlessError>> {
+    let (job_sender, job_recv) = crossbeam::channel::unbounded();
+    for (n, job) in jobs.iter().enumerate() {
+        job_sender.send((n, job)).unwrap();
+    }
+    drop(job_sender);
+
+    let (result_sender, result_recv) = crossbeam::channel::unbounded();
+    std::thread::scope(|scope| {
+        for _ in 0..nb_workers.max(1) {
+            let job_recv = job_recv.clone();
+            let result_sender = result_sender.clone();
+            scope.spawn(move || {
+                while let Ok((n, job)) = job_recv.recv() {
+                    result_sender.send((n, run_worker(job))).unwrap();
+                }
+            });
+        }
+    });
+    drop(result_sender);
+
+    let mut results: Vec<Option<Result<MatchResult, HeadlessError>>> =
+        jobs.iter().map(|_| None).collect();
+    for (n, result) in result_recv {
+        results[n] = Some(result);
+    }
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+fn run_worker(job: &MatchJob) -> Result<MatchResult, HeadlessError> {
+    job.config
+        .save(&job.config_path)
+        .map_err(HeadlessError::Config)?;
+    let exe = std::env::current_exe().map_err(|err| HeadlessError::Io(format!("{}", err)))?;
+    let mut command = Command::new(exe);
+    command
+        .arg("match")
+        .arg(&job.config_path)
+        .arg("--result")
+        .arg(&job.result_path);
+    if let Some(record_path) = &job.record_path {
+        command.arg("--record").arg(record_path);
+    }
+    let status = command
+        .status()
+        .map_err(|err| HeadlessError::Worker(format!("{}", err)))?;
+    if !status.success() {
+        return Err(HeadlessError::Worker(format!(
+            "{} ({})",
+            job.config_path.display(),
+            status
+        )));
+    }
+    read_result(&job.result_path)
+}
+
+pub fn write_result(result: &MatchResult, path: &Path) -> Result<(), HeadlessError> {
+    let content = serde_json::to_string_pretty(result)
+        .map_err(|err| HeadlessError::Io(format!("{}", err)))?;
+    std::fs::write(path, content).map_err(|err| HeadlessError::Io(format!("{}", err)))
+}
+
+pub fn read_result(path: &Path) -> Result<MatchResult, HeadlessError> {
+    let content =
+        std::fs::read_to_string(path).map_err(|err| HeadlessError::Io(format!("{}", err)))?;
+    serde_json::from_str(&content).map_err(|err| HeadlessError::Io(format!("{}", err)))
+}
@@ -19,6 +19,15 @@ pub const ROBOT_LINEAR_DAMPING: f32 = 0.5; // Friction qui s'applique tout le te
 pub const ROBOT_ANGULAR_DAMPING: f32 = 0.5; // De même mais pour la rotation
 pub const ROBOT_RESTITUTION: f32 = 0.1; // Elasticité, restitution de la force de collision
 pub const ROBOT_MASS: f32 = 10.0;
+pub const ROBOT_MASS_KG: f32 = 2.2; // masse réelle correspondant à ROBOT_MASS
+
+/// Limites des règles RCJ Soccer Open et du simulateur pour les profils de robot
+pub const RCJ_MAX_ROBOT_DIAMETER: f32 = 22.0; // cm
+pub const RCJ_MAX_ROBOT_MASS_KG: f32 = 2.4;
+pub const MAX_KICK_POWER: f32 = 1000.0;
+pub const MAX_KICK_ANGLE: f32 = 45.0; // degrés
+pub const MAX_ROBOT_SPEED: f32 = 400.0; // cm/s
+pub const MIN_TICK_BETWEEN_KICKS: u64 = 60;
 
 /// Constantes pour la balle
 pub const BALL_RADIUS: f32 = 2.0;
@@ -27,13 +36,32 @@ pub const BALL_LINEAR_DAMPING: f32 = 0.5; // Friction qui s'applique tout le tem
 pub const BALL_ANGULAR_DAMPING: f32 = 0.5; // De même mais pour la rotation
 pub const BALL_RESTITUTION: f32 = 0.1; // Elasticité, restitution de la force de collision
 pub const BALL_MASS: f32 = 10.0;
+pub const MAX_THROW_SPEED: f32 = 500.0; // cm/s, vitesse maximale de la balle lancée à la souris
 
 /// Constantes de jeu
+pub const DEFAULT_MATCH_DURATION: u64 = 600; // secondes, proposé quand la durée est limitée dans l'interface
+pub const DEFAULT_MAX_SCORE: u32 = 10;
 pub const NB_MIN_TICK_BETWEEN_KICKS: u64 = 500;
 pub const POWER_SPEED: f32 = 20.0;
 pub const DISTANCE_MIN_KICKER_BALL: f32 = 5.0; // depuis le bord du robot
 pub const KICK_POWER: f32 = 500.0;
-pub const ROTATION_SPEED: f32 = 5.0_f32.to_radians();
-pub const ROTATION_MAX_SPEED: f32 = 70.0_f32.to_radians();
-pub const ROTATION_DELTA: f32 = 5.0_f32.to_radians();
-pub const ROTATION_AUTO_DECREASE_RATIO: f32 = 0.9;
\ No newline at end of file
+pub const ROTATION_MAX_SPEED: f32 = 70.0_f32.to_radians(); // par seconde
+pub const ROTATION_MAX_ACCELERATION: f32 = 300.0_f32.to_radians(); // par seconde au carré
+// Correcteur PID de l'orientation, sa sortie est une vitesse angulaire
+pub const ROTATION_KP: f32 = 4.0;
+pub const ROTATION_KI: f32 = 0.5;
+pub const ROTATION_KD: f32 = 0.3;
+pub const ROTATION_TOLERANCE: f32 = 1.0_f32.to_radians(); // en dessous, le robot est considéré orienté
+pub const ROTATION_STOP_SPEED: f32 = 5.0_f32.to_radians(); // par seconde
+
+/// Constantes pour les modes de contrôle bas niveau
+pub const ROBOT_MAX_SPEED: f32 = 150.0; // cm/s, vitesse maximale demandable en mode vitesse
+pub const NB_MOTORS: usize = 4;
+// Angle de chaque roue omnidirectionnelle par rapport à l'avant du robot, sens trigonométrique
+pub const MOTOR_ANGLES: [f32; NB_MOTORS] = [
+    45.0_f32.to_radians(),
+    135.0_f32.to_radians(),
+    225.0_f32.to_radians(),
+    315.0_f32.to_radians(),
+];
+pub const MOTOR_ROTATION_SPEED: f32 = 2.0_f32.to_radians(); // par moteur à pleine puissance et par tick
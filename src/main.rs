@@ -1,9 +1,32 @@
 mod app_ui;
+mod batch;
+mod bots;
+mod cli;
+mod field_interaction;
+mod field_view;
+mod game_event;
 mod game_referee;
+mod headless;
 mod infos;
+mod manual_control;
+mod match_config;
+mod match_stats;
+mod orientation_controller;
+mod overlays;
 mod player_action;
+mod python_api;
+mod ratings;
+mod recording;
+mod replay;
+mod rng;
 mod robot;
+mod robot_profile;
+mod scenario;
+mod scenario_editor;
+mod scenario_test;
 mod simulator;
+mod snapshot;
+mod tournament;
 mod vector_converter;
 
 use rerun::external::{re_memory, tokio};
@@ -19,5 +42,58 @@ static GLOBAL: re_memory::AccountingAllocator<mimalloc::MiMalloc> =
 
 #[tokio::main]
 async fn main() {
-    app_ui::SimulatorApp::start().await.unwrap();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = cli::parse_args(&args).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    });
+
+    let result: Result<(), Box<dyn std::error::Error>> = match command {
+        // optional match configuration file, see match_config.rs
+        cli::Command::Ui { match_config_path } => {
+            app_ui::SimulatorApp::start(match_config_path).await
+        }
+        cli::Command::Match {
+            config_path,
+            result_path,
+            record_path,
+            events_path,
+            scenario_path,
+            ledger_path,
+        } => cli::run_match(
+            &config_path,
+            result_path.as_deref(),
+            record_path.as_deref(),
+            events_path.as_deref(),
+            scenario_path.as_deref(),
+            ledger_path.as_deref(),
+        ),
+        cli::Command::Tournament {
+            options,
+            ledger_path,
+        } => cli::run_tournament(&options, ledger_path.as_deref()),
+        cli::Command::Batch {
+            options,
+            ledger_path,
+        } => cli::run_batch(&options, ledger_path.as_deref()),
+        cli::Command::Ratings {
+            ledger_path,
+            history,
+        } => cli::show_ratings(&ledger_path, history.as_deref()),
+        cli::Command::Test {
+            suite_path,
+            single_case: Some((case, case_report_path)),
+            ..
+        } => cli::run_test_case(&suite_path, case, &case_report_path),
+        cli::Command::Test {
+            suite_path,
+            junit_path,
+            single_case: None,
+        } => cli::run_tests(&suite_path, junit_path.as_deref()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
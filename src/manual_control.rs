@@ -0,0 +1,220 @@
+use std::fmt::Debug;
+
+use nalgebra::Vector2;
+use rerun::external::egui::{self, Key, Modifiers};
+
+use crate::{
+    player_action::{Movement, PlayerAction},
+    simulator::{Simulator, front_angle},
+};
+
+/// Vitesse (cm/s) proposée au début
+const DEFAULT_SPEED: f32 = 150.0;
+/// Vitesse de rotation (degrés/s) à fond
+const ROTATION_SPEED: f32 = 180.0;
+/// Touches de pilotage, pour QWERTY (WASD) comme pour AZERTY (ZQSD)
+const KEYS_RIGHT: [Key; 2] = [Key::ArrowRight, Key::D];
+const KEYS_LEFT: [Key; 3] = [Key::ArrowLeft, Key::A, Key::Q];
+const KEYS_UP: [Key; 3] = [Key::ArrowUp, Key::W, Key::Z];
+const KEYS_DOWN: [Key; 2] = [Key::ArrowDown, Key::S];
+const KEYS_TURN_LEFT: [Key; 1] = [Key::J];
+const KEYS_TURN_RIGHT: [Key; 1] = [Key::L];
+const KEY_KICK: Key = Key::Space;
+/// En dessous, un axe de la manette est considéré au repos
+#[cfg(feature = "gamepad")]
+const GAMEPAD_DEAD_ZONE: f32 = 0.15;
+
+/// Sens des commandes de déplacement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlFrame {
+    /// Haut = vers le haut de l'écran
+    #[default]
+    Screen,
+    /// Haut = vers l'avant du robot
+    Robot,
+}
+
+/// Ce que demande le joueur, entre -1 et 1 sur chaque axe.
+/// `translation` : x vers la droite, y vers le haut (de l'écran ou du robot selon `ControlFrame`).
+/// `rotation` : positif pour tourner vers la gauche à l'écran
+#[derive(Debug, Clone, Copy, Default)]
+struct Stick {
+    translation: Vector2<f32>,
+    rotation: f32,
+    kick: bool,
+}
+
+/// Pilotage d'un robot au clavier, à la place de son code.
+/// La manette n'est prise en charge qu'en compilant avec la feature cargo `gamepad` (`--features gamepad`).
+/// L'action passe par le même chemin que celle des scripts, voir `Simulator::manual_actions`.
+pub struct ManualControl {
+    /// Index du robot piloté dans `Simulator::robots`
+    pub robot: Option<usize>,
+    pub speed: f32,
+    pub frame: ControlFrame,
+    #[cfg(feature = "gamepad")]
+    gamepad: Option<gilrs::Gilrs>,
+}
+
+impl Debug for ManualControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManualControl")
+            .field("robot", &self.robot)
+            .field("speed", &self.speed)
+            .field("frame", &self.frame)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ManualControl {
+    fn default() -> Self {
+        ManualControl {
+            robot: None,
+            speed: DEFAULT_SPEED,
+            frame: ControlFrame::default(),
+            // no gamepad support (no driver...) only disables the gamepad
+            #[cfg(feature = "gamepad")]
+            gamepad: gilrs::Gilrs::new().ok(),
+        }
+    }
+}
+
+impl ManualControl {
+    pub fn ui(&mut self, ui: &mut egui::Ui, simulation: &Simulator) {
+        let robot_name = |robot: Option<usize>| match robot {
+            Some(n) => simulation.robots[n].to_string(),
+            None => "aucun (tous suivent leur code)".to_owned(),
+        };
+        egui::ComboBox::from_label("robot piloté")
+            .selected_text(robot_name(self.robot))
+            .show_ui(ui, |ui| {
+                for robot in [None, Some(0), Some(1), Some(2), Some(3)] {
+                    ui.selectable_value(&mut self.robot, robot, robot_name(robot));
+                }
+            });
+        if self.robot.is_none() {
+            return;
+        }
+        ui.add(
+            egui::Slider::new(&mut self.speed, 20.0..=simulation_max_speed(simulation))
+                .text("vitesse (cm/s)"),
+        );
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut self.frame,
+                ControlFrame::Screen,
+                "par rapport à l'écran",
+            );
+            ui.radio_value(&mut self.frame, ControlFrame::Robot, "par rapport au robot");
+        });
+        ui.label(
+            "flèches, ZQSD ou WASD : se déplacer\nJ / L : tourner à gauche / à droite\nespace : tirer",
+        );
+        ui.label("les touches de pilotage ne commandent plus la lecture du viewer");
+        #[cfg(feature = "gamepad")]
+        if self.gamepad.is_some() {
+            ui.label(
+                "manette : stick gauche pour se déplacer, stick droit pour tourner, A pour tirer",
+            );
+        }
+        #[cfg(not(feature = "gamepad"))]
+        ui.label("manette : compiler le simulateur avec `--features gamepad`");
+    }
+
+    /// À appeler à chaque image, avant le tick : donne au robot piloté l'action demandée,
+    /// et rend leur code aux autres robots
+    pub fn apply(&mut self, ctx: &egui::Context, simulation: &mut Simulator) {
+        simulation.manual_actions.clear();
+        let Some(robot) = self.robot else {
+            return;
+        };
+        let stick = self.read_input(ctx);
+        let robot_handle = simulation.robots[robot].clone();
+
+        // the viewer shows the field with y downward
+        let velocity = match self.frame {
+            ControlFrame::Screen => Vector2::new(stick.translation.x, -stick.translation.y),
+            ControlFrame::Robot => {
+                let front_angle = front_angle(simulation.rotation_of(&robot_handle).angle());
+                let front = Vector2::new(front_angle.cos(), front_angle.sin());
+                let right_on_screen = Vector2::new(-front.y, front.x);
+                front * stick.translation.y + right_on_screen * stick.translation.x
+            }
+        } * self.speed;
+        simulation.manual_actions.insert(
+            robot_handle,
+            PlayerAction {
+                movement: Movement::Velocity {
+                    velocity: (velocity.x, velocity.y),
+                    // turning left on screen is turning clockwise on the field, y being downward
+                    angular_velocity: -stick.rotation * ROTATION_SPEED.to_radians(),
+                },
+                kick: if stick.kick { 1.0 } else { 0.0 },
+                kick_angle: 0.0,
+            },
+        );
+    }
+
+    fn read_input(&mut self, ctx: &egui::Context) -> Stick {
+        let mut stick = Stick::default();
+        // keys typed in a text field are not commands
+        if !ctx.wants_keyboard_input() {
+            ctx.input_mut(|input| {
+                let axis = |positive: &[Key], negative: &[Key]| {
+                    let pressed = |keys: &[Key]| keys.iter().any(|key| input.key_down(*key));
+                    pressed(positive) as i8 as f32 - pressed(negative) as i8 as f32
+                };
+                stick.translation =
+                    Vector2::new(axis(&KEYS_RIGHT, &KEYS_LEFT), axis(&KEYS_UP, &KEYS_DOWN));
+                stick.rotation = axis(&KEYS_TURN_LEFT, &KEYS_TURN_RIGHT);
+                stick.kick = input.key_down(KEY_KICK);
+
+                // the viewer runs after us : space would also play / pause and the arrows move in time
+                let driving_keys = [KEYS_RIGHT.as_slice(), &KEYS_LEFT, &KEYS_UP, &KEYS_DOWN]
+                    .concat()
+                    .into_iter()
+                    .chain(KEYS_TURN_LEFT)
+                    .chain(KEYS_TURN_RIGHT)
+                    .chain([KEY_KICK]);
+                for key in driving_keys {
+                    input.consume_key(Modifiers::NONE, key);
+                }
+            });
+        }
+
+        #[cfg(feature = "gamepad")]
+        if let Some(gilrs) = &mut self.gamepad {
+            // events must be consumed for the state of the gamepads to be updated
+            while gilrs.next_event().is_some() {}
+            let dead_zone = |value: f32| {
+                if value.abs() < GAMEPAD_DEAD_ZONE {
+                    0.0
+                } else {
+                    value
+                }
+            };
+            for (_, gamepad) in gilrs.gamepads() {
+                stick.translation += Vector2::new(
+                    dead_zone(gamepad.value(gilrs::Axis::LeftStickX)),
+                    dead_zone(gamepad.value(gilrs::Axis::LeftStickY)),
+                );
+                stick.rotation -= dead_zone(gamepad.value(gilrs::Axis::RightStickX));
+                stick.kick |= gamepad.is_pressed(gilrs::Button::South);
+            }
+        }
+
+        // diagonals are not faster than straight lines
+        stick.translation = stick.translation.cap_magnitude(1.0);
+        stick.rotation = stick.rotation.clamp(-1.0, 1.0);
+        stick
+    }
+}
+
+/// Vitesse maximale du plus rapide des robots
+fn simulation_max_speed(simulation: &Simulator) -> f32 {
+    simulation
+        .robot_builders
+        .values()
+        .map(|builder| builder.max_speed)
+        .fold(DEFAULT_SPEED, f32::max)
+}
@@ -0,0 +1,281 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{bots::BUILTIN_PREFIX, infos, scenario::Scenario};
+
+/// Couleurs des équipes dans le viewer quand la configuration n'en donne pas
+pub const DEFAULT_TEAM_COLORS: [[u8; 3]; 2] = [[30, 90, 255], [230, 40, 40]];
+
+/// Clés possibles au premier niveau du fichier : `teams` et les champs de `MatchSettings`
+const TOP_LEVEL_KEYS: [&str; 5] = ["teams", "seed", "rules", "kickoff", "scenario"];
+
+/// Fichier TOML décrivant un match complet, pour le lancer sans passer par l'écran de configuration.
+/// Les chemins des scripts et des profils sont relatifs au dossier du fichier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchConfig {
+    pub teams: Vec<TeamEntry>,
+    #[serde(flatten)]
+    pub settings: MatchSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TeamEntry {
+    pub script: String,
+    /// Remplace le `ROBOT_PROFILE` du script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub robot_profile: Option<String>,
+    /// Couleur des robots dans le viewer, en RGB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<[u8; 3]>,
+}
+
+/// Tout ce qui définit un match en dehors du code des équipes
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchSettings {
+    pub seed: u64,
+    pub rules: MatchRules,
+    pub kickoff: Kickoff,
+    /// Situation de départ à la place du coup d'envoi, voir `scenario.rs`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scenario: Option<Scenario>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchRules {
+    /// Durée du match en secondes, sans limite si absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    /// Le match s'arrête dès qu'une équipe atteint ce score
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<u32>,
+    /// Décalage aléatoire maximal (cm) des robots à chaque engagement, tiré avec la graine du match
+    pub kickoff_jitter: f32,
+}
+
+/// Position de départ d'un robot, dans le repère du terrain vu par la première équipe
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StartPosition {
+    pub x: f32,
+    pub y: f32,
+    pub orientation: f32, // degrés, même convention que `my_orientation` dans le code des équipes
+}
+
+/// Placement des robots et de la balle à chaque engagement.
+/// Les robots sont dans le même ordre que `Simulator::robots`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Kickoff {
+    pub robots: [StartPosition; 4],
+    pub ball: (f32, f32),
+}
+
+impl Default for Kickoff {
+    fn default() -> Self {
+        Kickoff {
+            robots: [
+                StartPosition {
+                    x: infos::START_POS_ALIGNED_X,
+                    y: -infos::START_POS_ALIGNED_Y,
+                    orientation: 180.0,
+                },
+                StartPosition {
+                    x: infos::START_POS_ALIGNED_X,
+                    y: infos::START_POS_ALIGNED_Y,
+                    orientation: 180.0,
+                },
+                StartPosition {
+                    x: -infos::START_POS_ALIGNED_X,
+                    y: -infos::START_POS_ALIGNED_Y,
+                    orientation: 0.0,
+                },
+                StartPosition {
+                    x: -infos::START_POS_ALIGNED_X,
+                    y: infos::START_POS_ALIGNED_Y,
+                    orientation: 0.0,
+                },
+            ],
+            ball: (0.0, 0.0),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MatchConfigError {
+    CannotReadFile(String),
+    CannotWriteFile(String),
+    InvalidFormat(String),
+    UnknownKey(String),
+    NegativeKickoffJitter(f32),
+    WrongNumberOfTeams(usize),
+}
+
+impl Display for MatchConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchConfigError::CannotReadFile(err_str) => {
+                write!(f, "Je n'arrive pas à lire la configuration : {}", err_str)
+            }
+            MatchConfigError::CannotWriteFile(err_str) => {
+                write!(
+                    f,
+                    "Je n'arrive pas à enregistrer la configuration : {}",
+                    err_str
+                )
+            }
+            MatchConfigError::InvalidFormat(err_str) => {
+                write!(f, "La configuration n'est pas un TOML valide : {}", err_str)
+            }
+            MatchConfigError::UnknownKey(key) => write!(
+                f,
+                "La clé `{}` de la configuration est inconnue. Clés possibles : {}",
+                key,
+                TOP_LEVEL_KEYS.join(", ")
+            ),
+            MatchConfigError::NegativeKickoffJitter(jitter) => write!(
+                f,
+                "`rules.kickoff_jitter` doit être positif ou nul, pas {}",
+                jitter
+            ),
+            MatchConfigError::WrongNumberOfTeams(nb) => write!(
+                f,
+                "La configuration doit contenir exactement 2 équipes (`[[teams]]`), pas {}",
+                nb
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MatchConfigError {}
+
+/// Lit le fichier et vérifie ses clés de premier niveau
+fn read_table(path: &Path) -> Result<toml::Table, MatchConfigError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| MatchConfigError::CannotReadFile(format!("{}", err)))?;
+    let table: toml::Table = toml::from_str(&content)
+        .map_err(|err| MatchConfigError::InvalidFormat(format!("{}", err)))?;
+    // `settings` is flattened, so serde cannot reject unknown keys by itself
+    if let Some(key) = table
+        .keys()
+        .find(|key| !TOP_LEVEL_KEYS.contains(&key.as_str()))
+    {
+        return Err(MatchConfigError::UnknownKey(key.clone()));
+    }
+    Ok(table)
+}
+
+impl MatchSettings {
+    /// Lit seulement les réglages d'un fichier de match : ses équipes, même absentes, sont ignorées
+    pub fn load(path: &Path) -> Result<MatchSettings, MatchConfigError> {
+        let mut table = read_table(path)?;
+        table.remove("teams");
+        let settings: MatchSettings = table
+            .try_into()
+            .map_err(|err| MatchConfigError::InvalidFormat(format!("{}", err)))?;
+        settings.check()?;
+        Ok(settings)
+    }
+
+    fn check(&self) -> Result<(), MatchConfigError> {
+        if self.rules.kickoff_jitter < 0.0 {
+            return Err(MatchConfigError::NegativeKickoffJitter(
+                self.rules.kickoff_jitter,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl MatchConfig {
+    pub fn load(path: &Path) -> Result<MatchConfig, MatchConfigError> {
+        let mut config: MatchConfig = read_table(path)?
+            .try_into()
+            .map_err(|err| MatchConfigError::InvalidFormat(format!("{}", err)))?;
+        if config.teams.len() != 2 {
+            return Err(MatchConfigError::WrongNumberOfTeams(config.teams.len()));
+        }
+        config.settings.check()?;
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for team_entry in &mut config.teams {
+            team_entry.map_paths(|path| resolve_path(directory, path));
+        }
+        Ok(config)
+    }
+
+    /// Couleur de chaque équipe, celle par défaut si elle n'est pas donnée
+    pub fn team_colors(&self) -> [[u8; 3]; 2] {
+        let mut colors = DEFAULT_TEAM_COLORS;
+        for (color, team_entry) in colors.iter_mut().zip(&self.teams) {
+            if let Some(team_color) = team_entry.color {
+                *color = team_color;
+            }
+        }
+        colors
+    }
+
+    /// Les chemins sont réécrits pour rester valides depuis le dossier du fichier enregistré
+    pub fn save(&self, path: &Path) -> Result<(), MatchConfigError> {
+        let mut config = self.clone();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for team_entry in &mut config.teams {
+            team_entry.map_paths(|team_path| relative_path(directory, team_path));
+        }
+        let content = toml::to_string_pretty(&config)
+            .map_err(|err| MatchConfigError::CannotWriteFile(format!("{}", err)))?;
+        std::fs::write(path, content)
+            .map_err(|err| MatchConfigError::CannotWriteFile(format!("{}", err)))
+    }
+}
+
+#[cfg(test)]
+impl MatchConfig {
+    /// Match entre deux équipes fournies, avec les réglages par défaut et la graine `seed`
+    pub fn builtin(teams: [crate::bots::BuiltinBot; 2], seed: u64) -> MatchConfig {
+        MatchConfig {
+            teams: teams
+                .map(|team| TeamEntry {
+                    script: team.path(),
+                    ..Default::default()
+                })
+                .to_vec(),
+            settings: MatchSettings {
+                seed,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl TeamEntry {
+    fn map_paths(&mut self, map: impl Fn(&str) -> String) {
+        if !self.script.starts_with(BUILTIN_PREFIX) {
+            self.script = map(&self.script);
+        }
+        if let Some(robot_profile) = &mut self.robot_profile {
+            *robot_profile = map(robot_profile);
+        }
+    }
+}
+
+/// `path` lu dans un fichier du dossier `directory`, inchangé s'il est absolu
+fn resolve_path(directory: &Path, path: &str) -> String {
+    directory.join(path).to_string_lossy().into_owned()
+}
+
+/// `path` (relatif au dossier courant) tel qu'il faut l'écrire dans un fichier du dossier `directory` :
+/// relatif s'il est dans ce dossier, absolu sinon
+fn relative_path(directory: &Path, path: &str) -> String {
+    let absolute = |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+    let path: PathBuf = absolute(Path::new(path));
+    match path.strip_prefix(absolute(directory)) {
+        Ok(relative) => relative.to_string_lossy().into_owned(),
+        Err(_) => path.to_string_lossy().into_owned(),
+    }
+}
@@ -0,0 +1,177 @@
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use crate::infos;
+
+/// Statistiques d'une équipe. L'équipe 0 (robots 0 et 1) attaque le but de gauche
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamStats {
+    /// Temps (s) pendant lequel un robot de l'équipe a été le dernier à toucher la balle
+    pub possession: f32,
+    /// Tirs dont la trajectoire part vers le but adverse
+    pub shots_on_goal: u32,
+    pub kicks_attempted: u32,
+    /// Tirs où la balle était à portée du kicker
+    pub kicks_effective: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RobotStats {
+    pub name: String,
+    pub distance: f32, // cm
+    pub touches: u32,
+    pub kicks_attempted: u32,
+    pub kicks_effective: u32,
+    pub robot_collisions: u32,
+}
+
+/// Statistiques collectées pendant le match, à partir des collisions et de l'état à chaque tick.
+/// Les robots sont dans le même ordre que `Simulator::robots`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchStats {
+    pub teams: [TeamStats; 2],
+    pub robots: [RobotStats; 4],
+    /// Temps (s) passé par la balle dans le tiers gauche, central et droit du terrain
+    pub ball_in_thirds: [f32; 3],
+    pub robot_collisions: u32,
+    #[serde(skip)]
+    tracking: StatsTracking,
+}
+
+/// Ce dont les statistiques se souviennent d'un tick à l'autre.
+/// Absent des résultats, mais gardé dans les instantanés du simulateur.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsTracking {
+    /// Dernier robot à avoir touché la balle
+    last_toucher: Option<usize>,
+    /// Position au tick précédent, `None` juste après un engagement
+    last_positions: [Option<Vector2<f32>>; 4],
+}
+
+#[inline]
+fn team_of(robot: usize) -> usize {
+    robot / 2
+}
+
+impl MatchStats {
+    pub fn new(robot_names: [String; 4]) -> MatchStats {
+        let mut stats = MatchStats::default();
+        for (robot_stats, name) in stats.robots.iter_mut().zip(robot_names) {
+            robot_stats.name = name;
+        }
+        stats
+    }
+
+    /// A appeler après chaque pas de physique
+    pub fn on_tick(
+        &mut self,
+        dt: f32,
+        ball_position: Vector2<f32>,
+        robot_positions: [Vector2<f32>; 4],
+    ) {
+        if let Some(robot) = self.tracking.last_toucher {
+            self.teams[team_of(robot)].possession += dt;
+        }
+
+        let third = ((ball_position.x / infos::FIELD_DEPTH + 0.5) * 3.0).clamp(0.0, 2.0) as usize;
+        self.ball_in_thirds[third] += dt;
+
+        for (n, position) in robot_positions.into_iter().enumerate() {
+            if let Some(last_position) = self.tracking.last_positions[n] {
+                self.robots[n].distance += (position - last_position).norm();
+            }
+            self.tracking.last_positions[n] = Some(position);
+        }
+    }
+
+    /// Les robots sont replacés à l'engagement : ce déplacement ne compte pas dans la distance parcourue
+    pub fn on_new_round(&mut self) {
+        self.tracking.last_positions = [None; 4];
+        self.tracking.last_toucher = None;
+    }
+
+    /// Un robot déplacé à la main : ce déplacement ne compte pas dans la distance parcourue
+    pub fn on_robot_moved(&mut self, robot: usize) {
+        self.tracking.last_positions[robot] = None;
+    }
+
+    /// La balle déplacée à la main n'est plus à celui qui l'a touchée en dernier
+    pub fn on_ball_moved(&mut self) {
+        self.tracking.last_toucher = None;
+    }
+
+    pub fn on_touch(&mut self, robot: usize) {
+        self.robots[robot].touches += 1;
+        self.tracking.last_toucher = Some(robot);
+    }
+
+    pub fn on_robot_collision(&mut self, robot1: usize, robot2: usize) {
+        self.robots[robot1].robot_collisions += 1;
+        self.robots[robot2].robot_collisions += 1;
+        self.robot_collisions += 1;
+    }
+
+    /// `ball_velocity` : vitesse de la balle juste après le tir, si la balle a été touchée
+    pub fn on_kick(
+        &mut self,
+        robot: usize,
+        ball_position: Vector2<f32>,
+        ball_velocity: Option<Vector2<f32>>,
+    ) {
+        let team = team_of(robot);
+        self.robots[robot].kicks_attempted += 1;
+        self.teams[team].kicks_attempted += 1;
+        let Some(ball_velocity) = ball_velocity else {
+            return;
+        };
+        self.robots[robot].kicks_effective += 1;
+        self.teams[team].kicks_effective += 1;
+        self.tracking.last_toucher = Some(robot);
+        if is_heading_to_goal(team, ball_position, ball_velocity) {
+            self.teams[team].shots_on_goal += 1;
+        }
+    }
+
+    pub fn tracking(&self) -> &StatsTracking {
+        &self.tracking
+    }
+
+    pub fn set_tracking(&mut self, tracking: StatsTracking) {
+        self.tracking = tracking;
+    }
+
+    /// Part de possession de chaque équipe, entre 0 et 1
+    pub fn possession_share(&self) -> [f32; 2] {
+        let total = self.teams[0].possession + self.teams[1].possession;
+        if total <= 0.0 {
+            return [0.5, 0.5];
+        }
+        [
+            self.teams[0].possession / total,
+            self.teams[1].possession / total,
+        ]
+    }
+}
+
+/// La balle, en ligne droite, finirait-elle dans le but que `team` attaque ?
+fn is_heading_to_goal(
+    team: usize,
+    ball_position: Vector2<f32>,
+    ball_velocity: Vector2<f32>,
+) -> bool {
+    let goal_x = if team == 0 {
+        -infos::FIELD_DEPTH / 2.0
+    } else {
+        infos::FIELD_DEPTH / 2.0
+    };
+    let towards_goal = (goal_x - ball_position.x) * ball_velocity.x;
+    if towards_goal <= 0.0 {
+        return false;
+    }
+    let time_to_goal_line = (goal_x - ball_position.x) / ball_velocity.x;
+    let y_at_goal_line = ball_position.y + ball_velocity.y * time_to_goal_line;
+    y_at_goal_line.abs() <= infos::GOAL_WIDTH / 2.0
+}
@@ -0,0 +1,214 @@
+use core::f32;
+
+use serde::{Deserialize, Serialize};
+
+use crate::infos;
+
+/// Gains du correcteur PID d'orientation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PidGains {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+}
+
+impl Default for PidGains {
+    fn default() -> Self {
+        PidGains {
+            kp: infos::ROTATION_KP,
+            ki: infos::ROTATION_KI,
+            kd: infos::ROTATION_KD,
+        }
+    }
+}
+
+/// Asservissement de l'orientation d'un robot.
+/// La sortie du PID est une vitesse angulaire, limitée en vitesse et en accélération.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrientationController {
+    pub gains: PidGains,
+    pub max_angular_acceleration: f32, // rad/s²
+    pub max_angular_velocity: f32,     // rad/s
+    integral: f32,
+    last_error: f32,
+}
+
+impl OrientationController {
+    pub fn new(gains: PidGains, max_angular_acceleration: f32, max_angular_velocity: f32) -> Self {
+        OrientationController {
+            gains,
+            max_angular_acceleration,
+            max_angular_velocity,
+            integral: 0.0,
+            last_error: 0.0,
+        }
+    }
+
+    /// Erreur restante (cible - actuelle) lors du dernier appel à `update`, en radians dans ]-pi; pi]
+    #[inline]
+    pub fn last_error(&self) -> f32 {
+        self.last_error
+    }
+
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_error = 0.0;
+    }
+
+    /// Calcule la nouvelle vitesse angulaire à appliquer au robot
+    pub fn update(&mut self, angle: f32, target_angle: f32, angvel: f32, dt: f32) -> f32 {
+        let error = normalize_angle(target_angle - angle);
+        self.last_error = error;
+
+        // Close enough and almost still : stop cleanly instead of oscillating around the target
+        if error.abs() <= infos::ROTATION_TOLERANCE && angvel.abs() <= infos::ROTATION_STOP_SPEED {
+            self.integral = 0.0;
+            return 0.0;
+        }
+
+        // The derivative is taken on the measure (-angvel) to avoid a kick when the target changes
+        let unsaturated = self.gains.kp * error + self.gains.ki * (self.integral + error * dt)
+            - self.gains.kd * angvel;
+        // Anti-windup : only integrate when the output is not saturated
+        if unsaturated.abs() < self.max_angular_velocity {
+            self.integral += error * dt;
+        }
+        let target_angvel = (self.gains.kp * error + self.gains.ki * self.integral
+            - self.gains.kd * angvel)
+            .clamp(-self.max_angular_velocity, self.max_angular_velocity);
+
+        self.limit_angvel(angvel, target_angvel, dt)
+    }
+
+    /// Rapproche la vitesse angulaire de `target_angvel` en respectant les limites du robot
+    pub fn limit_angvel(&self, angvel: f32, target_angvel: f32, dt: f32) -> f32 {
+        let max_change = self.max_angular_acceleration * dt;
+        let target_angvel =
+            target_angvel.clamp(-self.max_angular_velocity, self.max_angular_velocity);
+        angvel + (target_angvel - angvel).clamp(-max_change, max_change)
+    }
+}
+
+/// Ramène un angle dans ]-pi; pi]
+#[inline]
+pub fn normalize_angle(angle: f32) -> f32 {
+    let angle = (angle + f32::consts::PI).rem_euclid(2.0 * f32::consts::PI) - f32::consts::PI;
+    if angle == -f32::consts::PI {
+        f32::consts::PI
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+    // default step of rapier, the one of the simulator
+    const DT: f32 = 1.0 / 60.0;
+
+    fn controller() -> OrientationController {
+        OrientationController::new(
+            PidGains::default(),
+            infos::ROTATION_MAX_ACCELERATION,
+            infos::ROTATION_MAX_SPEED,
+        )
+    }
+
+    #[test]
+    fn update_converges_from_170_degrees_without_overshoot() {
+        for start in [-170.0_f32, 170.0] {
+            let mut controller = controller();
+            let mut angle = start.to_radians();
+            let mut angvel = 0.0;
+            // 10 s, the half turn takes less than 3 s at the maximal speed
+            for _ in 0..600 {
+                angvel = controller.update(angle, 0.0, angvel, DT);
+                angle += angvel * DT;
+                let error = normalize_angle(-angle);
+                assert!(
+                    error * -start.signum() >= -infos::ROTATION_TOLERANCE,
+                    "depuis {}°, l'erreur passe à {}°",
+                    start,
+                    error.to_degrees()
+                );
+            }
+            assert!(normalize_angle(angle).abs() <= infos::ROTATION_TOLERANCE);
+            assert_eq!(angvel, 0.0, "le robot doit être arrêté depuis {}°", start);
+        }
+    }
+
+    #[test]
+    fn update_returns_zero_inside_the_tolerance() {
+        let mut controller = controller();
+        let inside = infos::ROTATION_TOLERANCE / 2.0;
+        assert_eq!(controller.update(inside, 0.0, 0.0, DT), 0.0);
+        assert!((controller.last_error() + inside).abs() < EPSILON);
+        // still turning too fast to stop : the controller brakes
+        let angvel = 2.0 * infos::ROTATION_STOP_SPEED;
+        assert!(controller.update(inside, 0.0, angvel, DT) < angvel);
+    }
+
+    #[test]
+    fn integral_does_not_wind_up_while_saturated() {
+        let mut controller = controller();
+        for _ in 0..120 {
+            let angvel =
+                controller.update(0.0, 170.0_f32.to_radians(), infos::ROTATION_MAX_SPEED, DT);
+            assert_eq!(angvel, infos::ROTATION_MAX_SPEED);
+        }
+        assert_eq!(controller.integral, 0.0);
+
+        // not saturated : the error is integrated
+        controller.update(0.0, 5.0_f32.to_radians(), 0.0, DT);
+        assert!(controller.integral > 0.0);
+    }
+
+    #[test]
+    fn limit_angvel_caps_the_acceleration_and_the_velocity() {
+        let controller = controller();
+        let max_change = infos::ROTATION_MAX_ACCELERATION * DT;
+        assert!((controller.limit_angvel(0.0, 100.0, DT) - max_change).abs() < EPSILON);
+        assert!((controller.limit_angvel(0.0, -100.0, DT) + max_change).abs() < EPSILON);
+        assert_eq!(
+            controller.limit_angvel(infos::ROTATION_MAX_SPEED, 100.0, DT),
+            infos::ROTATION_MAX_SPEED
+        );
+        assert!(
+            (controller.limit_angvel(infos::ROTATION_MAX_SPEED, -100.0, DT)
+                - (infos::ROTATION_MAX_SPEED - max_change))
+                .abs()
+                < EPSILON
+        );
+        // a small change is reached at once
+        assert_eq!(
+            controller.limit_angvel(0.0, max_change / 2.0, DT),
+            max_change / 2.0
+        );
+    }
+
+    #[test]
+    fn normalize_angle_stays_in_half_open_range() {
+        use f32::consts::PI;
+        for (angle, expected) in [
+            (0.0, 0.0),
+            (PI, PI),
+            (-PI, PI),
+            (3.0 * PI / 2.0, -PI / 2.0),
+            (-3.0 * PI / 2.0, PI / 2.0),
+            (2.0 * PI + 0.1, 0.1),
+            (-7.0 * PI + 0.2, -PI + 0.2),
+        ] {
+            let normalized = normalize_angle(angle);
+            assert!(
+                (normalized - expected).abs() < EPSILON,
+                "{} -> {}, attendu {}",
+                angle,
+                normalized,
+                expected
+            );
+            assert!(-PI < normalized && normalized <= PI);
+        }
+    }
+}
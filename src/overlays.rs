@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+
+use rerun::{Arrows2D, Clear, Color, Image, LineStrips2D, Radius, RecordingStream, Transform3D};
+
+use crate::{infos, simulator::Simulator};
+
+/// Nombre de positions gardées dans chaque traînée
+const TRAIL_LENGTH: usize = 90;
+/// Au-delà de ce déplacement en un tick, le robot a été replacé : la traînée repart de zéro
+const TRAIL_MAX_JUMP: f32 = 20.0;
+/// Côté d'une case de la carte de chaleur, en cm
+const HEATMAP_CELL: f32 = 5.0;
+/// La carte de chaleur n'est envoyée au viewer que tous les `HEATMAP_LOG_PERIOD` ticks
+const HEATMAP_LOG_PERIOD: u64 = 30;
+/// Longueur de la flèche de vitesse de la balle : la distance parcourue en ce temps (s)
+const BALL_VELOCITY_ARROW_TIME: f32 = 0.25;
+
+const BALL_COLOR: [u8; 3] = [255, 128, 0];
+
+/// Couches d'aide à l'analyse, dessinées par-dessus le terrain
+pub struct Overlays {
+    pub show_trails: bool,
+    pub show_heatmap: bool,
+    pub show_ball_velocity: bool,
+    /// Couleurs des robots, dans l'ordre de `Simulator::robots`
+    robot_colors: [[u8; 3]; 4],
+    /// Une traînée par robot puis une pour la balle
+    trails: [VecDeque<[f32; 2]>; 5],
+    /// Nombre de ticks passés par les robots de chaque équipe dans chaque case
+    heatmaps: [Vec<u32>; 2],
+    heatmap_size: [usize; 2],
+    /// Ce qui était affiché au tick précédent, pour effacer ce qui vient d'être désactivé
+    shown: [bool; 3],
+}
+
+impl Overlays {
+    pub fn new(robot_colors: [[u8; 3]; 4]) -> Self {
+        let heatmap_size = [
+            (infos::FIELD_DEPTH / HEATMAP_CELL).ceil() as usize,
+            (infos::FIELD_WIDTH / HEATMAP_CELL).ceil() as usize,
+        ];
+        Overlays {
+            show_trails: false,
+            show_heatmap: false,
+            show_ball_velocity: true,
+            robot_colors,
+            trails: Default::default(),
+            heatmaps: [
+                vec![0; heatmap_size[0] * heatmap_size[1]],
+                vec![0; heatmap_size[0] * heatmap_size[1]],
+            ],
+            heatmap_size,
+            shown: [false; 3],
+        }
+    }
+
+    /// Accumule les positions du tick, même si les couches sont masquées
+    pub fn update(&mut self, simulation: &Simulator) {
+        let ball_position = simulation.position_of_ball();
+        for (n, robot_handle) in simulation.robots.iter().enumerate() {
+            let position = simulation.position_of(robot_handle);
+            push_trail_point(&mut self.trails[n], [position.x, position.y]);
+
+            let cell_x = ((position.x + infos::FIELD_DEPTH / 2.0) / HEATMAP_CELL) as usize;
+            let cell_y = ((position.y + infos::FIELD_WIDTH / 2.0) / HEATMAP_CELL) as usize;
+            if cell_x < self.heatmap_size[0] && cell_y < self.heatmap_size[1] {
+                self.heatmaps[n / 2][cell_y * self.heatmap_size[0] + cell_x] += 1;
+            }
+        }
+        push_trail_point(&mut self.trails[4], [ball_position.x, ball_position.y]);
+    }
+
+    pub fn log(&mut self, rec: &mut RecordingStream, simulation: &Simulator) {
+        if self.show_trails {
+            for (n, trail) in self.trails.iter().enumerate() {
+                let color = self.robot_colors.get(n).copied().unwrap_or(BALL_COLOR);
+                log_trail(rec, &format!("overlays/trails/{}", n), trail, color);
+            }
+        } else if self.shown[0] {
+            rec.log("overlays/trails", &Clear::recursive()).unwrap();
+        }
+
+        if self.show_heatmap {
+            if !self.shown[1] || simulation.tick_nb % HEATMAP_LOG_PERIOD == 0 {
+                for team in 0..2 {
+                    self.log_heatmap(rec, team, self.robot_colors[team * 2]);
+                }
+            }
+        } else if self.shown[1] {
+            rec.log("overlays/heatmap", &Clear::recursive()).unwrap();
+        }
+
+        if self.show_ball_velocity {
+            let position = simulation.position_of_ball();
+            let velocity = simulation.rigid_body_set[simulation.ball_rigid_body_handle].linvel()
+                * BALL_VELOCITY_ARROW_TIME;
+            rec.log(
+                "overlays/ball_velocity",
+                &Arrows2D::from_vectors([[velocity.x, velocity.y]])
+                    .with_origins([[position.x, position.y]])
+                    .with_colors([Color::from_rgb(BALL_COLOR[0], BALL_COLOR[1], BALL_COLOR[2])])
+                    .with_radii([Radius::new_scene_units(0.5)]),
+            )
+            .unwrap();
+        } else if self.shown[2] {
+            rec.log("overlays/ball_velocity", &Clear::flat()).unwrap();
+        }
+
+        self.shown = [self.show_trails, self.show_heatmap, self.show_ball_velocity];
+    }
+
+    /// Image semi-transparente de la couleur de l'équipe, plus opaque là où l'équipe passe souvent
+    fn log_heatmap(&self, rec: &mut RecordingStream, team: usize, color: [u8; 3]) {
+        let heatmap = &self.heatmaps[team];
+        let max = heatmap.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let mut pixels = Vec::with_capacity(heatmap.len() * 4);
+        for &count in heatmap {
+            // sqrt so that rarely visited cells are still visible
+            let alpha = ((count as f32 / max).sqrt() * 200.0) as u8;
+            pixels.extend_from_slice(&[color[0], color[1], color[2], alpha]);
+        }
+        let entity_path = format!("overlays/heatmap/team_{}", team + 1);
+        // one pixel per cell, scaled and moved so that the image covers the field
+        rec.log(
+            entity_path.as_str(),
+            &Transform3D::from_translation([
+                -infos::FIELD_DEPTH / 2.0,
+                -infos::FIELD_WIDTH / 2.0,
+                0.0,
+            ])
+            .with_scale(HEATMAP_CELL),
+        )
+        .unwrap();
+        rec.log(
+            entity_path.as_str(),
+            &Image::from_rgba32(
+                pixels,
+                [self.heatmap_size[0] as u32, self.heatmap_size[1] as u32],
+            )
+            .with_draw_order(15.0),
+        )
+        .unwrap();
+    }
+}
+
+fn push_trail_point(trail: &mut VecDeque<[f32; 2]>, point: [f32; 2]) {
+    if let Some(last) = trail.back()
+        && ((point[0] - last[0]).powi(2) + (point[1] - last[1]).powi(2)).sqrt() > TRAIL_MAX_JUMP
+    {
+        trail.clear();
+    }
+    trail.push_back(point);
+    if trail.len() > TRAIL_LENGTH {
+        trail.pop_front();
+    }
+}
+
+/// Chaque segment est une ligne séparée pour que la traînée s'estompe vers son extrémité la plus ancienne
+fn log_trail(
+    rec: &mut RecordingStream,
+    entity_path: &str,
+    trail: &VecDeque<[f32; 2]>,
+    color: [u8; 3],
+) {
+    let nb_segments = trail.len().saturating_sub(1);
+    let segments = trail
+        .iter()
+        .zip(trail.iter().skip(1))
+        .map(|(p1, p2)| [*p1, *p2]);
+    let colors = (0..nb_segments).map(|n| {
+        let alpha = (255 * (n + 1) / nb_segments.max(1)) as u8;
+        Color::from_unmultiplied_rgba(color[0], color[1], color[2], alpha)
+    });
+    rec.log(
+        entity_path,
+        &LineStrips2D::new(segments)
+            .with_colors(colors)
+            .with_radii([Radius::new_scene_units(0.6)])
+            .with_draw_order(25.0),
+    )
+    .unwrap();
+}
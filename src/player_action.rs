@@ -12,21 +12,42 @@ use pyo3::{
 };
 use rerun::external::re_error::format;
 
+use crate::bots::{BUILTIN_PREFIX, BuiltinBot, PlayerCodeBuiltin};
+use crate::infos;
+use crate::python_api;
+use crate::robot_profile::{RobotProfile, RobotProfileError};
+
 #[derive(Debug)]
 pub enum PlayerCode {
     Python(PlayerCodePython),
+    Builtin(PlayerCodeBuiltin),
 }
 
 pub struct PlayerCodePython {
     pub name: String,
     pub path: String,
+    pub mode: ActionMode,
+    pub robot_profile: RobotProfile,
+    pub robot_profile_path: Option<String>,
+    api: ScriptApi,
     activator: Py<PyModule>,
 }
 
+/// Façon dont le script donne ses actions
+enum ScriptApi {
+    /// Une fonction `update(data)` qui reçoit et renvoie des dictionnaires
+    Function,
+    /// Une classe `Robot` avec `setup()` et `update(state) -> Action`, instanciée pour chacun des deux robots
+    Class([Py<pyo3::PyAny>; 2]),
+}
+
 impl Debug for PlayerCodePython {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PlayerActionPython")
             .field("name", &self.name)
+            .field("mode", &self.mode)
+            .field("robot_profile", &self.robot_profile.name)
+            .field("class_api", &matches!(self.api, ScriptApi::Class(_)))
             .finish()
     }
 }
@@ -36,14 +57,60 @@ impl PlayerCode {
     pub fn name(&self) -> &str {
         match self {
             PlayerCode::Python(PlayerCodePython { name, .. }) => name,
+            PlayerCode::Builtin(PlayerCodeBuiltin { name, .. }) => name,
+        }
+    }
+
+    #[inline]
+    pub fn robot_profile(&self) -> &RobotProfile {
+        match self {
+            PlayerCode::Python(PlayerCodePython { robot_profile, .. }) => robot_profile,
+            PlayerCode::Builtin(PlayerCodeBuiltin { robot_profile, .. }) => robot_profile,
+        }
+    }
+
+    #[inline]
+    pub fn robot_profile_path(&self) -> Option<&str> {
+        match self {
+            PlayerCode::Python(PlayerCodePython {
+                robot_profile_path, ..
+            })
+            | PlayerCode::Builtin(PlayerCodeBuiltin {
+                robot_profile_path, ..
+            }) => robot_profile_path.as_deref(),
         }
     }
 
+    #[inline]
+    pub fn path(&self) -> &str {
+        match self {
+            PlayerCode::Python(PlayerCodePython { path, .. }) => path,
+            PlayerCode::Builtin(PlayerCodeBuiltin { path, .. }) => path,
+        }
+    }
+
+    /// Remplace le profil indiqué par `ROBOT_PROFILE` dans le code
+    pub fn set_robot_profile(&mut self, path: &str) -> Result<(), RobotProfileError> {
+        let profile = RobotProfile::load(Path::new(path))?;
+        match self {
+            PlayerCode::Python(python_code) => {
+                python_code.robot_profile = profile;
+                python_code.robot_profile_path = Some(path.to_owned());
+            }
+            PlayerCode::Builtin(builtin_code) => {
+                builtin_code.robot_profile = profile;
+                builtin_code.robot_profile_path = Some(path.to_owned());
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     /// Please do this only from ui::run function !
     pub fn _set_name(&mut self, new_name: &str) {
         match self {
             PlayerCode::Python(python_code) => python_code.name = new_name.to_owned(),
+            PlayerCode::Builtin(builtin_code) => builtin_code.name = new_name.to_owned(),
         }
     }
 
@@ -54,6 +121,7 @@ impl PlayerCode {
     ) -> Result<PlayerAction, CodeReturnValueError> {
         match self {
             PlayerCode::Python(python_code) => python_code.tick(player_info),
+            PlayerCode::Builtin(builtin_code) => Ok(builtin_code.tick(player_info)),
         }
     }
 }
@@ -63,12 +131,18 @@ pub enum CodeValidationError {
     Empty,
     DoesNotExists,
     IsNotAFile,
+    NotAPythonFile,
     CannotReadFile(String),
     ErrorOnLoadingCode(String),
     TeamNameIsMissing,
     TeamNameIncorrect(String),
     UpdateFunctionIsMissing,
     UpdateFunctionIncorrect(String),
+    RobotClassIncorrect(String),
+    ModeIncorrect(String),
+    RobotProfilePathIncorrect(String),
+    RobotProfileIncorrect(RobotProfileError),
+    UnknownBuiltinBot(String),
 }
 
 impl Display for CodeValidationError {
@@ -77,6 +151,12 @@ impl Display for CodeValidationError {
             CodeValidationError::Empty => write!(f, "Ce chemin est vide..."),
             CodeValidationError::DoesNotExists => write!(f, "Ce chemin ne mène nulle part :-("),
             CodeValidationError::IsNotAFile => write!(f, "Cela ne ressemble pas à un fichier..."),
+            CodeValidationError::NotAPythonFile => {
+                write!(
+                    f,
+                    "Seuls les fichiers python (.py) peuvent être déposés ici"
+                )
+            }
             CodeValidationError::CannotReadFile(err_str) => {
                 write!(f, "Je n'arrive pas à lire le fichier : {}", err_str)
             }
@@ -96,11 +176,40 @@ impl Display for CodeValidationError {
             ),
             CodeValidationError::UpdateFunctionIsMissing => write!(
                 f,
-                "Le code doit contenir une fonction `update(data)` sinon je ne peux pas l'appeler."
+                "Le code doit contenir une fonction `update(data)` ou une classe `Robot` sinon je ne peux pas l'appeler."
             ),
             CodeValidationError::UpdateFunctionIncorrect(err_str) => {
                 write!(f, "La fonction update est illisible : {}", err_str)
             }
+            CodeValidationError::RobotClassIncorrect(err_str) => {
+                write!(f, "La classe `Robot` est inutilisable : {}", err_str)
+            }
+            CodeValidationError::ModeIncorrect(err_str) => write!(
+                f,
+                "La variable globale `MODE` est invalide ({}). Valeurs possibles : {}",
+                err_str,
+                ActionMode::ALL
+                    .iter()
+                    .map(|mode| format!("\"{}\"", mode.script_name()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            CodeValidationError::RobotProfilePathIncorrect(err_str) => write!(
+                f,
+                "La variable globale `ROBOT_PROFILE` doit être le chemin (string) d'un fichier TOML : {}",
+                err_str
+            ),
+            CodeValidationError::RobotProfileIncorrect(err) => write!(f, "{}", err),
+            CodeValidationError::UnknownBuiltinBot(name) => write!(
+                f,
+                "Il n'y a pas d'équipe fournie nommée \"{}\". Équipes disponibles : {}",
+                name,
+                BuiltinBot::ALL
+                    .iter()
+                    .map(|bot| bot.path())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
 }
@@ -111,6 +220,11 @@ pub fn validate_path(path: &str) -> Result<PlayerCode, CodeValidationError> {
     if path == "" {
         return Err(CodeValidationError::Empty);
     }
+    if let Some(name) = path.strip_prefix(BUILTIN_PREFIX) {
+        return BuiltinBot::from_path(path)
+            .map(|bot| PlayerCode::Builtin(PlayerCodeBuiltin::new(bot)))
+            .ok_or_else(|| CodeValidationError::UnknownBuiltinBot(name.to_owned()));
+    }
     let path_obj = Path::new(path);
     if !path_obj.exists() {
         return Err(CodeValidationError::DoesNotExists);
@@ -167,6 +281,10 @@ pub fn validate_path(path: &str) -> Result<PlayerCode, CodeValidationError> {
             pypath.call_method1("append", (parent.to_str(),)).unwrap();
         }
 
+        // the helper module must be importable by the team code
+        python_api::install_simulator_module(py)
+            .map_err(|err| CodeValidationError::ErrorOnLoadingCode(format!("{}", err)))?;
+
         let activators = PyModule::from_code(py, file_content, file_name, module_name)
             .map_err(|err| CodeValidationError::ErrorOnLoadingCode(format!("{}", err)))?;
 
@@ -185,18 +303,78 @@ pub fn validate_path(path: &str) -> Result<PlayerCode, CodeValidationError> {
 
         let name = name.replace(" ", "_");
 
-        // check if update method is here
-        activators.getattr("update").map_err(|err| {
-            if err.is_instance_of::<exceptions::PyAttributeError>(py) {
-                CodeValidationError::UpdateFunctionIsMissing
-            } else {
-                CodeValidationError::UpdateFunctionIncorrect(format!("{}", err))
+        // Retrieve the action mode, `MODE` is optional
+        let mode = match activators.getattr("MODE") {
+            Err(err) if err.is_instance_of::<exceptions::PyAttributeError>(py) => {
+                ActionMode::default()
             }
-        })?;
+            Err(err) => return Err(CodeValidationError::ModeIncorrect(format!("{}", err))),
+            Ok(pymode) => {
+                let mode_name = pymode.extract::<String>().map_err(|err| {
+                    CodeValidationError::ModeIncorrect(format!("ce n'est pas une string : {}", err))
+                })?;
+                ActionMode::from_script_name(&mode_name).ok_or_else(|| {
+                    CodeValidationError::ModeIncorrect(format!("\"{}\" est inconnu", mode_name))
+                })?
+            }
+        };
+
+        // Retrieve the robot profile, `ROBOT_PROFILE` is optional.
+        // The path is relative to the directory of the python main file
+        let (robot_profile, robot_profile_path) = match activators.getattr("ROBOT_PROFILE") {
+            Err(err) if err.is_instance_of::<exceptions::PyAttributeError>(py) => {
+                (RobotProfile::default(), None)
+            }
+            Err(err) => {
+                return Err(CodeValidationError::RobotProfilePathIncorrect(format!(
+                    "{}",
+                    err
+                )));
+            }
+            Ok(pyprofile) => {
+                let profile_path = pyprofile.extract::<String>().map_err(|err| {
+                    CodeValidationError::RobotProfilePathIncorrect(format!("{}", err))
+                })?;
+                let profile_path = path_obj
+                    .parent()
+                    .map(|parent| parent.join(&profile_path))
+                    .unwrap_or_else(|| profile_path.into());
+                let robot_profile = RobotProfile::load(&profile_path)
+                    .map_err(CodeValidationError::RobotProfileIncorrect)?;
+                (
+                    robot_profile,
+                    Some(profile_path.to_string_lossy().into_owned()),
+                )
+            }
+        };
+
+        // a `Robot` class replaces the update function
+        let api = match activators.getattr("Robot") {
+            Ok(robot_class) => ScriptApi::Class([
+                instantiate_robot(&robot_class)?,
+                instantiate_robot(&robot_class)?,
+            ]),
+            Err(err) if err.is_instance_of::<exceptions::PyAttributeError>(py) => {
+                // check if update method is here
+                activators.getattr("update").map_err(|err| {
+                    if err.is_instance_of::<exceptions::PyAttributeError>(py) {
+                        CodeValidationError::UpdateFunctionIsMissing
+                    } else {
+                        CodeValidationError::UpdateFunctionIncorrect(format!("{}", err))
+                    }
+                })?;
+                ScriptApi::Function
+            }
+            Err(err) => return Err(CodeValidationError::RobotClassIncorrect(format!("{}", err))),
+        };
 
         Ok(PlayerCode::Python(PlayerCodePython {
             name,
             path: path.to_owned(),
+            mode,
+            robot_profile,
+            robot_profile_path,
+            api,
             activator: activators.into(),
         }))
     });
@@ -204,6 +382,37 @@ pub fn validate_path(path: &str) -> Result<PlayerCode, CodeValidationError> {
     return player_action_python;
 }
 
+/// Une instance de la classe `Robot` du script, après son `setup()` s'il y en a un
+fn instantiate_robot(
+    robot_class: &pyo3::Bound<'_, pyo3::PyAny>,
+) -> Result<Py<pyo3::PyAny>, CodeValidationError> {
+    let robot = robot_class
+        .call0()
+        .map_err(|err| CodeValidationError::RobotClassIncorrect(format!("{}", err)))?;
+    if !robot.hasattr("update").unwrap_or(false) {
+        return Err(CodeValidationError::RobotClassIncorrect(
+            "il manque la méthode `update(self, state)`".to_owned(),
+        ));
+    }
+    if robot.hasattr("setup").unwrap_or(false) {
+        robot.call_method0("setup").map_err(|err| {
+            CodeValidationError::RobotClassIncorrect(format!(
+                "`setup()` a levé l'exception {}",
+                err
+            ))
+        })?;
+    }
+    Ok(robot.unbind())
+}
+
+/// Initialise le module `random` de python, partagé par le code des deux équipes
+pub fn seed_python_random(seed: u64) {
+    Python::attach(|py| {
+        let random = py.import("random").unwrap();
+        random.call_method1("seed", (seed,)).unwrap();
+    });
+}
+
 // impl PlayerAction {
 //     pub fn tick(
 //         &self,
@@ -270,6 +479,21 @@ pub enum CodeReturnValueError {
         err: String,
         value_returned: String,
     },
+    /// Le dictionnaire renvoyé par `update` contient un champ qui ne sert pas avec le `MODE` du script
+    UnknownField {
+        code_name: String,
+        field_name: String,
+        /// Champ attendu le plus proche, pour une faute de frappe
+        suggestion: Option<String>,
+        mode: ActionMode,
+        value_returned: String,
+    },
+    /// `Robot.update` n'a pas renvoyé une `Action` utilisable avec le `MODE` du script
+    InvalidAction {
+        code_name: String,
+        err: String,
+        value_returned: String,
+    },
 }
 
 impl Display for CodeReturnValueError {
@@ -318,6 +542,34 @@ impl Display for CodeReturnValueError {
                 "Dans le code python de {}, il est impossible de convertir la valeur renvoyée en un dictionnaire ({}): Valeur renvoyée : {}",
                 code_name, err, value_returned
             ),
+            CodeReturnValueError::UnknownField {
+                code_name,
+                field_name,
+                suggestion,
+                mode,
+                value_returned,
+            } => write!(
+                f,
+                "Dans le code python de {}, le champ `{}` du dictionnaire renvoyé ne sert pas avec MODE = \"{}\".{} Champs attendus : {}, kick, kick_angle. Valeur renvoyée : {}",
+                code_name,
+                field_name,
+                mode.script_name(),
+                suggestion
+                    .as_ref()
+                    .map(|field| format!(" Vouliez-vous dire `{}` ?", field))
+                    .unwrap_or_default(),
+                mode.fields().join(", "),
+                value_returned
+            ),
+            CodeReturnValueError::InvalidAction {
+                code_name,
+                err,
+                value_returned,
+            } => write!(
+                f,
+                "Dans le code python de {}, l'action renvoyée par `Robot.update` est invalide : {}. Valeur renvoyée : {}",
+                code_name, err, value_returned
+            ),
         }
     }
 }
@@ -327,20 +579,86 @@ impl std::error::Error for CodeReturnValueError {}
 #[derive(Debug)]
 pub struct PlayerInformation {
     pub switch_coordinates: bool,
+    pub robot_number: u8, // 1 ou 2
     pub my_position: (f32, f32),
     pub my_orientation: f32, // en radians !, in ]-pi; pi]
     pub friend_position: (f32, f32),
     pub enemy1_position: (f32, f32),
     pub enemy2_position: (f32, f32),
     pub ball_position: (f32, f32),
+    pub orientation_error: f32, // en radians, cible - actuelle, le signe ne dépend pas du changement de repère
 }
 
-#[derive(Debug)]
+/// Schéma de la valeur renvoyée par `update`, choisi par la variable globale `MODE` du script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActionMode {
+    /// `target_position`, `power`, `target_orientation`, `kick`
+    #[default]
+    Position,
+    /// `velocity` (avant, gauche) dans le repère du robot, `angular_velocity`, `kick`
+    VelocityRobotFrame,
+    /// `velocity` dans le repère du terrain, `angular_velocity`, `kick`
+    VelocityFieldFrame,
+    /// `motors` : puissance de chacun des moteurs, `kick`
+    MotorPowers,
+}
+
+impl ActionMode {
+    pub const ALL: [ActionMode; 4] = [
+        ActionMode::Position,
+        ActionMode::VelocityRobotFrame,
+        ActionMode::VelocityFieldFrame,
+        ActionMode::MotorPowers,
+    ];
+
+    /// Nom à donner à `MODE` dans le code python
+    pub fn script_name(&self) -> &'static str {
+        match self {
+            ActionMode::Position => "position",
+            ActionMode::VelocityRobotFrame => "velocity_robot",
+            ActionMode::VelocityFieldFrame => "velocity_field",
+            ActionMode::MotorPowers => "motors",
+        }
+    }
+
+    pub fn from_script_name(name: &str) -> Option<ActionMode> {
+        ActionMode::ALL
+            .into_iter()
+            .find(|mode| mode.script_name() == name)
+    }
+
+    /// Champs de l'action utilisés par le mode, en plus de `kick` et `kick_angle`
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            ActionMode::Position => &["target_position", "power", "target_orientation"],
+            ActionMode::VelocityRobotFrame | ActionMode::VelocityFieldFrame => {
+                &["velocity", "angular_velocity"]
+            }
+            ActionMode::MotorPowers => &["motors"],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Movement {
+    Target {
+        target_position: (f32, f32),
+        power: u8,
+        target_orientation: f32, // en radians !
+    },
+    Velocity {
+        velocity: (f32, f32),  // en coordonnées globales, cm/s
+        angular_velocity: f32, // en radians/s
+    },
+    /// Puissances entre -255 et 255, dans l'ordre de `infos::MOTOR_ANGLES`
+    MotorPowers([i16; infos::NB_MOTORS]),
+}
+
+#[derive(Debug, Clone)]
 pub struct PlayerAction {
-    pub target_position: (f32, f32),
-    pub power: u8,
-    pub target_orientation: f32, // en radians !
-    pub kick: bool,
+    pub movement: Movement,
+    pub kick: f32,       // puissance entre 0 et 1, 0 pour ne pas tirer
+    pub kick_angle: f32, // en radians, par rapport à l'avant du robot
 }
 
 impl PlayerCodePython {
@@ -386,12 +704,14 @@ impl PlayerCodePython {
             let player_info = if player_info.switch_coordinates {
                 PlayerInformation {
                     switch_coordinates: player_info.switch_coordinates,
+                    robot_number: player_info.robot_number,
                     my_position: switch_coordinates(player_info.my_position),
                     my_orientation: switch_rotation(player_info.my_orientation),
                     friend_position: switch_coordinates(player_info.friend_position),
                     enemy1_position: switch_coordinates(player_info.enemy1_position),
                     enemy2_position: switch_coordinates(player_info.enemy2_position),
                     ball_position: switch_coordinates(player_info.ball_position),
+                    orientation_error: player_info.orientation_error,
                 }
             } else {
                 player_info
@@ -410,89 +730,316 @@ impl PlayerCodePython {
                 my_orientation: my_orientation_for_player_code,
                 ..player_info
             };
-            let data = PyDict::new(py);
-            data.set_item("my_position", player_info.my_position)
-                .unwrap();
-            data.set_item("my_orientation", player_info.my_orientation.to_degrees())
-                .unwrap();
-            data.set_item("friend_position", player_info.friend_position)
-                .unwrap();
-            data.set_item("enemy1_position", player_info.enemy1_position)
-                .unwrap();
-            data.set_item("enemy2_position", player_info.enemy2_position)
-                .unwrap();
-            data.set_item("ball_position", player_info.ball_position)
-                .unwrap();
-            let action = self
-                .activator
-                .getattr(py, "update")
-                .unwrap()
-                .call1(py, (data,))
-                .map_err(|err| CodeReturnValueError::PlayerCodeException {
-                    code_name: self.name.clone(),
-                    err: format!("{}", err),
-                })?;
+            let (action, dict) = match &self.api {
+                ScriptApi::Function => self.call_update_function(py, &player_info)?,
+                ScriptApi::Class(robots) => self.call_robot_update(
+                    py,
+                    &robots[player_info.robot_number as usize - 1],
+                    &player_info,
+                )?,
+            };
+            let has_kick_angle = dict.contains("kick_angle").unwrap_or(false);
 
-            let dict =
-                action
-                    .cast_bound::<PyDict>(py)
-                    .map_err(|err| CodeReturnValueError::NoDict {
-                        code_name: self.name.clone(),
-                        err: format!("{}", err),
-                        value_returned: format!("{}", action),
-                    })?;
-            if dict.len() != 4 {
-                println!(
-                    "WARN: Le dictionnaire de retour n'a pas le nombre exact d'arguments requis"
-                );
-            }
+            let movement = match self.mode {
+                ActionMode::Position => self.extract_target(&action, &dict, &player_info)?,
+                ActionMode::VelocityRobotFrame | ActionMode::VelocityFieldFrame => {
+                    self.extract_velocity(&action, &dict, &player_info)?
+                }
+                ActionMode::MotorPowers => self.extract_motors(&action, &dict)?,
+            };
+
+            let (kick, kick_angle) = self.extract_kick(&action, &dict, has_kick_angle)?;
+
+            Ok(PlayerAction {
+                movement,
+                kick,
+                kick_angle,
+            })
+        })
+    }
+
+    /// Appelle `update(data)` et renvoie la valeur renvoyée, qui doit être un dictionnaire
+    fn call_update_function<'py>(
+        &self,
+        py: Python<'py>,
+        player_info: &PlayerInformation,
+    ) -> Result<(Py<pyo3::PyAny>, pyo3::Bound<'py, PyDict>), CodeReturnValueError> {
+        let data = PyDict::new(py);
+        data.set_item("my_position", player_info.my_position)
+            .unwrap();
+        data.set_item("my_orientation", player_info.my_orientation.to_degrees())
+            .unwrap();
+        data.set_item("friend_position", player_info.friend_position)
+            .unwrap();
+        data.set_item("enemy1_position", player_info.enemy1_position)
+            .unwrap();
+        data.set_item("enemy2_position", player_info.enemy2_position)
+            .unwrap();
+        data.set_item("ball_position", player_info.ball_position)
+            .unwrap();
+        data.set_item(
+            "orientation_error",
+            player_info.orientation_error.to_degrees(),
+        )
+        .unwrap();
+        data.set_item("robot_number", player_info.robot_number)
+            .unwrap();
+        data.set_item("profile", python_api::Profile::new(&self.robot_profile))
+            .unwrap();
+        let action = self
+            .activator
+            .getattr(py, "update")
+            .unwrap()
+            .call1(py, (data,))
+            .map_err(|err| CodeReturnValueError::PlayerCodeException {
+                code_name: self.name.clone(),
+                err: format!("{}", err),
+            })?;
 
-            let mut target_position: (f32, f32) = self.dict_extract(
-                &action,
-                dict,
-                "target_position",
-                "un tuple `(float, float)`",
-            )?;
-            let power: u8 =
-                self.dict_extract(&action, dict, "power", "un entier entre 0 et 255")?;
-            let target_orientation: f32 = self.dict_extract(
-                &action,
-                dict,
-                "target_orientation",
-                "un float entre 0 et 360",
-            )?;
-
-            if !(0.0 <= target_orientation && target_orientation <= 360.0) {
-                return Err(CodeReturnValueError::InvalidType {
+        let dict = action
+            .cast_bound::<PyDict>(py)
+            .map_err(|err| CodeReturnValueError::NoDict {
+                code_name: self.name.clone(),
+                err: format!("{}", err),
+                value_returned: format!("{}", action),
+            })?
+            .clone();
+        let expected_fields: Vec<&str> = self
+            .mode
+            .fields()
+            .iter()
+            .copied()
+            .chain(["kick", "kick_angle"])
+            .collect();
+        for key in dict.keys() {
+            let field_name = key.to_string();
+            if !expected_fields.contains(&field_name.as_str()) {
+                return Err(CodeReturnValueError::UnknownField {
                     code_name: self.name.clone(),
-                    field_name: "target_orientation".to_owned(),
-                    invalid_type_hint: "un float compris entre 0 et 360".to_owned(),
-                    err: format!("c'est {}", target_orientation),
+                    suggestion: python_api::closest_field(&field_name, &expected_fields)
+                        .map(str::to_owned),
+                    field_name,
+                    mode: self.mode,
                     value_returned: format!("{}", action),
                 });
             }
-            let mut target_orientation = target_orientation.to_radians();
+        }
+        Ok((action, dict))
+    }
 
-            let kick: bool = self.dict_extract(&action, dict, "kick", "un booléen True/False")?;
+    /// Appelle `robot.update(state)` et renvoie l'`Action` renvoyée, avec son dictionnaire équivalent
+    fn call_robot_update<'py>(
+        &self,
+        py: Python<'py>,
+        robot: &Py<pyo3::PyAny>,
+        player_info: &PlayerInformation,
+    ) -> Result<(Py<pyo3::PyAny>, pyo3::Bound<'py, PyDict>), CodeReturnValueError> {
+        let action = robot
+            .call_method1(
+                py,
+                "update",
+                (python_api::State::new(player_info, &self.robot_profile),),
+            )
+            .map_err(|err| CodeReturnValueError::PlayerCodeException {
+                code_name: self.name.clone(),
+                err: format!("{}", err),
+            })?;
+        let invalid_action = |err: String| CodeReturnValueError::InvalidAction {
+            code_name: self.name.clone(),
+            err,
+            value_returned: format!("{}", action),
+        };
 
-            // transform back player coordinates to global coordinates
-            if player_info.switch_coordinates {
-                target_position = switch_coordinates(target_position);
-                target_orientation = switch_rotation(target_orientation);
-            }
+        let dict = action
+            .extract::<pyo3::PyRef<python_api::Action>>(py)
+            .map_err(|_| invalid_action("ce n'est pas une `simulator.Action`".to_owned()))?
+            .to_dict(py, self.mode)
+            .map_err(invalid_action)?;
+        Ok((action, dict))
+    }
 
-            // the rerun orientation is different from the code orientation
-            target_orientation =
-                (target_orientation + f32::consts::FRAC_PI_2).rem_euclid(2.0 * f32::consts::PI);
+    /// `kick` accepte un booléen (True : pleine puissance) ou un float entre 0 et 1.
+    /// `kick_angle` est optionnel et limité par le profil du robot.
+    fn extract_kick(
+        &self,
+        action: &Py<pyo3::PyAny>,
+        dict: &pyo3::Bound<'_, PyDict>,
+        has_kick_angle: bool,
+    ) -> Result<(f32, f32), CodeReturnValueError> {
+        // python booleans are converted to 0.0 and 1.0
+        let kick: f32 = self.dict_extract(
+            action,
+            dict,
+            "kick",
+            "un booléen True/False ou un float entre 0 et 1",
+        )?;
+        if !(0.0 <= kick && kick <= 1.0) {
+            return Err(CodeReturnValueError::InvalidType {
+                code_name: self.name.clone(),
+                field_name: "kick".to_owned(),
+                invalid_type_hint: "un booléen ou un float compris entre 0 et 1".to_owned(),
+                err: format!("c'est {}", kick),
+                value_returned: format!("{}", action),
+            });
+        }
 
-            Ok(PlayerAction {
-                target_position,
-                power,
-                target_orientation,
-                kick,
-            })
+        if !has_kick_angle {
+            return Ok((kick, 0.0));
+        }
+        let kick_angle: f32 =
+            self.dict_extract(action, dict, "kick_angle", "un float en degrés")?;
+        let range = self.robot_profile.kick_angle_range;
+        if !(-range <= kick_angle && kick_angle <= range) {
+            return Err(CodeReturnValueError::InvalidType {
+                code_name: self.name.clone(),
+                field_name: "kick_angle".to_owned(),
+                invalid_type_hint: format!(
+                    "un float compris entre -{} et {} (`kick_angle_range` du profil du robot)",
+                    range, range
+                ),
+                err: format!("c'est {}", kick_angle),
+                value_returned: format!("{}", action),
+            });
+        }
+        // a relative angle is the same in both coordinate systems
+        Ok((kick, kick_angle.to_radians()))
+    }
+
+    fn extract_target(
+        &self,
+        action: &Py<pyo3::PyAny>,
+        dict: &pyo3::Bound<'_, PyDict>,
+        player_info: &PlayerInformation,
+    ) -> Result<Movement, CodeReturnValueError> {
+        let mut target_position: (f32, f32) =
+            self.dict_extract(action, dict, "target_position", "un tuple `(float, float)`")?;
+        let power: u8 = self.dict_extract(action, dict, "power", "un entier entre 0 et 255")?;
+        let target_orientation: f32 = self.dict_extract(
+            action,
+            dict,
+            "target_orientation",
+            "un float entre 0 et 360",
+        )?;
+
+        if !(0.0 <= target_orientation && target_orientation <= 360.0) {
+            return Err(CodeReturnValueError::InvalidType {
+                code_name: self.name.clone(),
+                field_name: "target_orientation".to_owned(),
+                invalid_type_hint: "un float compris entre 0 et 360".to_owned(),
+                err: format!("c'est {}", target_orientation),
+                value_returned: format!("{}", action),
+            });
+        }
+        let mut target_orientation = target_orientation.to_radians();
+
+        // transform back player coordinates to global coordinates
+        if player_info.switch_coordinates {
+            target_position = switch_coordinates(target_position);
+            target_orientation = switch_rotation(target_orientation);
+        }
+
+        // the rerun orientation is different from the code orientation
+        target_orientation =
+            (target_orientation + f32::consts::FRAC_PI_2).rem_euclid(2.0 * f32::consts::PI);
+
+        Ok(Movement::Target {
+            target_position,
+            power,
+            target_orientation,
+        })
+    }
+
+    fn extract_velocity(
+        &self,
+        action: &Py<pyo3::PyAny>,
+        dict: &pyo3::Bound<'_, PyDict>,
+        player_info: &PlayerInformation,
+    ) -> Result<Movement, CodeReturnValueError> {
+        let mut velocity: (f32, f32) =
+            self.dict_extract(action, dict, "velocity", "un tuple `(float, float)`")?;
+        let angular_velocity: f32 = self.dict_extract(
+            action,
+            dict,
+            "angular_velocity",
+            "un float (en degrés par seconde)",
+        )?;
+
+        if !(velocity.0.is_finite() && velocity.1.is_finite()) {
+            return Err(CodeReturnValueError::InvalidType {
+                code_name: self.name.clone(),
+                field_name: "velocity".to_owned(),
+                invalid_type_hint: "un tuple de deux nombres finis".to_owned(),
+                err: format!("c'est {:?}", velocity),
+                value_returned: format!("{}", action),
+            });
+        }
+        if !angular_velocity.is_finite() {
+            return Err(CodeReturnValueError::InvalidType {
+                code_name: self.name.clone(),
+                field_name: "angular_velocity".to_owned(),
+                invalid_type_hint: "un nombre fini".to_owned(),
+                err: format!("c'est {}", angular_velocity),
+                value_returned: format!("{}", action),
+            });
+        }
+
+        if self.mode == ActionMode::VelocityRobotFrame {
+            // x vers l'avant du robot, y vers sa gauche.
+            // player_info.my_orientation est déjà dans le repère du code
+            let (sin, cos) = player_info.my_orientation.sin_cos();
+            velocity = (
+                velocity.0 * cos - velocity.1 * sin,
+                velocity.0 * sin + velocity.1 * cos,
+            );
+        }
+
+        // transform back player coordinates to global coordinates
+        // (a rotation of pi does not change the direction of the angular velocity)
+        if player_info.switch_coordinates {
+            velocity = switch_coordinates(velocity);
+        }
+
+        Ok(Movement::Velocity {
+            velocity,
+            angular_velocity: angular_velocity.to_radians(),
         })
     }
+
+    fn extract_motors(
+        &self,
+        action: &Py<pyo3::PyAny>,
+        dict: &pyo3::Bound<'_, PyDict>,
+    ) -> Result<Movement, CodeReturnValueError> {
+        let motors: Vec<i16> = self.dict_extract(
+            action,
+            dict,
+            "motors",
+            "une liste d'entiers entre -255 et 255",
+        )?;
+
+        let motors: [i16; infos::NB_MOTORS] =
+            motors
+                .try_into()
+                .map_err(|motors: Vec<i16>| CodeReturnValueError::InvalidType {
+                    code_name: self.name.clone(),
+                    field_name: "motors".to_owned(),
+                    invalid_type_hint: format!("une liste de {} entiers", infos::NB_MOTORS),
+                    err: format!("elle contient {} valeurs", motors.len()),
+                    value_returned: format!("{}", action),
+                })?;
+
+        if let Some(invalid) = motors.iter().find(|power| !(-255..=255).contains(*power)) {
+            return Err(CodeReturnValueError::InvalidType {
+                code_name: self.name.clone(),
+                field_name: "motors".to_owned(),
+                invalid_type_hint: "une liste d'entiers compris entre -255 et 255".to_owned(),
+                err: format!("elle contient {}", invalid),
+                value_returned: format!("{}", action),
+            });
+        }
+
+        Ok(Movement::MotorPowers(motors))
+    }
 }
 
 #[inline]
@@ -0,0 +1,469 @@
+use std::ffi::CString;
+
+use pyo3::{
+    Bound, IntoPyObjectExt, PyErr, PyResult, Python,
+    exceptions::{PyAttributeError, PyTypeError},
+    pyclass, pymethods,
+    types::{PyAny, PyAnyMethods, PyDict, PyDictMethods, PyModule, PyModuleMethods},
+};
+
+use crate::{
+    infos,
+    player_action::{ActionMode, PlayerInformation},
+    robot_profile::RobotProfile,
+};
+
+/// Nom sous lequel les scripts importent le module d'aide
+pub const MODULE_NAME: &str = "simulator";
+/// Code du module d'aide, voir aussi `python/simulator.pyi` pour la complétion dans l'éditeur
+const MODULE_CODE: &str = include_str!("../python/simulator.py");
+
+/// Ajoute le module `simulator` à l'interpréteur, une seule fois, avant de charger le code des équipes.
+/// Les constantes de `infos` sont définies avant d'exécuter le code du module, qui s'en sert.
+pub fn install_simulator_module(py: Python<'_>) -> PyResult<()> {
+    let modules = py
+        .import("sys")?
+        .getattr("modules")?
+        .cast_into::<PyDict>()?;
+    if modules.contains(MODULE_NAME)? {
+        return Ok(());
+    }
+
+    let module = PyModule::new(py, MODULE_NAME)?;
+    for (name, value) in [
+        ("FIELD_WIDTH", infos::FIELD_WIDTH),
+        ("FIELD_DEPTH", infos::FIELD_DEPTH),
+        ("SPACE_BEFORE_LINE_SIDE", infos::SPACE_BEFORE_LINE_SIDE),
+        ("GOAL_WIDTH", infos::GOAL_WIDTH),
+        ("ENBUT_DEPTH", infos::ENBUT_DEPTH),
+        ("ENBUT_WIDTH", infos::ENBUT_WIDTH),
+        ("ROBOT_RADIUS", infos::ROBOT_RADIUS),
+        ("BALL_RADIUS", infos::BALL_RADIUS),
+        ("DISTANCE_MIN_KICKER_BALL", infos::DISTANCE_MIN_KICKER_BALL),
+        ("RULE_MAX_ROBOT_SPEED", infos::MAX_ROBOT_SPEED),
+        ("MAX_KICK_ANGLE", infos::MAX_KICK_ANGLE),
+    ] {
+        module.add(name, value)?;
+    }
+    module.add("NB_MOTORS", infos::NB_MOTORS)?;
+    // the scripts work in degrees
+    module.add(
+        "MOTOR_ANGLES",
+        infos::MOTOR_ANGLES.map(f32::to_degrees).to_vec(),
+    )?;
+
+    module.add_class::<Profile>()?;
+    module.add_class::<State>()?;
+    module.add_class::<Action>()?;
+
+    // registered first, so that the dataclasses of the module find it while they are created
+    modules.set_item(MODULE_NAME, &module)?;
+    let code = CString::new(MODULE_CODE)?;
+    if let Err(err) = py.run(&code, Some(&module.dict()), None) {
+        modules.del_item(MODULE_NAME)?;
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Profil matériel chargé pour les robots de l'équipe, en `State.profile` ou `data["profile"]`
+#[pyclass(name = "Profile", module = "simulator", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct Profile {
+    name: String,
+    /// cm
+    radius: f32,
+    /// kg
+    mass: f32,
+    kick_power: f32,
+    /// en ticks
+    kick_cooldown: u64,
+    /// degrés
+    kick_angle_range: f32,
+    /// cm/s, vitesse maximale demandable en mode vitesse
+    max_speed: f32,
+    /// cm/s, vitesse au-delà de laquelle le robot est freiné, `None` si le profil n'en donne pas
+    speed_limit: Option<f32>,
+}
+
+impl Profile {
+    const FIELDS: [&'static str; 8] = [
+        "name",
+        "radius",
+        "mass",
+        "kick_power",
+        "kick_cooldown",
+        "kick_angle_range",
+        "max_speed",
+        "speed_limit",
+    ];
+
+    pub fn new(profile: &RobotProfile) -> Profile {
+        Profile {
+            name: profile.name.clone(),
+            radius: profile.radius,
+            mass: profile.mass,
+            kick_power: profile.kick_power,
+            kick_cooldown: profile.kick_cooldown,
+            kick_angle_range: profile.kick_angle_range,
+            max_speed: profile.command_max_speed(),
+            speed_limit: profile.max_speed,
+        }
+    }
+}
+
+#[pymethods]
+impl Profile {
+    /// Appelé seulement quand l'attribut n'existe pas
+    fn __getattr__(&self, name: &str) -> PyResult<()> {
+        Err(unknown_field("Profile", name, &Profile::FIELDS))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Ce que reçoit `Robot.update` dans l'API à base de classes : les champs du dictionnaire de `update`,
+/// en attributs en lecture seule
+#[pyclass(name = "State", module = "simulator", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct State {
+    my_position: (f32, f32),
+    my_orientation: f32,
+    friend_position: (f32, f32),
+    enemy1_position: (f32, f32),
+    enemy2_position: (f32, f32),
+    ball_position: (f32, f32),
+    orientation_error: f32,
+    /// 1 ou 2
+    robot_number: u8,
+    profile: Profile,
+}
+
+impl State {
+    const FIELDS: [&'static str; 9] = [
+        "my_position",
+        "my_orientation",
+        "friend_position",
+        "enemy1_position",
+        "enemy2_position",
+        "ball_position",
+        "orientation_error",
+        "robot_number",
+        "profile",
+    ];
+
+    /// `player_info` doit déjà être dans le repère du code de l'équipe
+    pub fn new(player_info: &PlayerInformation, profile: &RobotProfile) -> State {
+        State {
+            my_position: player_info.my_position,
+            my_orientation: player_info.my_orientation.to_degrees(),
+            friend_position: player_info.friend_position,
+            enemy1_position: player_info.enemy1_position,
+            enemy2_position: player_info.enemy2_position,
+            ball_position: player_info.ball_position,
+            orientation_error: player_info.orientation_error.to_degrees(),
+            robot_number: player_info.robot_number,
+            profile: Profile::new(profile),
+        }
+    }
+}
+
+#[pymethods]
+impl State {
+    /// Appelé seulement quand l'attribut n'existe pas
+    fn __getattr__(&self, name: &str) -> PyResult<()> {
+        Err(unknown_field("State", name, &State::FIELDS))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Ce que renvoie `Robot.update` dans l'API à base de classes.
+/// Seuls les champs du mode choisi par `MODE` doivent être donnés ; `kick` vaut 0 (pas de tir) par défaut.
+#[pyclass(name = "Action", module = "simulator", get_all)]
+#[derive(Debug, Clone, Default)]
+pub struct Action {
+    target_position: Option<(f32, f32)>,
+    power: Option<u8>,
+    target_orientation: Option<f32>,
+    velocity: Option<(f32, f32)>,
+    angular_velocity: Option<f32>,
+    motors: Option<Vec<i16>>,
+    kick: f32,
+    kick_angle: Option<f32>,
+}
+
+impl Action {
+    const FIELDS: [&'static str; 8] = [
+        "target_position",
+        "power",
+        "target_orientation",
+        "velocity",
+        "angular_velocity",
+        "motors",
+        "kick",
+        "kick_angle",
+    ];
+
+    fn set_field(&mut self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        fn extract<'py, T>(
+            name: &str,
+            value: &Bound<'py, PyAny>,
+            type_hint: &str,
+        ) -> PyResult<Option<T>>
+        where
+            for<'a> T: pyo3::FromPyObject<'a, 'py>,
+            for<'a> <T as pyo3::FromPyObject<'a, 'py>>::Error: std::fmt::Display,
+        {
+            if value.is_none() {
+                return Ok(None);
+            }
+            value.extract::<T>().map(Some).map_err(|err| {
+                PyTypeError::new_err(format!(
+                    "`Action.{}` doit être {} ({})",
+                    name, type_hint, err
+                ))
+            })
+        }
+
+        match name {
+            "target_position" => {
+                self.target_position = extract(name, value, "un tuple `(float, float)`")?
+            }
+            "power" => self.power = extract(name, value, "un entier entre 0 et 255")?,
+            "target_orientation" => {
+                self.target_orientation = extract(name, value, "un float entre 0 et 360")?
+            }
+            "velocity" => self.velocity = extract(name, value, "un tuple `(float, float)`")?,
+            "angular_velocity" => {
+                self.angular_velocity = extract(name, value, "un float (en degrés par seconde)")?
+            }
+            "motors" => {
+                self.motors = extract(name, value, "une liste d'entiers entre -255 et 255")?
+            }
+            // python booleans are converted to 0.0 and 1.0
+            "kick" => {
+                self.kick =
+                    extract(name, value, "un booléen ou un float entre 0 et 1")?.unwrap_or(0.0)
+            }
+            "kick_angle" => self.kick_angle = extract(name, value, "un float en degrés")?,
+            _ => return Err(unknown_field("Action", name, &Action::FIELDS)),
+        }
+        Ok(())
+    }
+
+    /// Dictionnaire équivalent pour `mode`, qui sera vérifié comme celui de l'API à base de fonction.
+    /// Erreur si un champ du mode manque, ou si un champ d'un autre mode est donné.
+    pub fn to_dict<'py>(
+        &self,
+        py: Python<'py>,
+        mode: ActionMode,
+    ) -> Result<Bound<'py, PyDict>, String> {
+        let dict = PyDict::new(py);
+        let set_item = |name: &str, value: Option<Bound<'py, PyAny>>| {
+            let needed = mode.fields().contains(&name);
+            match (value, needed) {
+                (Some(value), true) => {
+                    dict.set_item(name, value).unwrap();
+                    Ok(())
+                }
+                (None, false) => Ok(()),
+                (Some(_), false) => Err(format!(
+                    "`{}` ne sert pas avec MODE = \"{}\" (champs attendus : {})",
+                    name,
+                    mode.script_name(),
+                    mode.fields().join(", ")
+                )),
+                (None, true) => Err(format!(
+                    "`{}` est nécessaire avec MODE = \"{}\"",
+                    name,
+                    mode.script_name()
+                )),
+            }
+        };
+        set_item("target_position", to_python(py, self.target_position))?;
+        set_item("power", to_python(py, self.power))?;
+        set_item("target_orientation", to_python(py, self.target_orientation))?;
+        set_item("velocity", to_python(py, self.velocity))?;
+        set_item("angular_velocity", to_python(py, self.angular_velocity))?;
+        set_item("motors", to_python(py, self.motors.clone()))?;
+        dict.set_item("kick", self.kick).unwrap();
+        if let Some(kick_angle) = self.kick_angle {
+            dict.set_item("kick_angle", kick_angle).unwrap();
+        }
+        Ok(dict)
+    }
+}
+
+#[pymethods]
+impl Action {
+    /// Les champs se donnent par leur nom : `Action(target_position=(0, 0), power=255, target_orientation=0)`
+    #[new]
+    #[pyo3(signature = (**fields))]
+    fn new(fields: Option<&Bound<'_, PyDict>>) -> PyResult<Action> {
+        let mut action = Action::default();
+        for (name, value) in fields.into_iter().flat_map(|fields| fields.iter()) {
+            action.set_field(&name.extract::<String>()?, &value)?;
+        }
+        Ok(action)
+    }
+
+    fn __setattr__(&mut self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.set_field(name, value)
+    }
+
+    /// Appelé seulement quand l'attribut n'existe pas
+    fn __getattr__(&self, name: &str) -> PyResult<()> {
+        Err(unknown_field("Action", name, &Action::FIELDS))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+fn to_python<'py, T: IntoPyObjectExt<'py>>(
+    py: Python<'py>,
+    value: Option<T>,
+) -> Option<Bound<'py, PyAny>> {
+    value.map(|value| value.into_bound_py_any(py).unwrap())
+}
+
+/// Erreur pour un attribut mal orthographié, avec le nom le plus proche s'il y en a un
+fn unknown_field(class_name: &str, name: &str, fields: &[&str]) -> PyErr {
+    let suggestion = closest_field(name, fields)
+        .map(|field| format!(" Vouliez-vous dire `{}` ?", field))
+        .unwrap_or_default();
+    PyAttributeError::new_err(format!(
+        "`{}` n'a pas d'attribut `{}`.{} Attributs : {}",
+        class_name,
+        name,
+        suggestion,
+        fields.join(", ")
+    ))
+}
+
+/// Champ de `fields` le plus proche de `name`, s'il est à 3 caractères près
+pub fn closest_field<'a>(name: &str, fields: &[&'a str]) -> Option<&'a str> {
+    fields
+        .iter()
+        .map(|field| (edit_distance(name, field), *field))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field)
+}
+
+/// Distance de Levenshtein, en caractères
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, char_a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = previous[j] + (char_a != *char_b) as usize;
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyListMethods;
+
+    fn position_action() -> Action {
+        Action {
+            target_position: Some((10.0, -20.0)),
+            power: Some(200),
+            target_orientation: Some(90.0),
+            kick: 1.0,
+            ..Action::default()
+        }
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("kick", "kick"), 0);
+        assert_eq!(edit_distance("", "power"), 5);
+        assert_eq!(edit_distance("powr", "power"), 1);
+        assert_eq!(edit_distance("kick", "kack"), 1);
+        assert_eq!(edit_distance("velocity", "velocyti"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("é", "e"), 1);
+    }
+
+    #[test]
+    fn closest_field_suggests_only_near_names() {
+        let fields = ["target_position", "power", "target_orientation", "kick"];
+        assert_eq!(closest_field("powr", &fields), Some("power"));
+        assert_eq!(
+            closest_field("target_positon", &fields),
+            Some("target_position")
+        );
+        assert_eq!(closest_field("velocity", &fields), None);
+    }
+
+    #[test]
+    fn to_dict_keeps_the_fields_of_the_mode() {
+        Python::attach(|py| {
+            let dict = position_action().to_dict(py, ActionMode::Position).unwrap();
+            let mut keys: Vec<String> = dict.keys().iter().map(|key| key.to_string()).collect();
+            keys.sort();
+            assert_eq!(
+                keys,
+                ["kick", "power", "target_orientation", "target_position"]
+            );
+
+            let action = Action {
+                kick_angle: Some(10.0),
+                ..position_action()
+            };
+            let dict = action.to_dict(py, ActionMode::Position).unwrap();
+            assert!(dict.contains("kick_angle").unwrap());
+
+            let action = Action {
+                velocity: Some((50.0, 0.0)),
+                angular_velocity: Some(0.0),
+                ..Action::default()
+            };
+            for mode in [
+                ActionMode::VelocityRobotFrame,
+                ActionMode::VelocityFieldFrame,
+            ] {
+                let dict = action.to_dict(py, mode).unwrap();
+                assert_eq!(dict.len(), 3);
+            }
+        });
+    }
+
+    #[test]
+    fn to_dict_rejects_missing_and_foreign_fields() {
+        Python::attach(|py| {
+            let missing = Action {
+                power: None,
+                ..position_action()
+            };
+            let err = missing.to_dict(py, ActionMode::Position).unwrap_err();
+            assert!(err.contains("`power` est nécessaire"), "{}", err);
+
+            let foreign = Action {
+                motors: Some(vec![0; infos::NB_MOTORS]),
+                ..position_action()
+            };
+            let err = foreign.to_dict(py, ActionMode::Position).unwrap_err();
+            assert!(err.contains("`motors` ne sert pas"), "{}", err);
+
+            let err = position_action()
+                .to_dict(py, ActionMode::MotorPowers)
+                .unwrap_err();
+            assert!(err.contains("`target_position` ne sert pas"), "{}", err);
+        });
+    }
+}
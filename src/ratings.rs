@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write as _},
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bots::BUILTIN_PREFIX;
+
+pub const DEFAULT_LEDGER_PATH: &str = "classement_elo.jsonl";
+pub const INITIAL_RATING: f64 = 1500.0;
+/// Variation maximale du classement Elo sur un match
+pub const ELO_K: f64 = 32.0;
+
+/// Une version du code d'une équipe : le même nom avec un contenu différent est une nouvelle version
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ScriptVersion {
+    pub team_name: String,
+    /// Empreinte FNV-1a 64 bits du contenu du script, en hexadécimal
+    pub script_hash: String,
+}
+
+impl ScriptVersion {
+    pub fn from_script(team_name: &str, script_path: &str) -> Result<ScriptVersion, RatingError> {
+        // the code of the built-in teams is the one of the simulator, their path identifies them
+        let content = if script_path.starts_with(BUILTIN_PREFIX) {
+            script_path.as_bytes().to_vec()
+        } else {
+            std::fs::read(script_path).map_err(|err| {
+                RatingError::CannotReadScript(format!("{} : {}", script_path, err))
+            })?
+        };
+        Ok(ScriptVersion {
+            team_name: team_name.to_owned(),
+            script_hash: format!("{:016x}", fnv1a_64(&content)),
+        })
+    }
+
+    /// Les 8 premiers caractères de l'empreinte suffisent à distinguer les versions à l'écran
+    #[inline]
+    pub fn short_hash(&self) -> &str {
+        &self.script_hash[..8.min(self.script_hash.len())]
+    }
+}
+
+impl Display for ScriptVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.team_name, self.short_hash())
+    }
+}
+
+/// Une ligne du registre : un match terminé et l'évolution du classement des deux équipes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// Secondes depuis le 1er janvier 1970
+    pub unix_time: u64,
+    pub teams: [ScriptVersion; 2],
+    pub scores: [u32; 2],
+    pub ratings_before: [f64; 2],
+    pub ratings_after: [f64; 2],
+}
+
+/// Classement actuel d'une version de script
+#[derive(Debug, Clone)]
+pub struct LeaderboardRow {
+    pub version: ScriptVersion,
+    pub rating: f64,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+/// Registre des matchs joués, enregistré en JSON Lines (une ligne par match).
+/// Le fichier n'est jamais réécrit, chaque match terminé y est ajouté à la fin.
+#[derive(Debug, Clone)]
+pub struct RatingLedger {
+    pub path: PathBuf,
+    pub entries: Vec<LedgerEntry>,
+    ratings: HashMap<ScriptVersion, f64>,
+}
+
+#[derive(Debug)]
+pub enum RatingError {
+    CannotReadLedger(String),
+    CannotWriteLedger(String),
+    InvalidEntry { line: usize, err_str: String },
+    CannotReadScript(String),
+}
+
+impl Display for RatingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RatingError::CannotReadLedger(err_str) => {
+                write!(f, "Je n'arrive pas à lire le classement : {}", err_str)
+            }
+            RatingError::CannotWriteLedger(err_str) => {
+                write!(
+                    f,
+                    "Je n'arrive pas à enregistrer le classement : {}",
+                    err_str
+                )
+            }
+            RatingError::InvalidEntry { line, err_str } => {
+                write!(
+                    f,
+                    "La ligne {} du classement est invalide : {}",
+                    line, err_str
+                )
+            }
+            RatingError::CannotReadScript(err_str) => {
+                write!(
+                    f,
+                    "Je n'arrive pas à lire le code de l'équipe : {}",
+                    err_str
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RatingError {}
+
+impl RatingLedger {
+    /// Charge le registre, un fichier absent est un registre vide
+    pub fn load(path: &Path) -> Result<RatingLedger, RatingError> {
+        let mut ledger = RatingLedger {
+            path: path.to_owned(),
+            entries: Vec::new(),
+            ratings: HashMap::new(),
+        };
+        if !path.exists() {
+            return Ok(ledger);
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| RatingError::CannotReadLedger(format!("{}", err)))?;
+        for (n, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: LedgerEntry =
+                serde_json::from_str(line).map_err(|err| RatingError::InvalidEntry {
+                    line: n + 1,
+                    err_str: format!("{}", err),
+                })?;
+            ledger.apply(entry);
+        }
+        Ok(ledger)
+    }
+
+    fn apply(&mut self, entry: LedgerEntry) {
+        for (version, rating) in entry.teams.iter().zip(entry.ratings_after) {
+            self.ratings.insert(version.clone(), rating);
+        }
+        self.entries.push(entry);
+    }
+
+    #[inline]
+    pub fn rating_of(&self, version: &ScriptVersion) -> f64 {
+        self.ratings.get(version).copied().unwrap_or(INITIAL_RATING)
+    }
+
+    /// Met à jour le classement Elo des deux équipes et ajoute le match à la fin du fichier
+    pub fn record(
+        &mut self,
+        teams: [ScriptVersion; 2],
+        scores: [u32; 2],
+    ) -> Result<&LedgerEntry, RatingError> {
+        let ratings_before = [self.rating_of(&teams[0]), self.rating_of(&teams[1])];
+        let ratings_after = elo_update(ratings_before, scores);
+        let entry = LedgerEntry {
+            unix_time: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            teams,
+            scores,
+            ratings_before,
+            ratings_after,
+        };
+
+        let mut line = serde_json::to_string(&entry)
+            .map_err(|err| RatingError::CannotWriteLedger(format!("{}", err)))?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|err| RatingError::CannotWriteLedger(format!("{}", err)))?;
+
+        self.apply(entry);
+        Ok(self.entries.last().unwrap())
+    }
+
+    /// Raccourci pour enregistrer un match à partir du nom et du chemin du code des équipes
+    pub fn record_match(
+        &mut self,
+        team_names: [&str; 2],
+        scripts: [&str; 2],
+        scores: [u32; 2],
+    ) -> Result<&LedgerEntry, RatingError> {
+        let teams = [
+            ScriptVersion::from_script(team_names[0], scripts[0])?,
+            ScriptVersion::from_script(team_names[1], scripts[1])?,
+        ];
+        self.record(teams, scores)
+    }
+
+    /// Toutes les versions de script, de la mieux classée à la moins bien classée
+    pub fn leaderboard(&self) -> Vec<LeaderboardRow> {
+        let mut rows: HashMap<&ScriptVersion, LeaderboardRow> = HashMap::new();
+        for entry in &self.entries {
+            for side in 0..2 {
+                let row = rows
+                    .entry(&entry.teams[side])
+                    .or_insert_with(|| LeaderboardRow {
+                        version: entry.teams[side].clone(),
+                        rating: INITIAL_RATING,
+                        played: 0,
+                        wins: 0,
+                        draws: 0,
+                        losses: 0,
+                    });
+                row.rating = entry.ratings_after[side];
+                row.played += 1;
+                match entry.scores[side].cmp(&entry.scores[1 - side]) {
+                    std::cmp::Ordering::Greater => row.wins += 1,
+                    std::cmp::Ordering::Equal => row.draws += 1,
+                    std::cmp::Ordering::Less => row.losses += 1,
+                }
+            }
+        }
+        let mut rows: Vec<LeaderboardRow> = rows.into_values().collect();
+        rows.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        rows
+    }
+
+    /// Matchs joués par une équipe, toutes versions confondues, du plus ancien au plus récent.
+    /// Renvoie aussi de quel côté l'équipe jouait pour chaque match.
+    pub fn history<'a>(
+        &'a self,
+        team_name: &'a str,
+    ) -> impl Iterator<Item = (&'a LedgerEntry, usize)> + 'a {
+        self.entries.iter().filter_map(move |entry| {
+            entry
+                .teams
+                .iter()
+                .position(|version| version.team_name == team_name)
+                .map(|side| (entry, side))
+        })
+    }
+
+    /// Tableau du classement, pour l'afficher dans le terminal
+    pub fn leaderboard_table(&self) -> String {
+        let mut table = format!(
+            "{:<4}{:<30}{:<10}{:>8}{:>4}{:>4}{:>4}{:>4}\n",
+            "#", "Équipe", "Version", "Elo", "J", "G", "N", "P"
+        );
+        for (rank, row) in self.leaderboard().iter().enumerate() {
+            let _ = writeln!(
+                table,
+                "{:<4}{:<30}{:<10}{:>8.0}{:>4}{:>4}{:>4}{:>4}",
+                rank + 1,
+                row.version.team_name,
+                row.version.short_hash(),
+                row.rating,
+                row.played,
+                row.wins,
+                row.draws,
+                row.losses
+            );
+        }
+        table
+    }
+
+    /// Évolution du classement d'une équipe, version par version
+    pub fn history_table(&self, team_name: &str) -> String {
+        let mut table = format!(
+            "{:<10}{:<30}{:>8}{:>8}{:>8}\n",
+            "Version", "Adversaire", "Score", "Avant", "Après"
+        );
+        for (entry, side) in self.history(team_name) {
+            let _ = writeln!(
+                table,
+                "{:<10}{:<30}{:>8}{:>8.0}{:>8.0}",
+                entry.teams[side].short_hash(),
+                entry.teams[1 - side].to_string(),
+                format!("{} - {}", entry.scores[side], entry.scores[1 - side]),
+                entry.ratings_before[side],
+                entry.ratings_after[side]
+            );
+        }
+        table
+    }
+}
+
+/// Nouveaux classements Elo après un match
+pub fn elo_update(ratings: [f64; 2], scores: [u32; 2]) -> [f64; 2] {
+    let expected_first = 1.0 / (1.0 + 10f64.powf((ratings[1] - ratings[0]) / 400.0));
+    let actual_first = match scores[0].cmp(&scores[1]) {
+        std::cmp::Ordering::Greater => 1.0,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Less => 0.0,
+    };
+    let change = ELO_K * (actual_first - expected_first);
+    [ratings[0] + change, ratings[1] - change]
+}
+
+/// Empreinte FNV-1a : stable d'une version de Rust à l'autre, contrairement à `DefaultHasher`
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    #[test]
+    fn elo_update_is_symmetric() {
+        for (ratings, scores) in [
+            ([1500.0, 1500.0], [2, 1]),
+            ([1700.0, 1400.0], [0, 3]),
+            ([1320.0, 1610.0], [1, 1]),
+        ] {
+            let after = elo_update(ratings, scores);
+            let swapped = elo_update([ratings[1], ratings[0]], [scores[1], scores[0]]);
+            assert!((after[0] - swapped[1]).abs() < EPSILON);
+            assert!((after[1] - swapped[0]).abs() < EPSILON);
+            // what one team wins, the other loses
+            assert!((after[0] + after[1] - ratings[0] - ratings[1]).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn elo_update_uses_k_32() {
+        // equal ratings : the winner gets half of K
+        let after = elo_update([1500.0, 1500.0], [1, 0]);
+        assert!((after[0] - (1500.0 + ELO_K / 2.0)).abs() < EPSILON);
+        assert!((after[1] - (1500.0 - ELO_K / 2.0)).abs() < EPSILON);
+        assert_eq!(ELO_K, 32.0);
+
+        // the change never exceeds K, even for the biggest upset
+        let after = elo_update([1000.0, 2400.0], [5, 0]);
+        assert!(after[0] - 1000.0 < ELO_K);
+        assert!(after[0] - 1000.0 > ELO_K * 0.99);
+    }
+
+    #[test]
+    fn elo_update_draws() {
+        // a draw between equal teams changes nothing
+        assert_eq!(elo_update([1500.0, 1500.0], [2, 2]), [1500.0, 1500.0]);
+
+        // the favourite loses points on a draw, the underdog wins as many
+        let after = elo_update([1700.0, 1500.0], [0, 0]);
+        let expected_first = 1.0 / (1.0 + 10f64.powf(-200.0 / 400.0));
+        let change = ELO_K * (0.5 - expected_first);
+        assert!(change < 0.0);
+        assert!((after[0] - (1700.0 + change)).abs() < EPSILON);
+        assert!((after[1] - (1500.0 - change)).abs() < EPSILON);
+    }
+}
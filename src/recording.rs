@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use rerun::{RecordingStream, TextDocument};
+use serde::{Deserialize, Serialize};
+
+use crate::{match_config::MatchRules, ratings::ScriptVersion, simulator::Simulator};
+
+pub const DEFAULT_RECORDINGS_DIR: &str = "enregistrements";
+/// Les métadonnées en JSON, relues par le mode « revoir »
+pub const METADATA_ENTITY: &str = "match/metadata";
+
+/// Informations sur le match, enregistrées comme entités statiques au début du fichier .rrd
+/// et mises à jour à la fin avec le score final
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchMetadata {
+    /// Date UTC du début du match, `AAAA-MM-JJ HH:MM:SS`
+    pub date: String,
+    pub team_names: [String; 2],
+    /// Empreinte du code de chaque équipe, la même que dans le classement Elo
+    pub script_hashes: [String; 2],
+    pub seed: u64,
+    pub rules: MatchRules,
+    pub score: [u32; 2],
+    pub finished: bool,
+}
+
+impl MatchMetadata {
+    pub fn from_simulation(simulation: &Simulator, date: &str) -> MatchMetadata {
+        let team_names = simulation.team_names();
+        let scripts = simulation.team_scripts();
+        let script_hash = |n: usize| {
+            ScriptVersion::from_script(team_names[n], scripts[n])
+                .map(|version| version.script_hash)
+                .unwrap_or_else(|_| "inconnue".to_owned())
+        };
+        MatchMetadata {
+            date: date.to_owned(),
+            team_names: team_names.map(str::to_owned),
+            script_hashes: [script_hash(0), script_hash(1)],
+            seed: simulation.seed,
+            rules: simulation.rules.clone(),
+            score: [
+                simulation.game_referee.score_team_left,
+                simulation.game_referee.score_team_right,
+            ],
+            finished: simulation.is_finished(),
+        }
+    }
+
+    /// `match/summary` est lisible dans le viewer, `match/metadata` contient le JSON pour les outils
+    pub fn log(&self, rec: &RecordingStream) {
+        let duration = match self.rules.duration {
+            Some(duration) => format!("{} s", duration),
+            None => "illimitée".to_owned(),
+        };
+        let max_score = match self.rules.max_score {
+            Some(max_score) => format!("{}", max_score),
+            None => "aucun".to_owned(),
+        };
+        let summary = format!(
+            "# {} {} - {} {}\n\n\
+             | | |\n|---|---|\n\
+             | date | {} |\n\
+             | graine | {} |\n\
+             | durée | {} |\n\
+             | score maximal | {} |\n\
+             | code {} | `{}` |\n\
+             | code {} | `{}` |\n\
+             | terminé | {} |\n",
+            self.team_names[0],
+            self.score[0],
+            self.score[1],
+            self.team_names[1],
+            self.date,
+            self.seed,
+            duration,
+            max_score,
+            self.team_names[0],
+            self.script_hashes[0],
+            self.team_names[1],
+            self.script_hashes[1],
+            if self.finished { "oui" } else { "non" },
+        );
+        rec.log_static("match/summary", &TextDocument::from_markdown(summary))
+            .unwrap();
+        rec.log_static(
+            METADATA_ENTITY,
+            &TextDocument::new(serde_json::to_string_pretty(self).unwrap())
+                .with_media_type("application/json"),
+        )
+        .unwrap();
+    }
+}
+
+/// Date et heure UTC actuelles, `AAAA-MM-JJ HH:MM:SS`
+pub fn now_utc() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let time_of_day = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// `dossier/AAAA-MM-JJ_HH-MM-SS_equipe1_vs_equipe2.rrd`
+pub fn recording_path(dir: &Path, date: &str, team_names: [&str; 2]) -> PathBuf {
+    let file_name = format!(
+        "{}_{}_vs_{}.rrd",
+        date.replace(' ', "_").replace(':', "-"),
+        sanitize(team_names[0]),
+        sanitize(team_names[1])
+    );
+    dir.join(file_name)
+}
+
+/// Les noms d'équipe viennent des noms de fichier, mais on évite tout ce qui gênerait dans un chemin
+pub fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Jour du calendrier à partir du nombre de jours depuis le 1er janvier 1970 (algorithme de H. Hinnant)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
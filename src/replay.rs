@@ -0,0 +1,345 @@
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::Arc;
+
+use rerun::dataframe::external::re_chunk::Chunk;
+use rerun::dataframe::external::re_chunk_store::{ChunkStore, ChunkStoreConfig};
+use rerun::dataframe::{EntityPath, TimelineName};
+use rerun::external::arrow::array::{
+    Array, BooleanArray, Float32Array, StringArray, UInt32Array, UInt64Array,
+};
+use rerun::{DynamicArchetype, RecordingStream, TextDocument};
+use serde_json::Value;
+
+use crate::{
+    app_ui::EVENT_ARCHETYPE,
+    game_event::{GameEvent, TimedEvent},
+    match_config::MatchConfig,
+    recording::{self, MatchMetadata},
+    snapshot::SimulatorSnapshot,
+};
+
+/// Un instantané du simulateur est écrit tous les `SNAPSHOT_PERIOD_TICKS` ticks (2 s)
+pub const SNAPSHOT_PERIOD_TICKS: u64 = 120;
+/// Instantanés du simulateur, sur la timeline `tick`
+pub const SNAPSHOT_ENTITY: &str = "match/snapshot";
+/// Nom de l'archétype des instantanés : `tick` et `state`, l'instantané en JSON
+pub const SNAPSHOT_ARCHETYPE: &str = "simulator.Snapshot";
+
+/// Timeline des ticks de la simulation dans l'enregistrement
+pub const TICK_TIMELINE: &str = "tick";
+/// Configuration du match en JSON, statique : de quoi le reprendre depuis un instantané
+pub const CONFIG_ENTITY: &str = "match/config";
+/// Parent des entités `events/<type>`, voir `app_ui::event_archetype`
+pub const EVENTS_ENTITY: &str = "events";
+
+/// Écrit la configuration du match dans l'enregistrement
+pub fn log_config(rec: &RecordingStream, config: &MatchConfig) {
+    rec.log_static(
+        CONFIG_ENTITY,
+        &TextDocument::new(serde_json::to_string_pretty(config).unwrap())
+            .with_media_type("application/json"),
+    )
+    .unwrap();
+}
+
+/// Écrit un instantané au tick courant de l'enregistrement
+pub fn log_snapshot(rec: &RecordingStream, snapshot: &SimulatorSnapshot) {
+    let state = serde_json::to_string(snapshot).unwrap();
+    let archetype = DynamicArchetype::new(SNAPSHOT_ARCHETYPE)
+        .with_component_from_data("tick", Arc::new(UInt64Array::from(vec![snapshot.tick_nb])))
+        .with_component_from_data("state", Arc::new(StringArray::from(vec![state])));
+    rec.log(SNAPSHOT_ENTITY, &archetype).unwrap();
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(String),
+    /// Une entité du match ne se relit pas
+    Format {
+        entity: String,
+        message: String,
+    },
+    /// Ni métadonnées ni ticks : ce n'est pas un match du simulateur
+    Incomplete,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "Impossible de lire le match : {}", err),
+            ReplayError::Format { entity, message } => {
+                write!(
+                    f,
+                    "`{}` invalide dans l'enregistrement : {}",
+                    entity, message
+                )
+            }
+            ReplayError::Incomplete => write!(
+                f,
+                "Le fichier ne contient pas de match du simulateur (ni `{}`, ni timeline `{}`)",
+                recording::METADATA_ENTITY,
+                TICK_TIMELINE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Un match enregistré dans un fichier .rrd. Le viewer affiche l'enregistrement lui-même,
+/// on en garde ce qu'il faut pour s'y repérer et pour reprendre le match.
+pub struct Replay {
+    pub metadata: MatchMetadata,
+    /// Absente des matchs enregistrés avant qu'elle ne soit écrite dans le .rrd
+    pub config: Option<MatchConfig>,
+    /// Par tick croissant, relus depuis `events/<type>`
+    pub events: Vec<TimedEvent>,
+    /// Par tick croissant, relus depuis `match/snapshot`
+    pub snapshots: Vec<SimulatorSnapshot>,
+    pub last_tick: u64,
+    /// Durée d'un tick en secondes
+    pub tick_duration: f32,
+    /// Contenu du fichier à envoyer au viewer, sans les instantanés qu'il n'a pas à afficher
+    pub chunks: Vec<Chunk>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Replay, ReplayError> {
+        let stores = ChunkStore::from_rrd_filepath(&ChunkStoreConfig::DEFAULT, path)
+            .map_err(|err| ReplayError::Io(format!("{} : {:#}", path.display(), err)))?;
+        let store = stores
+            .into_iter()
+            .find(|(store_id, _)| store_id.is_recording())
+            .map(|(_, store)| store)
+            .ok_or(ReplayError::Incomplete)?;
+
+        let metadata = static_text(&store, recording::METADATA_ENTITY)
+            .ok_or(ReplayError::Incomplete)
+            .and_then(|text| parse_json(recording::METADATA_ENTITY, &text))?;
+        let config = static_text(&store, CONFIG_ENTITY)
+            .map(|text| parse_json(CONFIG_ENTITY, &text))
+            .transpose()?;
+        let last_tick = store
+            .time_range(&TimelineName::new(TICK_TIMELINE))
+            .ok_or(ReplayError::Incomplete)?
+            .max()
+            .as_i64()
+            .max(0) as u64;
+        let events = read_events(&store)?;
+        let tick_duration = events
+            .iter()
+            .find(|timed_event| timed_event.tick > 0)
+            .map_or(1.0 / 60.0, |timed_event| {
+                timed_event.time / timed_event.tick as f32
+            });
+
+        let snapshots = read_snapshots(&store)?;
+        let snapshot_path = EntityPath::from(SNAPSHOT_ENTITY);
+
+        Ok(Replay {
+            metadata,
+            config,
+            events,
+            snapshots,
+            last_tick,
+            tick_duration,
+            chunks: store
+                .iter_chunks()
+                .filter(|chunk| chunk.entity_path() != &snapshot_path)
+                .map(|chunk| (**chunk).clone())
+                .collect(),
+        })
+    }
+
+    /// Dernier instantané à ou avant `tick`
+    pub fn snapshot_before(&self, tick: u64) -> Option<&SimulatorSnapshot> {
+        self.snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.tick_nb <= tick)
+    }
+
+    /// Score après les buts marqués jusqu'à `tick` inclus
+    pub fn score_at(&self, tick: u64) -> [u32; 2] {
+        self.events
+            .iter()
+            .take_while(|timed_event| timed_event.tick <= tick)
+            .filter_map(|timed_event| match &timed_event.event {
+                GameEvent::Goal { score, .. } => Some(*score),
+                _ => None,
+            })
+            .last()
+            .unwrap_or([0, 0])
+    }
+
+    /// Premier évènement de type `kind` (voir `GameEvent::kind`) strictement après `tick`
+    pub fn next_event(&self, tick: u64, kind: &str) -> Option<&TimedEvent> {
+        self.events
+            .iter()
+            .find(|timed_event| timed_event.tick > tick && timed_event.event.kind() == kind)
+    }
+
+    /// Dernier évènement de type `kind` strictement avant `tick`
+    pub fn previous_event(&self, tick: u64, kind: &str) -> Option<&TimedEvent> {
+        self.events
+            .iter()
+            .rev()
+            .find(|timed_event| timed_event.tick < tick && timed_event.event.kind() == kind)
+    }
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(entity: &str, text: &str) -> Result<T, ReplayError> {
+    serde_json::from_str(text).map_err(|err| ReplayError::Format {
+        entity: entity.to_owned(),
+        message: format!("{}", err),
+    })
+}
+
+/// Dernier texte (`TextDocument`) écrit en statique sous `entity_path`
+fn static_text(store: &ChunkStore, entity_path: &str) -> Option<String> {
+    let entity_path = EntityPath::from(entity_path);
+    store
+        .iter_chunks()
+        .filter(|chunk| chunk.is_static() && chunk.entity_path() == &entity_path)
+        .flat_map(|chunk| {
+            let row_ids = chunk.row_ids_slice();
+            chunk
+                .components()
+                .iter()
+                .filter(|(descriptor, _)| {
+                    descriptor.component_type.is_some_and(|component_type| {
+                        component_type.as_str() == "rerun.components.Text"
+                    })
+                })
+                .flat_map(move |(_, list)| {
+                    (0..list.len())
+                        .filter(|row| list.is_valid(*row))
+                        .map(move |row| (row_ids[row], list.value(row)))
+                })
+        })
+        .max_by_key(|(row_id, _)| *row_id)
+        .and_then(|(_, texts)| {
+            let texts = texts.as_any().downcast_ref::<StringArray>()?;
+            (!texts.is_empty()).then(|| texts.value(0).to_owned())
+        })
+}
+
+/// Les évènements sont rebâtis à partir des champs de l'archétype : `kind` est le `type` du format JSON,
+/// et les deux robots d'un choc sont dans `robot`
+fn read_events(store: &ChunkStore) -> Result<Vec<TimedEvent>, ReplayError> {
+    let events_path = EntityPath::from(EVENTS_ENTITY);
+    let mut events = Vec::new();
+    for chunk in store.iter_chunks() {
+        if !chunk.entity_path().starts_with(&events_path) {
+            continue;
+        }
+        let fields: Vec<_> = chunk
+            .components()
+            .iter()
+            .filter(|(descriptor, _)| {
+                descriptor
+                    .archetype
+                    .is_some_and(|archetype| archetype.as_str() == EVENT_ARCHETYPE)
+            })
+            .map(|(descriptor, list)| {
+                // `simulator.GameEvent:kind` -> `kind`
+                let component = descriptor.component.as_str();
+                (component.rsplit(':').next().unwrap_or(component), list)
+            })
+            .collect();
+        if fields.is_empty() {
+            continue;
+        }
+        for (row, row_id) in chunk.row_ids().enumerate() {
+            let mut object = serde_json::Map::new();
+            for (field, list) in &fields {
+                if list.is_valid(row) {
+                    object.insert((*field).to_owned(), to_json(list.value(row).as_ref()));
+                }
+            }
+            // the rows of the markers logged at the same place
+            if object.is_empty() {
+                continue;
+            }
+            if let Some(kind) = object.remove("kind") {
+                object.insert("type".to_owned(), kind);
+            }
+            if let Some(Value::Array(robots)) = object.get("robot").cloned()
+                && let [robot1, robot2] = robots.as_slice()
+            {
+                object.insert("robot1".to_owned(), robot1.clone());
+                object.insert("robot2".to_owned(), robot2.clone());
+            }
+            let timed_event: TimedEvent =
+                serde_json::from_value(Value::Object(object)).map_err(|err| {
+                    ReplayError::Format {
+                        entity: chunk.entity_path().to_string(),
+                        message: format!("{}", err),
+                    }
+                })?;
+            events.push((timed_event.tick, row_id, timed_event));
+        }
+    }
+    events.sort_by_key(|(tick, row_id, _)| (*tick, *row_id));
+    Ok(events
+        .into_iter()
+        .map(|(_, _, timed_event)| timed_event)
+        .collect())
+}
+
+/// Une seule valeur pour un champ qui n'en a qu'une, un tableau sinon
+fn to_json(array: &dyn Array) -> Value {
+    let any = array.as_any();
+    let values: Vec<Value> = if let Some(array) = any.downcast_ref::<StringArray>() {
+        array.iter().map(|value| value.into()).collect()
+    } else if let Some(array) = any.downcast_ref::<Float32Array>() {
+        array
+            .iter()
+            .map(|value| value.map(f64::from).into())
+            .collect()
+    } else if let Some(array) = any.downcast_ref::<UInt64Array>() {
+        array.iter().map(|value| value.into()).collect()
+    } else if let Some(array) = any.downcast_ref::<UInt32Array>() {
+        array.iter().map(|value| value.into()).collect()
+    } else if let Some(array) = any.downcast_ref::<BooleanArray>() {
+        array.iter().map(|value| value.into()).collect()
+    } else {
+        Vec::new()
+    };
+    match <[Value; 1]>::try_from(values) {
+        Ok([value]) => value,
+        Err(values) => Value::Array(values),
+    }
+}
+
+/// Les instantanés sont relus dans l'ordre des ticks
+fn read_snapshots(store: &ChunkStore) -> Result<Vec<SimulatorSnapshot>, ReplayError> {
+    let snapshot_path = EntityPath::from(SNAPSHOT_ENTITY);
+    let mut snapshots = Vec::new();
+    for chunk in store.iter_chunks() {
+        if chunk.entity_path() != &snapshot_path {
+            continue;
+        }
+        for (descriptor, list) in chunk.components().iter() {
+            let is_state = descriptor
+                .archetype
+                .is_some_and(|archetype| archetype.as_str() == SNAPSHOT_ARCHETYPE)
+                && descriptor.component.as_str().ends_with(":state");
+            if !is_state {
+                continue;
+            }
+            for row in (0..list.len()).filter(|row| list.is_valid(*row)) {
+                let states = list.value(row);
+                let Some(states) = states.as_any().downcast_ref::<StringArray>() else {
+                    continue;
+                };
+                for state in states.iter().flatten() {
+                    snapshots.push(parse_json::<SimulatorSnapshot>(SNAPSHOT_ENTITY, state)?);
+                }
+            }
+        }
+    }
+    snapshots.sort_by_key(|snapshot| snapshot.tick_nb);
+    Ok(snapshots)
+}
@@ -1,6 +1,8 @@
 use nalgebra::Vector2;
 
 use crate::infos;
+use crate::orientation_controller::{OrientationController, PidGains};
+use crate::robot_profile::RobotProfile;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct RobotHandler {
@@ -43,6 +45,15 @@ pub struct RobotBuilder {
     pub restitution: f32,
     pub mass: f32,
     pub radius: f32,
+    pub rotation_gains: PidGains,
+    pub max_angular_acceleration: f32,
+    pub max_angular_velocity: f32,
+    pub kick_power: f32,
+    pub kick_cooldown: u64,
+    /// Vitesse maximale demandable en mode vitesse
+    pub max_speed: f32,
+    /// Vitesse que le robot ne dépasse jamais, quel que soit le mode, si le profil en donne une
+    pub speed_limit: Option<f32>,
 }
 
 impl RobotBuilder {
@@ -53,10 +64,7 @@ impl RobotBuilder {
         }
     }
 
-    pub fn from_basic_robot(
-        team_name: &str,
-        robot_number: u8,
-    ) -> Self {
+    pub fn from_basic_robot(team_name: &str, robot_number: u8) -> Self {
         RobotBuilder {
             team_name: team_name.to_owned(),
             robot_number,
@@ -66,6 +74,38 @@ impl RobotBuilder {
             restitution: infos::ROBOT_RESTITUTION,
             mass: infos::ROBOT_MASS,
             radius: infos::ROBOT_RADIUS,
+            rotation_gains: PidGains::default(),
+            max_angular_acceleration: infos::ROTATION_MAX_ACCELERATION,
+            max_angular_velocity: infos::ROTATION_MAX_SPEED,
+            kick_power: infos::KICK_POWER,
+            kick_cooldown: infos::NB_MIN_TICK_BETWEEN_KICKS,
+            max_speed: infos::ROBOT_MAX_SPEED,
+            speed_limit: None,
         }
     }
+
+    /// Le profil doit avoir été validé au préalable (`RobotProfile::validate`)
+    pub fn from_profile(team_name: &str, robot_number: u8, profile: &RobotProfile) -> Self {
+        RobotBuilder {
+            friction: profile.friction,
+            linear_damping: profile.linear_damping,
+            angular_damping: profile.angular_damping,
+            restitution: profile.restitution,
+            mass: profile.simulator_mass(),
+            radius: profile.radius,
+            kick_power: profile.kick_power,
+            kick_cooldown: profile.kick_cooldown,
+            max_speed: profile.command_max_speed(),
+            speed_limit: profile.max_speed,
+            ..RobotBuilder::from_basic_robot(team_name, robot_number)
+        }
+    }
+
+    pub fn to_orientation_controller(&self) -> OrientationController {
+        OrientationController::new(
+            self.rotation_gains,
+            self.max_angular_acceleration,
+            self.max_angular_velocity,
+        )
+    }
 }
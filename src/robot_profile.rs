@@ -0,0 +1,155 @@
+use std::{fmt::Display, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::infos;
+
+/// Description du matériel d'un robot, lue depuis un fichier TOML fourni par l'équipe.
+/// Les unités sont celles du monde réel (cm, kg) pour pouvoir comparer avec les limites RCJ.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RobotProfile {
+    pub name: String,
+    pub radius: f32, // cm
+    pub mass: f32,   // kg
+    pub friction: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    pub restitution: f32,
+    pub kick_power: f32,
+    pub kick_cooldown: u64,    // en ticks
+    pub kick_angle_range: f32, // degrés, décalage maximal du tir par rapport à l'avant du robot
+    /// cm/s, absent : seule la vitesse demandée en mode vitesse est limitée, à `infos::ROBOT_MAX_SPEED`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_speed: Option<f32>,
+}
+
+impl Default for RobotProfile {
+    fn default() -> Self {
+        RobotProfile {
+            name: "robot de base".to_owned(),
+            radius: infos::ROBOT_RADIUS,
+            mass: infos::ROBOT_MASS_KG,
+            friction: infos::ROBOT_FRICTION,
+            linear_damping: infos::ROBOT_LINEAR_DAMPING,
+            angular_damping: infos::ROBOT_ANGULAR_DAMPING,
+            restitution: infos::ROBOT_RESTITUTION,
+            kick_power: infos::KICK_POWER,
+            kick_cooldown: infos::NB_MIN_TICK_BETWEEN_KICKS,
+            kick_angle_range: 0.0,
+            max_speed: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RobotProfileError {
+    CannotReadFile(String),
+    InvalidFormat(String),
+    OutOfLimits {
+        field_name: &'static str,
+        value: f32,
+        limit_hint: String,
+    },
+}
+
+impl Display for RobotProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RobotProfileError::CannotReadFile(err_str) => {
+                write!(f, "Je n'arrive pas à lire le profil du robot : {}", err_str)
+            }
+            RobotProfileError::InvalidFormat(err_str) => {
+                write!(
+                    f,
+                    "Le profil du robot n'est pas un TOML valide : {}",
+                    err_str
+                )
+            }
+            RobotProfileError::OutOfLimits {
+                field_name,
+                value,
+                limit_hint,
+            } => write!(
+                f,
+                "Dans le profil du robot, `{}` vaut {} mais doit être {}",
+                field_name, value, limit_hint
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RobotProfileError {}
+
+impl RobotProfile {
+    pub fn load(path: &Path) -> Result<RobotProfile, RobotProfileError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| RobotProfileError::CannotReadFile(format!("{}", err)))?;
+        let profile: RobotProfile = toml::from_str(&content)
+            .map_err(|err| RobotProfileError::InvalidFormat(format!("{}", err)))?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Vérifie que le robot respecte les règles RCJ Soccer Open et reste simulable
+    pub fn validate(&self) -> Result<(), RobotProfileError> {
+        check_range(
+            "radius",
+            self.radius,
+            1.0,
+            infos::RCJ_MAX_ROBOT_DIAMETER / 2.0,
+        )?;
+        check_range("mass", self.mass, 0.1, infos::RCJ_MAX_ROBOT_MASS_KG)?;
+        check_range("friction", self.friction, 0.0, 1.0)?;
+        check_range("linear_damping", self.linear_damping, 0.0, 10.0)?;
+        check_range("angular_damping", self.angular_damping, 0.0, 10.0)?;
+        check_range("restitution", self.restitution, 0.0, 1.0)?;
+        check_range("kick_power", self.kick_power, 0.0, infos::MAX_KICK_POWER)?;
+        check_range(
+            "kick_angle_range",
+            self.kick_angle_range,
+            0.0,
+            infos::MAX_KICK_ANGLE,
+        )?;
+        if let Some(max_speed) = self.max_speed {
+            check_range("max_speed", max_speed, 1.0, infos::MAX_ROBOT_SPEED)?;
+        }
+        if self.kick_cooldown < infos::MIN_TICK_BETWEEN_KICKS {
+            return Err(RobotProfileError::OutOfLimits {
+                field_name: "kick_cooldown",
+                value: self.kick_cooldown as f32,
+                limit_hint: format!("au moins {}", infos::MIN_TICK_BETWEEN_KICKS),
+            });
+        }
+        Ok(())
+    }
+
+    /// Vitesse maximale qu'un code peut demander au robot
+    #[inline]
+    pub fn command_max_speed(&self) -> f32 {
+        self.max_speed.unwrap_or(infos::ROBOT_MAX_SPEED)
+    }
+
+    /// Masse dans l'unité utilisée par le moteur physique
+    #[inline]
+    pub fn simulator_mass(&self) -> f32 {
+        self.mass * infos::ROBOT_MASS / infos::ROBOT_MASS_KG
+    }
+}
+
+#[inline]
+fn check_range(
+    field_name: &'static str,
+    value: f32,
+    min: f32,
+    max: f32,
+) -> Result<(), RobotProfileError> {
+    if !(min <= value && value <= max) {
+        return Err(RobotProfileError::OutOfLimits {
+            field_name,
+            value,
+            limit_hint: format!("compris entre {} et {}", min, max),
+        });
+    }
+    Ok(())
+}
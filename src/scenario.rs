@@ -0,0 +1,97 @@
+use std::{fmt::Display, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::match_config::Kickoff;
+
+/// État d'un robot au début d'un scénario, dans le repère du terrain vu par la première équipe
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioRobot {
+    pub x: f32,
+    pub y: f32,
+    pub orientation: f32, // degrés, même convention que `my_orientation` dans le code des équipes
+    pub velocity: (f32, f32), // cm/s
+    pub angular_velocity: f32, // degrés/s
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioBall {
+    pub x: f32,
+    pub y: f32,
+    pub velocity: (f32, f32), // cm/s
+}
+
+/// Situation de départ d'un match, à la place du coup d'envoi habituel (pour travailler les phases de jeu).
+/// Elle ne sert qu'au début : après un but, les robots sont replacés selon `Kickoff`.
+/// Les robots sont dans le même ordre que `Simulator::robots`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+    pub robots: [ScenarioRobot; 4],
+    pub ball: ScenarioBall,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Scenario::from_kickoff(&Kickoff::default())
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    CannotReadFile(String),
+    CannotWriteFile(String),
+    InvalidFormat(String),
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenarioError::CannotReadFile(err_str) => {
+                write!(f, "Je n'arrive pas à lire le scénario : {}", err_str)
+            }
+            ScenarioError::CannotWriteFile(err_str) => {
+                write!(f, "Je n'arrive pas à enregistrer le scénario : {}", err_str)
+            }
+            ScenarioError::InvalidFormat(err_str) => {
+                write!(f, "Le scénario n'est pas un TOML valide : {}", err_str)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl Scenario {
+    /// Les positions de l'engagement, tout le monde à l'arrêt
+    pub fn from_kickoff(kickoff: &Kickoff) -> Scenario {
+        Scenario {
+            robots: kickoff.robots.map(|start| ScenarioRobot {
+                x: start.x,
+                y: start.y,
+                orientation: start.orientation,
+                ..Default::default()
+            }),
+            ball: ScenarioBall {
+                x: kickoff.ball.0,
+                y: kickoff.ball.1,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Scenario, ScenarioError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| ScenarioError::CannotReadFile(format!("{}", err)))?;
+        toml::from_str(&content).map_err(|err| ScenarioError::InvalidFormat(format!("{}", err)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ScenarioError> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|err| ScenarioError::CannotWriteFile(format!("{}", err)))?;
+        std::fs::write(path, content)
+            .map_err(|err| ScenarioError::CannotWriteFile(format!("{}", err)))
+    }
+}
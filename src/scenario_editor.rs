@@ -0,0 +1,196 @@
+use std::path::Path;
+
+use nalgebra::Vector2;
+use rerun::external::egui::{self, Color32, Pos2, Stroke, Vec2};
+
+use crate::{field_view, infos, match_config::Kickoff, scenario::Scenario};
+
+/// Les flèches de vitesse montrent la distance parcourue en ce temps (s)
+const VELOCITY_ARROW_TIME: f32 = 0.25;
+/// Largeur maximale du terrain dessiné, en points
+const MAX_FIELD_WIDTH: f32 = 500.0;
+
+/// Édition du scénario de départ : on déplace les robots et la balle à la souris,
+/// et on règle orientations et vitesses dans le tableau en dessous
+#[derive(Debug, Default)]
+pub struct ScenarioEditor {
+    /// Objet déplacé à la souris : les robots (0 à 3) puis la balle (4)
+    dragged: Option<usize>,
+    pub path: String,
+    pub message: Option<String>,
+}
+
+impl ScenarioEditor {
+    /// `scenario` : `None` si le match commence par le coup d'envoi habituel
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        scenario: &mut Option<Scenario>,
+        kickoff: &Kickoff,
+        team_colors: [[u8; 3]; 2],
+    ) {
+        let mut enabled = scenario.is_some();
+        ui.checkbox(&mut enabled, "commencer le match par ce scénario");
+        if enabled != scenario.is_some() {
+            *scenario = enabled.then(|| Scenario::from_kickoff(kickoff));
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("fichier :");
+            ui.text_edit_singleline(&mut self.path);
+            if ui.button("…").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("toml", &["toml"])
+                    .pick_file()
+            {
+                self.path = path.display().to_string();
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Charger").clicked() {
+                match Scenario::load(Path::new(&self.path)) {
+                    Ok(loaded) => {
+                        *scenario = Some(loaded);
+                        self.message = None;
+                    }
+                    Err(err) => self.message = Some(format!("{err}")),
+                }
+            }
+            if let Some(scenario) = scenario.as_mut() {
+                if ui.button("Enregistrer").clicked() {
+                    self.message = Some(match scenario.save(Path::new(&self.path)) {
+                        Ok(()) => format!("scénario enregistré dans {}", self.path),
+                        Err(err) => format!("{err}"),
+                    });
+                }
+                if ui.button("Repartir de l'engagement").clicked() {
+                    *scenario = Scenario::from_kickoff(kickoff);
+                }
+            }
+        });
+        if let Some(message) = &self.message {
+            ui.label(egui::RichText::new(message).color(Color32::ORANGE));
+        }
+
+        if let Some(scenario) = scenario {
+            self.ui_field(ui, scenario, team_colors);
+            ui_values(ui, scenario);
+        }
+    }
+
+    /// Terrain vu de dessus, dans le même sens que le viewer (y vers le bas)
+    fn ui_field(&mut self, ui: &mut egui::Ui, scenario: &mut Scenario, team_colors: [[u8; 3]; 2]) {
+        let (response, painter, transform) = field_view::allocate_field(ui, MAX_FIELD_WIDTH);
+        field_view::paint_field(&painter, &transform);
+
+        // drag and drop
+        if response.drag_started()
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            let ball = scenario.ball;
+            self.dragged = field_view::pick(
+                transform.to_field(pointer),
+                scenario
+                    .robots
+                    .iter()
+                    .map(|robot| (Vector2::new(robot.x, robot.y), infos::ROBOT_RADIUS))
+                    .chain([(Vector2::new(ball.x, ball.y), infos::BALL_RADIUS)]),
+            );
+        }
+        if let Some(dragged) = self.dragged
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            let position = field_view::clamp_to_field(transform.to_field(pointer));
+            match scenario.robots.get_mut(dragged) {
+                Some(robot) => (robot.x, robot.y) = (position.x, position.y),
+                None => (scenario.ball.x, scenario.ball.y) = (position.x, position.y),
+            }
+        }
+        if response.drag_stopped() {
+            self.dragged = None;
+        }
+
+        for (n, robot) in scenario.robots.iter().enumerate() {
+            // robots 0 and 1 are in the first team, 2 and 3 in the second
+            let [r, g, b] = team_colors[n / 2];
+            let position = Vector2::new(robot.x, robot.y);
+            // the orientation of the scripts is the direction of the front
+            field_view::paint_robot(
+                &painter,
+                &transform,
+                position,
+                infos::ROBOT_RADIUS,
+                robot.orientation.to_radians(),
+                Color32::from_rgb(r, g, b),
+                &format!("{}", n % 2 + 1),
+            );
+            draw_velocity(
+                &painter,
+                transform.to_screen(position),
+                robot.velocity,
+                transform.scale,
+            );
+        }
+        let ball_position = transform.to_screen(Vector2::new(scenario.ball.x, scenario.ball.y));
+        painter.circle_filled(
+            ball_position,
+            infos::BALL_RADIUS * transform.scale,
+            field_view::BALL_COLOR,
+        );
+        draw_velocity(
+            &painter,
+            ball_position,
+            scenario.ball.velocity,
+            transform.scale,
+        );
+    }
+}
+
+fn draw_velocity(painter: &egui::Painter, origin: Pos2, velocity: (f32, f32), scale: f32) {
+    if velocity != (0.0, 0.0) {
+        painter.arrow(
+            origin,
+            Vec2::new(velocity.0, velocity.1) * VELOCITY_ARROW_TIME * scale,
+            Stroke::new(1.5, Color32::YELLOW),
+        );
+    }
+}
+
+/// Positions, orientations et vitesses, pour les régler précisément
+fn ui_values(ui: &mut egui::Ui, scenario: &mut Scenario) {
+    let half_depth = infos::FIELD_DEPTH / 2.0;
+    let half_width = infos::FIELD_WIDTH / 2.0;
+    let max_speed = infos::MAX_ROBOT_SPEED;
+    egui::Grid::new("scenario").striped(true).show(ui, |ui| {
+        for title in [
+            "",
+            "x",
+            "y",
+            "orientation (°)",
+            "vx (cm/s)",
+            "vy (cm/s)",
+            "rotation (°/s)",
+        ] {
+            ui.strong(title);
+        }
+        ui.end_row();
+        for (n, robot) in scenario.robots.iter_mut().enumerate() {
+            ui.label(format!("équipe {} robot {}", n / 2 + 1, n % 2 + 1));
+            ui.add(egui::DragValue::new(&mut robot.x).range(-half_depth..=half_depth));
+            ui.add(egui::DragValue::new(&mut robot.y).range(-half_width..=half_width));
+            ui.add(egui::DragValue::new(&mut robot.orientation).range(-180.0..=180.0));
+            ui.add(egui::DragValue::new(&mut robot.velocity.0).range(-max_speed..=max_speed));
+            ui.add(egui::DragValue::new(&mut robot.velocity.1).range(-max_speed..=max_speed));
+            ui.add(egui::DragValue::new(&mut robot.angular_velocity).range(-720.0..=720.0));
+            ui.end_row();
+        }
+        let ball = &mut scenario.ball;
+        ui.label("balle");
+        ui.add(egui::DragValue::new(&mut ball.x).range(-half_depth..=half_depth));
+        ui.add(egui::DragValue::new(&mut ball.y).range(-half_width..=half_width));
+        ui.label("");
+        ui.add(egui::DragValue::new(&mut ball.velocity.0));
+        ui.add(egui::DragValue::new(&mut ball.velocity.1));
+        ui.end_row();
+    });
+}
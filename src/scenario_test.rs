@@ -0,0 +1,614 @@
+use std::{
+    fmt::{Display, Write as _},
+    path::Path,
+    process::Command,
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bots::{BUILTIN_PREFIX, BuiltinBot},
+    headless::HeadlessError,
+    infos,
+    match_config::MatchSettings,
+    player_action::{PlayerCode, validate_path},
+    scenario::Scenario,
+    simulator::Simulator,
+};
+
+/// Un fichier de tests : des situations de jeu (scénarios) et ce que le code de l'équipe doit y réussir.
+/// L'équipe testée joue les robots 0 et 1 du scénario, son adversaire les robots 2 et 3.
+/// Les chemins sont relatifs au dossier du fichier de tests.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestSuite {
+    /// Nom de la suite dans le rapport, le nom du fichier si absent
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Script de l'équipe testée
+    pub team: String,
+    /// Script de l'adversaire ou équipe fournie, des robots immobiles si absent
+    #[serde(default = "default_opponent")]
+    pub opponent: String,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(rename = "test", default)]
+    pub tests: Vec<TestCase>,
+}
+
+fn default_opponent() -> String {
+    BuiltinBot::Idle.path()
+}
+
+/// Un test : la situation de départ, le temps laissé à l'équipe et ce qui est vérifié.
+/// Le test s'arrête au premier but, quelle que soit l'équipe qui marque.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    pub name: String,
+    pub scenario: ScenarioSource,
+    /// Durée maximale du test, en secondes de jeu, strictement positive
+    pub duration: f32,
+    pub assertions: Vec<Assertion>,
+}
+
+/// `scenario = "fichier.toml"` ou une table `[test.scenario]` au format de `scenario.rs`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ScenarioSource {
+    File(String),
+    Inline(Scenario),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Assertion {
+    /// L'équipe testée marque avant la fin du test
+    GoalScored,
+    /// La balle n'entre jamais dans le but de l'équipe testée
+    NoGoalConceded,
+    /// Les robots de l'équipe testée ne sortent jamais entièrement des lignes du terrain
+    StaysInField,
+}
+
+/// Résultat d'un test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCaseReport {
+    pub name: String,
+    /// Assertions non respectées, vide si le test est réussi
+    pub failures: Vec<String>,
+    /// Le test n'a pas pu être joué (scénario illisible...)
+    pub error: Option<String>,
+    /// Temps de jeu simulé, en secondes
+    pub simulated_time: f32,
+    /// Temps de calcul, en secondes
+    pub wall_time: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestSuiteReport {
+    pub name: String,
+    pub cases: Vec<TestCaseReport>,
+}
+
+#[derive(Debug)]
+pub enum ScenarioTestError {
+    CannotReadFile(String),
+    InvalidFormat(String),
+    NoTest,
+    NoSuchTest(usize),
+    Io(String),
+    /// Des tests ont échoué : (nombre d'échecs, nombre de tests)
+    TestsFailed(usize, usize),
+}
+
+impl Display for ScenarioTestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenarioTestError::CannotReadFile(err_str) => {
+                write!(
+                    f,
+                    "Je n'arrive pas à lire le fichier de tests : {}",
+                    err_str
+                )
+            }
+            ScenarioTestError::InvalidFormat(err_str) => {
+                write!(f, "Le fichier de tests n'est pas valide : {}", err_str)
+            }
+            ScenarioTestError::NoTest => {
+                write!(f, "Le fichier ne contient aucun test (tables [[test]])")
+            }
+            ScenarioTestError::NoSuchTest(n) => {
+                write!(f, "Le fichier ne contient pas de test numéro {}", n)
+            }
+            ScenarioTestError::Io(err_str) => {
+                write!(f, "Impossible d'écrire le rapport de tests : {}", err_str)
+            }
+            ScenarioTestError::TestsFailed(failed, total) => {
+                write!(f, "{} test(s) sur {} en échec", failed, total)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioTestError {}
+
+impl TestSuite {
+    pub fn load(path: &Path) -> Result<TestSuite, ScenarioTestError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| ScenarioTestError::CannotReadFile(format!("{}", err)))?;
+        TestSuite::from_toml(&content)
+    }
+
+    /// Une durée nulle, négative ou NaN ne jouerait aucun tick : les assertions passeraient sans rien vérifier
+    fn from_toml(content: &str) -> Result<TestSuite, ScenarioTestError> {
+        let suite: TestSuite = toml::from_str(content)
+            .map_err(|err| ScenarioTestError::InvalidFormat(format!("{}", err)))?;
+        if let Some(case) = suite
+            .tests
+            .iter()
+            .find(|case| !(case.duration.is_finite() && case.duration > 0.0))
+        {
+            return Err(ScenarioTestError::InvalidFormat(format!(
+                "le test « {} » dure {} s, la durée doit être un nombre de secondes positif",
+                case.name, case.duration
+            )));
+        }
+        Ok(suite)
+    }
+}
+
+/// Chemin écrit dans le fichier de tests -> chemin utilisable depuis le dossier courant
+fn resolve(dir: &Path, path: &str) -> String {
+    if path.starts_with(BUILTIN_PREFIX) {
+        path.to_owned()
+    } else {
+        dir.join(path).to_string_lossy().into_owned()
+    }
+}
+
+fn load_code(script: &str) -> Result<PlayerCode, String> {
+    validate_path(script).map_err(|err| {
+        format!(
+            "{}",
+            HeadlessError::InvalidTeam {
+                script: script.to_owned(),
+                err,
+            }
+        )
+    })
+}
+
+/// Joue tous les tests du fichier `path`, sans interface.
+/// Chaque test est joué dans son propre processus, avec un interpréteur python neuf :
+/// un test ne dépend pas des précédents (`sys.modules`, variables globales des scripts...).
+/// Un test impossible à jouer (scénario ou code illisible) est rapporté comme une erreur.
+pub fn run_suite(path: &Path) -> Result<TestSuiteReport, ScenarioTestError> {
+    let suite = TestSuite::load(path)?;
+    if suite.tests.is_empty() {
+        return Err(ScenarioTestError::NoTest);
+    }
+    let exe = std::env::current_exe().map_err(|err| ScenarioTestError::Io(format!("{}", err)))?;
+
+    let mut cases = Vec::with_capacity(suite.tests.len());
+    for (n, case) in suite.tests.iter().enumerate() {
+        let start = Instant::now();
+        let mut report = run_case_in_process(&exe, path, n).unwrap_or_else(|err| TestCaseReport {
+            name: case.name.clone(),
+            failures: Vec::new(),
+            error: Some(err),
+            simulated_time: 0.0,
+            wall_time: 0.0,
+        });
+        report.wall_time = start.elapsed().as_secs_f64();
+        cases.push(report);
+    }
+
+    let name = suite.name.clone().unwrap_or_else(|| {
+        path.file_stem().map_or_else(
+            || "tests".to_owned(),
+            |stem| stem.to_string_lossy().into_owned(),
+        )
+    });
+    Ok(TestSuiteReport { name, cases })
+}
+
+/// Lance `simulator test <path> --case <n> --case-report <fichier>` et lit le rapport écrit
+fn run_case_in_process(exe: &Path, path: &Path, n: usize) -> Result<TestCaseReport, String> {
+    let report_path =
+        std::env::temp_dir().join(format!("simulator_test_{}_{}.json", std::process::id(), n));
+    let status = Command::new(exe)
+        .arg("test")
+        .arg(path)
+        .arg("--case")
+        .arg(n.to_string())
+        .arg("--case-report")
+        .arg(&report_path)
+        .status()
+        .map_err(|err| format!("Le processus du test n'a pas pu démarrer : {}", err))?;
+    let report = std::fs::read_to_string(&report_path)
+        .map_err(|err| format!("{}", err))
+        .and_then(|content| serde_json::from_str(&content).map_err(|err| format!("{}", err)));
+    let _ = std::fs::remove_file(&report_path);
+    report.map_err(|err| format!("Le processus du test a échoué ({}) : {}", status, err))
+}
+
+/// Joue le test numéro `n` du fichier `path` dans ce processus
+pub fn run_single_case(path: &Path, n: usize) -> Result<TestCaseReport, ScenarioTestError> {
+    let suite = TestSuite::load(path)?;
+    let case = suite.tests.get(n).ok_or(ScenarioTestError::NoSuchTest(n))?;
+    let dir = path.parent().map(Path::to_owned).unwrap_or_default();
+
+    let scenario = match &case.scenario {
+        ScenarioSource::Inline(scenario) => Ok(scenario.clone()),
+        ScenarioSource::File(scenario_path) => {
+            Scenario::load(&dir.join(scenario_path)).map_err(|err| format!("{}", err))
+        }
+    };
+    let teams = scenario.and_then(|scenario| {
+        let team = load_code(&resolve(&dir, &suite.team))?;
+        let opponent = load_code(&resolve(&dir, &suite.opponent))?;
+        Ok((scenario, team, opponent))
+    });
+    Ok(match teams {
+        Ok((scenario, team, opponent)) => {
+            let settings = MatchSettings {
+                seed: suite.seed,
+                scenario: Some(scenario),
+                ..Default::default()
+            };
+            run_case(case, Simulator::from_teams(team, opponent, settings))
+        }
+        Err(err) => TestCaseReport {
+            name: case.name.clone(),
+            failures: Vec::new(),
+            error: Some(err),
+            simulated_time: 0.0,
+            wall_time: 0.0,
+        },
+    })
+}
+
+/// Joue un test jusqu'au premier but ou jusqu'à `case.duration`, en vérifiant les assertions à chaque tick
+fn run_case(case: &TestCase, mut simulation: Simulator) -> TestCaseReport {
+    let mut failures = Vec::new();
+    let mut goal_scored = false;
+    // a robot is out of the field once it has entirely crossed a line
+    let line_x = infos::FIELD_DEPTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+    let line_y = infos::FIELD_WIDTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+    let mut out_of_field = [false; 2];
+    let mut code_error_reported = false;
+
+    simulation.start();
+    while simulation.elapsed_time() < case.duration {
+        let errors = simulation.tick();
+        let time = simulation.elapsed_time();
+
+        // only the first error of the tested code is reported, the others usually follow from it
+        if !code_error_reported
+            && let Some((robot, err)) = errors
+                .iter()
+                .find(|(robot, _)| simulation.robot_index(robot) < 2)
+        {
+            failures.push(format!(
+                "erreur du code de {} à {:.2} s : {}",
+                robot, time, err
+            ));
+            code_error_reported = true;
+        }
+
+        if case.assertions.contains(&Assertion::StaysInField) {
+            for (n, robot) in simulation.robots[..2].iter().enumerate() {
+                let position = simulation.position_of(robot);
+                let radius = simulation.robot_builders[robot].radius;
+                let out = position.x.abs() > line_x + radius || position.y.abs() > line_y + radius;
+                if out && !out_of_field[n] {
+                    failures.push(format!(
+                        "{} est sorti du terrain à {:.2} s ({:.0}, {:.0})",
+                        robot, time, position.x, position.y
+                    ));
+                }
+                out_of_field[n] |= out;
+            }
+        }
+
+        // a goal ends the test : the robots are then put back at the kickoff positions
+        let referee = &simulation.game_referee;
+        if referee.score_team_right > 0 {
+            if case.assertions.contains(&Assertion::NoGoalConceded) {
+                failures.push(format!("but encaissé à {:.2} s", time));
+            }
+            break;
+        }
+        if referee.score_team_left > 0 {
+            goal_scored = true;
+            break;
+        }
+    }
+
+    if case.assertions.contains(&Assertion::GoalScored) && !goal_scored {
+        failures.push(format!("aucun but marqué en {} s", case.duration));
+    }
+
+    TestCaseReport {
+        name: case.name.clone(),
+        failures,
+        error: None,
+        simulated_time: simulation.elapsed_time(),
+        wall_time: 0.0,
+    }
+}
+
+impl TestCaseReport {
+    #[inline]
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty() && self.error.is_none()
+    }
+
+    /// Rapport d'un test joué dans un processus séparé, voir `run_suite`
+    pub fn write(&self, path: &Path) -> Result<(), ScenarioTestError> {
+        let content =
+            serde_json::to_string(self).map_err(|err| ScenarioTestError::Io(format!("{}", err)))?;
+        std::fs::write(path, content).map_err(|err| ScenarioTestError::Io(format!("{}", err)))
+    }
+}
+
+impl TestSuiteReport {
+    pub fn nb_failed(&self) -> usize {
+        self.cases.iter().filter(|case| !case.passed()).count()
+    }
+
+    /// Une ligne par test, puis le bilan
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for case in &self.cases {
+            let status = if case.passed() { "OK   " } else { "ÉCHEC" };
+            writeln!(
+                summary,
+                "{} {} ({:.2} s de jeu)",
+                status, case.name, case.simulated_time
+            )
+            .unwrap();
+            for failure in case.error.iter().chain(&case.failures) {
+                writeln!(summary, "      - {}", failure).unwrap();
+            }
+        }
+        write!(
+            summary,
+            "{} : {} test(s), {} réussi(s), {} en échec",
+            self.name,
+            self.cases.len(),
+            self.cases.len() - self.nb_failed(),
+            self.nb_failed()
+        )
+        .unwrap();
+        summary
+    }
+
+    /// Rapport au format JUnit XML, lu par la plupart des outils d'intégration continue.
+    /// Une assertion non respectée est un `failure`, un test impossible à jouer une `error`.
+    pub fn junit_xml(&self) -> String {
+        let nb_errors = self
+            .cases
+            .iter()
+            .filter(|case| case.error.is_some())
+            .count();
+        let nb_failures = self
+            .cases
+            .iter()
+            .filter(|case| case.error.is_none() && !case.failures.is_empty())
+            .count();
+        let total_time: f64 = self.cases.iter().map(|case| case.wall_time).sum();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+        writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">",
+            xml_escape(&self.name),
+            self.cases.len(),
+            nb_failures,
+            nb_errors,
+            total_time
+        )
+        .unwrap();
+        for case in &self.cases {
+            write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&case.name),
+                xml_escape(&self.name),
+                case.wall_time
+            )
+            .unwrap();
+            if case.passed() {
+                xml += "/>\n";
+                continue;
+            }
+            xml += ">\n";
+            if let Some(err) = &case.error {
+                writeln!(xml, "      <error message=\"{}\"/>", xml_escape(err)).unwrap();
+            } else {
+                writeln!(
+                    xml,
+                    "      <failure message=\"{}\">{}</failure>",
+                    xml_escape(&case.failures[0]),
+                    xml_escape(&case.failures.join("\n"))
+                )
+                .unwrap();
+            }
+            xml += "    </testcase>\n";
+        }
+        xml += "  </testsuite>\n</testsuites>\n";
+        xml
+    }
+
+    pub fn export_junit(&self, path: &Path) -> Result<(), ScenarioTestError> {
+        std::fs::write(path, self.junit_xml())
+            .map_err(|err| ScenarioTestError::Io(format!("{}", err)))
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&apos;",
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::{ScenarioBall, ScenarioRobot};
+
+    fn case_report(name: &str, failures: &[&str], error: Option<&str>) -> TestCaseReport {
+        TestCaseReport {
+            name: name.to_owned(),
+            failures: failures.iter().map(|failure| failure.to_string()).collect(),
+            error: error.map(str::to_owned),
+            simulated_time: 1.0,
+            wall_time: 0.5,
+        }
+    }
+
+    #[test]
+    fn junit_separates_failures_from_errors() {
+        let report = TestSuiteReport {
+            name: "suite".to_owned(),
+            cases: vec![
+                case_report("réussi", &[], None),
+                case_report("raté", &["premier", "second"], None),
+                case_report("injouable", &[], Some("scénario illisible")),
+                case_report("raté aussi", &["seul"], None),
+            ],
+        };
+        let xml = report.junit_xml();
+        assert!(
+            xml.contains(
+                "<testsuite name=\"suite\" tests=\"4\" failures=\"2\" errors=\"1\" time=\"2.000\">"
+            ),
+            "{}",
+            xml
+        );
+        assert!(xml.contains("<testcase name=\"réussi\" classname=\"suite\" time=\"0.500\"/>"));
+        assert!(xml.contains("<failure message=\"premier\">premier\nsecond</failure>"));
+        assert!(xml.contains("<error message=\"scénario illisible\"/>"));
+        assert_eq!(xml.matches("<failure").count(), 2);
+        assert_eq!(xml.matches("<error").count(), 1);
+        assert_eq!(report.nb_failed(), 3);
+    }
+
+    #[test]
+    fn xml_escape_replaces_special_characters() {
+        assert_eq!(
+            xml_escape("a & b < c > d \"e\" 'f'"),
+            "a &amp; b &lt; c &gt; d &quot;e&quot; &apos;f&apos;"
+        );
+        assert_eq!(xml_escape("sans rien à échapper"), "sans rien à échapper");
+
+        let report = TestSuiteReport {
+            name: "<suite>".to_owned(),
+            cases: vec![case_report("tir & but", &["x < 0"], None)],
+        };
+        let xml = report.junit_xml();
+        assert!(xml.contains("name=\"&lt;suite&gt;\""));
+        assert!(xml.contains("name=\"tir &amp; but\""));
+        assert!(xml.contains("<failure message=\"x &lt; 0\">"));
+    }
+
+    #[test]
+    fn durations_that_play_no_tick_are_refused() {
+        for duration in ["0.0", "-2.0", "nan", "inf"] {
+            let content = format!(
+                "team = \"builtin:idle\"\n[[test]]\nname = \"t\"\nscenario = \"s.toml\"\nduration = {}\nassertions = []\n",
+                duration
+            );
+            assert!(
+                matches!(
+                    TestSuite::from_toml(&content),
+                    Err(ScenarioTestError::InvalidFormat(_))
+                ),
+                "durée {}",
+                duration
+            );
+        }
+        let content = "team = \"builtin:idle\"\n[[test]]\nname = \"t\"\nscenario = \"s.toml\"\nduration = 0.5\nassertions = []\n";
+        assert!(TestSuite::from_toml(content).is_ok());
+    }
+
+    /// Deux équipes immobiles ; seuls le scénario et l'élan de la balle décident du résultat
+    fn run_idle(scenario: Scenario, duration: f32) -> TestCaseReport {
+        let case = TestCase {
+            name: "test".to_owned(),
+            scenario: ScenarioSource::Inline(scenario.clone()),
+            duration,
+            assertions: vec![
+                Assertion::GoalScored,
+                Assertion::NoGoalConceded,
+                Assertion::StaysInField,
+            ],
+        };
+        let idle = || load_code(&BuiltinBot::Idle.path()).unwrap();
+        let settings = MatchSettings {
+            scenario: Some(scenario),
+            ..Default::default()
+        };
+        run_case(&case, Simulator::from_teams(idle(), idle(), settings))
+    }
+
+    fn ball_towards(x: f32, velocity: f32) -> Scenario {
+        Scenario {
+            ball: ScenarioBall {
+                x,
+                y: 0.0,
+                velocity: (velocity, 0.0),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn run_case_checks_the_assertions() {
+        // the tested team attacks x < 0 : a ball rolling into that goal is a goal scored, and ends the test
+        let report = run_idle(ball_towards(-90.0, -200.0), 3.0);
+        assert!(report.passed(), "{:?}", report);
+        assert!(report.simulated_time < 3.0);
+
+        // into its own goal : conceded, and nothing scored
+        let report = run_idle(ball_towards(90.0, 200.0), 3.0);
+        assert!(report.error.is_none());
+        assert_eq!(report.failures.len(), 2, "{:?}", report.failures);
+        assert!(report.failures[0].starts_with("but encaissé"));
+        assert!(report.failures[1].starts_with("aucun but marqué"));
+
+        // a robot of the tested team put beyond the wall : out of the field, reported once
+        let mut scenario = Scenario::default();
+        scenario.robots[0] = ScenarioRobot {
+            x: 0.0,
+            y: 120.0,
+            ..scenario.robots[0]
+        };
+        let report = run_idle(scenario, 1.0);
+        let out: Vec<_> = report
+            .failures
+            .iter()
+            .filter(|failure| failure.contains("est sorti du terrain"))
+            .collect();
+        assert_eq!(out.len(), 1, "{:?}", report.failures);
+        assert!(
+            report
+                .failures
+                .iter()
+                .any(|failure| failure.starts_with("aucun but marqué"))
+        );
+        assert!(report.simulated_time >= 1.0);
+    }
+}
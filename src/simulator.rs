@@ -1,17 +1,27 @@
 use crate::{
+    game_event::{EventBus, GameEvent, TimedEvent},
     game_referee::{GameReferee, RefereeAction},
     infos,
-    player_action::{CodeReturnValueError, PlayerAction, PlayerCode, PlayerInformation},
+    match_config::{Kickoff, MatchConfig, MatchRules, MatchSettings, TeamEntry},
+    match_stats::MatchStats,
+    orientation_controller::OrientationController,
+    player_action::{
+        CodeReturnValueError, Movement, PlayerAction, PlayerCode, PlayerInformation,
+        seed_python_random,
+    },
+    rng::SimRng,
     robot::{RobotBuilder, RobotHandler},
+    scenario::Scenario,
 };
 use core::f32;
 use crossbeam::channel::Receiver;
 use nalgebra::{ComplexField, Vector2};
 use rapier2d::prelude::*;
 use rerun::{RecordingStreamBuilder, TextLog};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum FieldWallKind {
     Top,
     Bottom,
@@ -43,13 +53,35 @@ pub struct Simulator {
     pub contact_force_recv: Receiver<ContactForceEvent>,
     // Simulator :
     pub tick_nb: u64,
+    pub rules: MatchRules,
+    /// Match arrêté à la main avant sa fin, voir `stop`
+    pub stopped: bool,
+    pub kickoff: Kickoff,
+    pub scenario: Option<Scenario>,
+    /// Graine du match, gardée pour les métadonnées des enregistrements
+    pub seed: u64,
+    pub rng: SimRng,
     pub game_referee: GameReferee,
+    pub stats: MatchStats,
+    /// Évènements du match, voir `subscribe`
+    pub events: EventBus,
+    /// La balle était hors des lignes au tick précédent
+    pub ball_out_of_bounds: bool,
     pub player_code: HashMap<String, PlayerCode>,
+    /// `TEAM_NAME` des deux codes, sans les suffixes `_1` et `_2` ajoutés quand ils sont identiques
+    pub script_team_names: [String; 2],
     pub last_kick_time: HashMap<RobotHandler, u64>,
+    /// Dernière action valide renvoyée par le code de chaque robot
+    pub last_actions: HashMap<RobotHandler, PlayerAction>,
+    /// Robots pilotés à la main (clavier, manette) : leur code n'est pas appelé, voir `manual_control.rs`
+    pub manual_actions: HashMap<RobotHandler, PlayerAction>,
+    pub orientation_controllers: HashMap<RobotHandler, OrientationController>,
     pub ball_rigid_body_handle: RigidBodyHandle,
     pub ball_collider_handle: ColliderHandle,
     pub robots: [RobotHandler; 4],
     pub robot_to_rigid_body_handle: HashMap<RobotHandler, RigidBodyHandle>,
+    /// Caractéristiques de chaque robot (rayon, kicker, vitesse max...)
+    pub robot_builders: HashMap<RobotHandler, RobotBuilder>,
     pub collider_to_robot_handle: HashMap<ColliderHandle, RobotHandler>,
     pub collider_to_field_wall: HashMap<ColliderHandle, FieldWallKind>,
 }
@@ -111,7 +143,11 @@ impl Simulator {
     pub fn new(
         robots_builders: [RobotBuilder; 4],
         player_code: HashMap<String, PlayerCode>,
+        settings: MatchSettings,
     ) -> Simulator {
+        // python scripts use the same seed so that the whole match is reproducible
+        seed_python_random(settings.seed);
+
         let robot_handlers: [RobotHandler; 4] = [
             robots_builders[0].to_robot_handle(),
             robots_builders[1].to_robot_handle(),
@@ -140,13 +176,34 @@ impl Simulator {
             contact_force_recv,
             // Simulator :
             tick_nb: 0,
+            rules: settings.rules,
+            stopped: false,
+            kickoff: settings.kickoff,
+            scenario: settings.scenario,
+            seed: settings.seed,
+            rng: SimRng::new(settings.seed),
             game_referee: GameReferee::default(),
+            stats: MatchStats::new(robot_handlers.clone().map(|r| r.to_string())),
+            events: EventBus::default(),
+            ball_out_of_bounds: false,
             player_code,
+            script_team_names: [
+                robot_handlers[0].team_name().to_owned(),
+                robot_handlers[2].team_name().to_owned(),
+            ],
+            last_actions: HashMap::new(),
+            manual_actions: HashMap::new(),
             last_kick_time: HashMap::from_iter(robot_handlers.iter().map(|r| (r.clone(), 0u64))),
+            orientation_controllers: HashMap::from_iter(
+                robots_builders
+                    .iter()
+                    .map(|r| (r.to_robot_handle(), r.to_orientation_controller())),
+            ),
             ball_rigid_body_handle: RigidBodyHandle::invalid(),
             ball_collider_handle: ColliderHandle::invalid(),
             robots: robot_handlers,
             robot_to_rigid_body_handle: HashMap::new(),
+            robot_builders: HashMap::new(),
             collider_to_robot_handle: HashMap::new(),
             collider_to_field_wall: HashMap::new(),
         };
@@ -163,6 +220,8 @@ impl Simulator {
             let collider_handle = sim.create_collider(&robot_builder, rigid_body_handle);
             sim.collider_to_robot_handle
                 .insert(collider_handle, robot_builder.to_robot_handle());
+            sim.robot_builders
+                .insert(robot_builder.to_robot_handle(), robot_builder);
         }
 
         sim.build_field_colliders();
@@ -171,6 +230,166 @@ impl Simulator {
     }
 }
 
+impl Simulator {
+    /// Construit un match entre deux équipes, en s'assurant que leurs noms sont différents
+    pub fn from_teams(
+        mut team1: PlayerCode,
+        mut team2: PlayerCode,
+        settings: MatchSettings,
+    ) -> Simulator {
+        // ensure unique team name
+        let mut name1 = team1.name().to_owned();
+        let mut name2 = team2.name().to_owned();
+        let script_team_names = [name1.clone(), name2.clone()];
+        if name1 == name2 {
+            name1 += "_1";
+            name2 += "_2";
+            team1._set_name(&name1);
+            team2._set_name(&name2);
+        }
+
+        let profile1 = team1.robot_profile().clone();
+        let profile2 = team2.robot_profile().clone();
+
+        let mut teams = HashMap::with_capacity(2);
+        teams.insert(name1.clone(), team1);
+        teams.insert(name2.clone(), team2);
+
+        // CRUCIAL : robots from the same team _must_ be at (0 and 1) or (2 and 3)
+        let mut sim = Simulator::new(
+            [
+                RobotBuilder::from_profile(&name1, 1, &profile1),
+                RobotBuilder::from_profile(&name1, 2, &profile1),
+                RobotBuilder::from_profile(&name2, 1, &profile2),
+                RobotBuilder::from_profile(&name2, 2, &profile2),
+            ],
+            teams,
+            settings,
+        );
+        sim.script_team_names = script_team_names;
+        sim
+    }
+
+    /// Coup d'envoi : la balle part vers la première équipe, sauf si le match commence par un scénario
+    pub fn start(&mut self) {
+        match self.scenario.clone() {
+            Some(scenario) => self.apply_scenario(&scenario),
+            None => self.rigid_body_set[self.ball_rigid_body_handle]
+                .apply_impulse(vector![-100.0, 0.0], true),
+        }
+    }
+
+    /// Place les robots et la balle et leur donne leur vitesse de départ
+    pub fn apply_scenario(&mut self, scenario: &Scenario) {
+        for (robot_handle, robot) in self.robots.clone().iter().zip(scenario.robots) {
+            let rigid_body =
+                &mut self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]];
+            Simulator::reset_rigid_body(
+                rigid_body,
+                // the rerun orientation is different from the code orientation
+                robot.orientation.to_radians() + f32::consts::FRAC_PI_2,
+                Vector2::new(robot.x, robot.y),
+            );
+            rigid_body.set_linvel(Vector2::new(robot.velocity.0, robot.velocity.1), true);
+            rigid_body.set_angvel(robot.angular_velocity.to_radians(), true);
+        }
+
+        let ball = &mut self.rigid_body_set[self.ball_rigid_body_handle];
+        Simulator::reset_rigid_body(ball, 0.0, Vector2::new(scenario.ball.x, scenario.ball.y));
+        ball.set_linvel(
+            Vector2::new(scenario.ball.velocity.0, scenario.ball.velocity.1),
+            true,
+        );
+        // robots were moved by hand : distances and possession restart from here
+        self.stats.on_new_round();
+    }
+
+    /// Déplace un robot (index dans `robots`) à la main, sans le tourner, et lui donne `velocity` (cm/s)
+    pub fn move_robot_by_hand(
+        &mut self,
+        robot: usize,
+        position: Vector2<f32>,
+        velocity: Vector2<f32>,
+    ) {
+        let robot_handle = &self.robots[robot];
+        let max_speed = self.robot_builders[robot_handle].max_speed;
+        let rigid_body = &mut self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]];
+        let angle = rigid_body.rotation().angle();
+        Simulator::reset_rigid_body(rigid_body, angle, position);
+        rigid_body.set_linvel(velocity.cap_magnitude(max_speed), true);
+        self.stats.on_robot_moved(robot);
+    }
+
+    /// Déplace la balle à la main et lui donne `velocity` (cm/s)
+    pub fn move_ball_by_hand(&mut self, position: Vector2<f32>, velocity: Vector2<f32>) {
+        let ball = &mut self.rigid_body_set[self.ball_rigid_body_handle];
+        Simulator::reset_rigid_body(ball, 0.0, position);
+        ball.set_linvel(velocity.cap_magnitude(infos::MAX_THROW_SPEED), true);
+        self.stats.on_ball_moved();
+    }
+
+    /// Noms des deux équipes, dans l'ordre de `robots`
+    #[inline]
+    pub fn team_names(&self) -> [&str; 2] {
+        [self.robots[0].team_name(), self.robots[2].team_name()]
+    }
+
+    /// Le kicker est à l'avant du robot, là où se trouve le dribbler
+    pub fn kicker_position(&self, robot_handle: &RobotHandler) -> Vector2<f32> {
+        let front_angle = front_angle(self.rotation_of(robot_handle).angle());
+        let radius = self.robot_builders[robot_handle].radius;
+        self.position_of(robot_handle) + radius * Vector2::new(front_angle.cos(), front_angle.sin())
+    }
+
+    /// La balle est à portée du kicker du robot
+    pub fn has_ball(&self, robot_handle: &RobotHandler) -> bool {
+        self.kicker_position(robot_handle)
+            .metric_distance(&self.position_of_ball())
+            <= infos::DISTANCE_MIN_KICKER_BALL
+    }
+
+    /// Index du robot dans `robots`
+    #[inline]
+    pub fn robot_index(&self, robot_handle: &RobotHandler) -> usize {
+        self.robots.iter().position(|r| r == robot_handle).unwrap()
+    }
+
+    /// Chemins du code des deux équipes, dans l'ordre de `robots`
+    pub fn team_scripts(&self) -> [&str; 2] {
+        let [name1, name2] = self.team_names();
+        [
+            self.player_code[name1].path(),
+            self.player_code[name2].path(),
+        ]
+    }
+
+    /// Configuration qui permet de rejouer ce match, les équipes dans l'ordre de `robots`
+    pub fn match_config(&self, team_colors: [[u8; 3]; 2]) -> MatchConfig {
+        let teams = self
+            .team_names()
+            .iter()
+            .zip(team_colors)
+            .map(|(team_name, color)| {
+                let code = &self.player_code[*team_name];
+                TeamEntry {
+                    script: code.path().to_owned(),
+                    robot_profile: code.robot_profile_path().map(str::to_owned),
+                    color: Some(color),
+                }
+            })
+            .collect();
+        MatchConfig {
+            teams,
+            settings: MatchSettings {
+                seed: self.seed,
+                rules: self.rules.clone(),
+                kickoff: self.kickoff.clone(),
+                scenario: self.scenario.clone(),
+            },
+        }
+    }
+}
+
 impl Simulator {
     fn create_rigid_body(&mut self, robot_builder: &RobotBuilder) -> RigidBodyHandle {
         let body = RigidBodyBuilder::dynamic()
@@ -257,22 +476,38 @@ impl Simulator {
                 }],
             );
             let ball_pos = self.position_of_ball();
-            let action = code.tick(PlayerInformation {
+            // the orientation is only controlled in position mode, and the error is stale after a code error
+            let orientation_error = match self.last_actions.get(robot_handle) {
+                Some(PlayerAction {
+                    movement: Movement::Target { .. },
+                    ..
+                }) => self.orientation_controllers[robot_handle].last_error(),
+                _ => 0.0,
+            };
+            let player_info = PlayerInformation {
                 switch_coordinates: n >= 2,
+                robot_number: robot_handle.robot_number(),
                 my_position: (my_pos.x, my_pos.y),
                 my_orientation,
                 friend_position: (friend_pos.x, friend_pos.y),
                 enemy1_position: (enemy1_pos.x, enemy1_pos.y),
                 enemy2_position: (enemy2_pos.x, enemy2_pos.y),
                 ball_position: (ball_pos.x, ball_pos.y),
-            });
+                orientation_error,
+            };
+            // a human driven robot goes through the same path as the code of the team
+            let action = match self.manual_actions.get(robot_handle) {
+                Some(manual_action) => Ok(manual_action.clone()),
+                None => code.tick(player_info),
+            };
             match action {
                 Err(err) => {
+                    self.last_actions.remove(robot_handle);
                     errors.insert(robot_handle.clone(), err);
                 }
                 Ok(action) => {
-                    // do things
-
+                    self.last_actions
+                        .insert(robot_handle.clone(), action.clone());
                     self.apply_player_forces(&robot_handle, action);
                 }
             }
@@ -301,6 +536,14 @@ impl Simulator {
                 referee_actions.push(referee_action);
             }
         }
+        let robot_positions = self.robots.clone().map(|r| self.position_of(&r));
+        let ball_position = self.position_of_ball();
+        self.stats.on_tick(
+            self.integration_parameters.dt,
+            ball_position,
+            robot_positions,
+        );
+        self.check_out_of_bounds(ball_position);
         if referee_actions.contains(&RefereeAction::NewRound) {
             self.new_round();
         }
@@ -336,112 +579,172 @@ impl Simulator {
 
     #[inline]
     fn apply_player_forces(&mut self, robot_handle: &RobotHandler, action: PlayerAction) {
-        // Position :
-        let my_pos = self.position_of(robot_handle);
         let robot_angle = self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]]
             .rotation()
             .angle();
-        let angvel = self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]].angvel();
-        let dx = action.target_position.0 - my_pos.x;
-        let dy = action.target_position.1 - my_pos.y;
-        let angle = dy.atan2(dx);
-        // Bravo, vous avez trouvé la source de la non-linéarité l'accélération, vous pouvez donc la rectifier
-        let difficult_power = ease_in_out_quad(action.power as f32 / 255.0) * infos::POWER_SPEED;
-        self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]].apply_impulse(
-            vector![difficult_power * angle.cos(), difficult_power * angle.sin()],
-            true,
-        );
-
-        debug_assert!(-f32::consts::PI <= robot_angle && robot_angle <= f32::consts::PI);
-        debug_assert!(
-            -f32::consts::PI <= action.target_orientation
-                && action.target_orientation <= f32::consts::PI
-        );
-
-        // TODO : improve rotation
-
-        let angle_dist = ((robot_angle - action.target_orientation + f32::consts::PI)
-            .rem_euclid(2.0 * f32::consts::PI))
-            - f32::consts::PI;
-        dbg!(angle_dist);
 
-        let rotation_sign = if angle_dist > 0.0 {
-            1.0
-        } else if angle_dist < 0.0 {
-            -1.0
-        } else {
-            0.0
-        };
-        dbg!(rotation_sign);
-        // We should start to decrease speed when we are close
+        match action.movement {
+            Movement::Target {
+                target_position,
+                power,
+                target_orientation,
+            } => {
+                self.apply_target_movement(robot_handle, target_position, power, target_orientation)
+            }
+            Movement::Velocity {
+                velocity,
+                angular_velocity,
+            } => self.apply_velocity_movement(robot_handle, velocity, angular_velocity),
+            Movement::MotorPowers(powers) => self.apply_motor_powers(robot_handle, powers),
+        }
 
-        // Rotation :
+        let robot_builder = &self.robot_builders[robot_handle];
+        let (kick_power, kick_cooldown, speed_limit) = (
+            robot_builder.kick_power,
+            robot_builder.kick_cooldown,
+            robot_builder.speed_limit,
+        );
 
-        // // Equation du carré de la vitesse
-        // let speed_at_target =
-        //     angvel.powi(2) + 2.0 * (-1.0) * rotation_sign * infos::ROTATION_SPEED * angle_dist;
-        // let correction_sign = if speed_at_target >= 0.0 { -1.0 } else { 1.0 };
-
-        // if robot_handle.team_name().ends_with("1") && robot_handle.robot_number() == 1 {
-        //     let rec = RecordingStreamBuilder::new("simulator")
-        //         .recording_id("simulator")
-        //         .spawn()
-        //         .unwrap();
-        //     rec.log(
-        //         "/log",
-        //         &TextLog::new(format!(
-        //             "{} rotation {}, target {}, so {}, speed_at_taeget {}, so {}",
-        //             robot_handle,
-        //             robot_angle,
-        //             action.target_orientation,
-        //             rotation_sign,
-        //             speed_at_target,
-        //             correction_sign
-        //         )),
-        //     )
-        //     .unwrap();
-        // }
-
-        if rotation_sign == 0.0 {
-            // decrease speed
-            self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]]
-                .set_angvel(angvel * infos::ROTATION_AUTO_DECREASE_RATIO, false);
-        } else {
-            self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]].set_angvel(
-                (angvel + rotation_sign * infos::ROTATION_SPEED)
-                    .clamp(-infos::ROTATION_MAX_SPEED, infos::ROTATION_MAX_SPEED),
-                true,
-            );
+        // Max speed, only for robots whose profile sets one :
+        if let Some(speed_limit) = speed_limit {
+            let rigid_body =
+                &mut self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]];
+            if rigid_body.linvel().norm() > speed_limit {
+                let capped = rigid_body.linvel().cap_magnitude(speed_limit);
+                rigid_body.set_linvel(capped, true);
+            }
         }
 
-        // ROTATION_MAX_SPEED
-
         // Kicker :
+        if action.kick <= 0.0 {
+            return;
+        }
         let last_kick = self.last_kick_time[robot_handle];
         // if last_kick is 0 then it means that the robot never kicked
-        if last_kick == 0 || self.tick_nb >= last_kick + infos::NB_MIN_TICK_BETWEEN_KICKS {
+        if last_kick == 0 || self.tick_nb >= last_kick + kick_cooldown {
             // do a kick
             // the energy is consumed even if the kick is not applied
             self.last_kick_time
                 .entry(robot_handle.clone())
                 .and_modify(|e| *e = self.tick_nb);
-            let robot_angle_unit_vector = Vector2::new(robot_angle.cos(), robot_angle.sin());
-            let kicker_position = my_pos + infos::ROBOT_RADIUS * robot_angle_unit_vector;
-            if kicker_position
-                .metric_distance(&self.position_of_ball())
-                .abs()
-                <= infos::DISTANCE_MIN_KICKER_BALL
-            {
-                self.rigid_body_set[self.ball_rigid_body_handle].apply_impulse_at_point(
-                    infos::KICK_POWER * robot_angle_unit_vector,
+            let kicker_position = self.kicker_position(robot_handle);
+            let ball_position = self.position_of_ball();
+            let mut ball_velocity = None;
+            if self.has_ball(robot_handle) {
+                let kick_angle = front_angle(robot_angle) + action.kick_angle;
+                let ball = &mut self.rigid_body_set[self.ball_rigid_body_handle];
+                ball.apply_impulse_at_point(
+                    action.kick * kick_power * Vector2::new(kick_angle.cos(), kick_angle.sin()),
                     kicker_position.into(),
                     true,
                 );
+                ball_velocity = Some(*ball.linvel());
             }
+            let robot = self.robot_index(robot_handle);
+            self.stats.on_kick(robot, ball_position, ball_velocity);
+            self.emit(GameEvent::Kick {
+                robot: robot_handle.to_string(),
+                power: action.kick,
+                effective: ball_velocity.is_some(),
+                position: (kicker_position.x, kicker_position.y),
+            });
+        }
+    }
+
+    /// Mode `position` : le robot se dirige vers une cible avec une puissance donnée
+    fn apply_target_movement(
+        &mut self,
+        robot_handle: &RobotHandler,
+        target_position: (f32, f32),
+        power: u8,
+        target_orientation: f32,
+    ) {
+        // Position :
+        let my_pos = self.position_of(robot_handle);
+        let robot_angle = self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]]
+            .rotation()
+            .angle();
+        let angvel = self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]].angvel();
+        let dx = target_position.0 - my_pos.x;
+        let dy = target_position.1 - my_pos.y;
+        let angle = dy.atan2(dx);
+        // Bravo, vous avez trouvé la source de la non-linéarité l'accélération, vous pouvez donc la rectifier
+        let difficult_power = ease_in_out_quad(power as f32 / 255.0) * infos::POWER_SPEED;
+        self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]].apply_impulse(
+            vector![difficult_power * angle.cos(), difficult_power * angle.sin()],
+            true,
+        );
+
+        debug_assert!(-f32::consts::PI <= robot_angle && robot_angle <= f32::consts::PI);
+
+        // Rotation :
+        let dt = self.integration_parameters.dt;
+        let new_angvel = self
+            .orientation_controllers
+            .get_mut(robot_handle)
+            .unwrap()
+            .update(robot_angle, target_orientation, angvel, dt);
+        self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]]
+            .set_angvel(new_angvel, true);
+    }
+
+    /// Modes `velocity_robot` et `velocity_field` : la vitesse est déjà exprimée dans le repère global.
+    /// Les moteurs ne peuvent fournir que `POWER_SPEED` par tick, la vitesse demandée est donc atteinte progressivement.
+    fn apply_velocity_movement(
+        &mut self,
+        robot_handle: &RobotHandler,
+        velocity: (f32, f32),
+        angular_velocity: f32,
+    ) {
+        let dt = self.integration_parameters.dt;
+        let controller = self.orientation_controllers.get_mut(robot_handle).unwrap();
+        // The orientation is not controlled in this mode
+        controller.reset();
+        let rigid_body = &mut self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]];
+
+        let max_speed = self.robot_builders[robot_handle].max_speed;
+        let target_velocity = Vector2::new(velocity.0, velocity.1).cap_magnitude(max_speed);
+        let impulse = ((target_velocity - rigid_body.linvel()) * rigid_body.mass())
+            .cap_magnitude(infos::POWER_SPEED);
+        rigid_body.apply_impulse(impulse, true);
+
+        let angvel = rigid_body.angvel();
+        rigid_body.set_angvel(controller.limit_angvel(angvel, angular_velocity, dt), true);
+    }
+
+    /// Mode `motors` : chaque roue omnidirectionnelle pousse perpendiculairement à son axe
+    fn apply_motor_powers(&mut self, robot_handle: &RobotHandler, powers: [i16; infos::NB_MOTORS]) {
+        let controller = self.orientation_controllers.get_mut(robot_handle).unwrap();
+        // The orientation is not controlled in this mode
+        controller.reset();
+        let max_angular_velocity = controller.max_angular_velocity;
+        let rigid_body = &mut self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]];
+        let front_angle = front_angle(rigid_body.rotation().angle());
+
+        let mut impulse = Vector2::zeros();
+        let mut angvel_change = 0.0;
+        for (power, motor_angle) in powers.iter().zip(infos::MOTOR_ANGLES) {
+            let power = *power as f32 / 255.0;
+            let push_angle = front_angle + motor_angle + f32::consts::FRAC_PI_2;
+            impulse += power * Vector2::new(push_angle.cos(), push_angle.sin());
+            angvel_change += power * infos::MOTOR_ROTATION_SPEED;
         }
+        // With 4 wheels at 45°, two pairs push in the same direction
+        rigid_body.apply_impulse(impulse * infos::POWER_SPEED / 2.0, true);
+        rigid_body.set_angvel(
+            (rigid_body.angvel() + angvel_change)
+                .clamp(-max_angular_velocity, max_angular_velocity),
+            true,
+        );
     }
 }
 
+/// The front of the robot (where the dribbler is) is rotated by -pi/2 from the rapier angle
+#[inline]
+pub fn front_angle(robot_angle: f32) -> f32 {
+    robot_angle - f32::consts::FRAC_PI_2
+}
+
 #[inline]
 fn ease_in_out_quad(x: f32) -> f32 {
     if x < 0.5 {
@@ -466,32 +769,36 @@ impl Simulator {
             .get(&collision_event.collider2());
 
         // Cas entre deux robots
-        if let Some(robot1) = try_robot_for_1 {
-            if let Some(robot2) = try_robot_for_2 {
-                // println!("{} touched {}", robot1, robot2);
-                return None;
-            }
+        if let (Some(robot1), Some(robot2)) = (try_robot_for_1, try_robot_for_2) {
+            let (robot1, robot2) = (robot1.clone(), robot2.clone());
+            self.on_robot_collision(&robot1, &robot2);
+            return None;
         }
 
         let try_wall_for_1 = self
             .collider_to_field_wall
-            .get(&collision_event.collider1());
+            .get(&collision_event.collider1())
+            .copied();
         let try_wall_for_2 = self
             .collider_to_field_wall
-            .get(&collision_event.collider2());
+            .get(&collision_event.collider2())
+            .copied();
 
         // Cas entre un robot et un mur
-        if let Some(robot1) = try_robot_for_1 {
-            if let Some(wall2) = try_wall_for_2 {
-                // println!("{} touched {:?}", robot1, wall2);
-                return None;
-            }
-        }
-        if let Some(robot2) = try_robot_for_2 {
-            if let Some(wall1) = try_wall_for_1 {
-                // println!("{} touched {:?}", robot2, wall1);
-                return None;
-            }
+        if let (Some(robot), Some(wall), ..) | (.., Some(robot), Some(wall)) = (
+            try_robot_for_1,
+            try_wall_for_2,
+            try_robot_for_2,
+            try_wall_for_1,
+        ) {
+            let robot = robot.clone();
+            let position = self.position_of(&robot);
+            self.emit(GameEvent::WallHit {
+                robot: Some(robot.to_string()),
+                wall,
+                position: (position.x, position.y),
+            });
+            return None;
         }
 
         let try_ball_for_1 = collision_event.collider1() == self.ball_collider_handle;
@@ -500,34 +807,22 @@ impl Simulator {
         // Cas ball/robot et ball/mur
         if try_ball_for_1 {
             if let Some(robot2) = try_robot_for_2 {
-                // println!("ball touched {}", robot2);
+                let robot2 = robot2.clone();
+                self.on_ball_touch(&robot2);
                 return None;
             }
             if let Some(wall2) = try_wall_for_2 {
-                // println!("ball touched {:?}", wall2);
-                if *wall2 == FieldWallKind::Left || *wall2 == FieldWallKind::Right {
-                    return Some(
-                        self.game_referee
-                            .maybe_goal(&self.position_of_ball(), wall2),
-                    );
-                }
-                return None;
+                return self.on_ball_wall_hit(wall2);
             }
         }
         if try_ball_for_2 {
             if let Some(robot1) = try_robot_for_1 {
-                // println!("ball touched {}", robot1);
+                let robot1 = robot1.clone();
+                self.on_ball_touch(&robot1);
                 return None;
             }
             if let Some(wall1) = try_wall_for_1 {
-                // println!("ball touched {:?}", wall1);
-                if *wall1 == FieldWallKind::Left || *wall1 == FieldWallKind::Right {
-                    return Some(
-                        self.game_referee
-                            .maybe_goal(&self.position_of_ball(), wall1),
-                    );
-                }
-                return None;
+                return self.on_ball_wall_hit(wall1);
             }
         }
 
@@ -543,39 +838,139 @@ impl Simulator {
         return None;
     }
 
-    pub fn new_round(&mut self) {
-        Simulator::reset_rigid_body(
-            &mut self.rigid_body_set[self.robot_to_rigid_body_handle[&self.robots[0]]],
-            3.0 * f32::consts::FRAC_PI_2,
-            Vector2::new(infos::START_POS_ALIGNED_X, -infos::START_POS_ALIGNED_Y),
-        );
+    fn on_robot_collision(&mut self, robot1: &RobotHandler, robot2: &RobotHandler) {
+        let (index1, index2) = (self.robot_index(robot1), self.robot_index(robot2));
+        self.stats.on_robot_collision(index1, index2);
+        let position = (self.position_of(robot1) + self.position_of(robot2)) / 2.0;
+        self.emit(GameEvent::RobotCollision {
+            robot1: robot1.to_string(),
+            robot2: robot2.to_string(),
+            position: (position.x, position.y),
+        });
+    }
 
-        Simulator::reset_rigid_body(
-            &mut self.rigid_body_set[self.robot_to_rigid_body_handle[&self.robots[1]]],
-            3.0 * f32::consts::FRAC_PI_2,
-            Vector2::new(infos::START_POS_ALIGNED_X, infos::START_POS_ALIGNED_Y),
-        );
+    fn on_ball_touch(&mut self, robot: &RobotHandler) {
+        let index = self.robot_index(robot);
+        self.stats.on_touch(index);
+        let position = self.position_of_ball();
+        self.emit(GameEvent::Touch {
+            robot: robot.to_string(),
+            position: (position.x, position.y),
+        });
+    }
 
-        Simulator::reset_rigid_body(
-            &mut self.rigid_body_set[self.robot_to_rigid_body_handle[&self.robots[2]]],
-            f32::consts::FRAC_PI_2,
-            Vector2::new(-infos::START_POS_ALIGNED_X, -infos::START_POS_ALIGNED_Y),
-        );
+    fn on_ball_wall_hit(&mut self, wall: FieldWallKind) -> Option<RefereeAction> {
+        let position = self.position_of_ball();
+        self.emit(GameEvent::WallHit {
+            robot: None,
+            wall,
+            position: (position.x, position.y),
+        });
+        if wall != FieldWallKind::Left && wall != FieldWallKind::Right {
+            return None;
+        }
+        let referee_action = self.game_referee.maybe_goal(&position, &wall);
+        if referee_action == RefereeAction::NewRound {
+            // the team attacking the left wall is the first one
+            let scoring_team = if wall == FieldWallKind::Left { 0 } else { 2 };
+            let team = self.robots[scoring_team].team_name().to_owned();
+            let score = [
+                self.game_referee.score_team_left,
+                self.game_referee.score_team_right,
+            ];
+            self.emit(GameEvent::Goal {
+                team,
+                score,
+                position: (position.x, position.y),
+            });
+        }
+        Some(referee_action)
+    }
 
-        Simulator::reset_rigid_body(
-            &mut self.rigid_body_set[self.robot_to_rigid_body_handle[&self.robots[3]]],
-            f32::consts::FRAC_PI_2,
-            Vector2::new(-infos::START_POS_ALIGNED_X, infos::START_POS_ALIGNED_Y),
-        );
+    /// Signale la balle quand elle franchit les lignes, sauf dans la largeur des buts
+    fn check_out_of_bounds(&mut self, ball_position: Vector2<f32>) {
+        let half_depth = infos::FIELD_DEPTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+        let half_width = infos::FIELD_WIDTH / 2.0 - infos::SPACE_BEFORE_LINE_SIDE;
+        let out_on_side = ball_position.y.abs() > half_width;
+        let out_on_goal_line =
+            ball_position.x.abs() > half_depth && ball_position.y.abs() > infos::GOAL_WIDTH / 2.0;
+        let out_of_bounds = out_on_side || out_on_goal_line;
+        if out_of_bounds && !self.ball_out_of_bounds {
+            self.emit(GameEvent::OutOfBounds {
+                position: (ball_position.x, ball_position.y),
+            });
+        }
+        self.ball_out_of_bounds = out_of_bounds;
+    }
+
+    /// S'abonner aux évènements du match. Chaque abonné reçoit tous les évènements à partir de maintenant
+    pub fn subscribe(&mut self) -> crossbeam::channel::Receiver<TimedEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        let timed_event = TimedEvent {
+            tick: self.tick_nb,
+            time: self.elapsed_time(),
+            event,
+        };
+        self.events.publish(timed_event);
+    }
+
+    pub fn new_round(&mut self) {
+        for controller in self.orientation_controllers.values_mut() {
+            controller.reset();
+        }
+        self.stats.on_new_round();
+        self.ball_out_of_bounds = false;
+        self.emit(GameEvent::KickOff);
+
+        let jitter = self.rules.kickoff_jitter;
+        for (robot_handle, start) in self.robots.clone().iter().zip(self.kickoff.robots) {
+            let translation = Vector2::new(
+                start.x + self.rng.range(-jitter, jitter),
+                start.y + self.rng.range(-jitter, jitter),
+            );
+            Simulator::reset_rigid_body(
+                &mut self.rigid_body_set[self.robot_to_rigid_body_handle[robot_handle]],
+                // the rerun orientation is different from the code orientation
+                start.orientation.to_radians() + f32::consts::FRAC_PI_2,
+                translation,
+            );
+        }
 
         // ball
         Simulator::reset_rigid_body(
             &mut self.rigid_body_set[self.ball_rigid_body_handle],
             0.0,
-            vector![0.0, 0.0],
+            Vector2::new(self.kickoff.ball.0, self.kickoff.ball.1),
         );
     }
 
+    /// Temps de jeu écoulé, en secondes
+    #[inline]
+    pub fn elapsed_time(&self) -> f32 {
+        self.tick_nb as f32 * self.integration_parameters.dt
+    }
+
+    pub fn is_finished(&self) -> bool {
+        let time_is_up = self
+            .rules
+            .duration
+            .is_some_and(|duration| self.elapsed_time() >= duration as f32);
+        let score_reached = self.rules.max_score.is_some_and(|max_score| {
+            self.game_referee.score_team_left >= max_score
+                || self.game_referee.score_team_right >= max_score
+        });
+        self.stopped || time_is_up || score_reached
+    }
+
+    /// Termine le match avec le score actuel, pour un match sans durée ni score maximal
+    #[inline]
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
     #[inline]
     fn reset_rigid_body(rigid_body: &mut RigidBody, angle: f32, translation: Vector2<f32>) {
         (*rigid_body).set_linvel(Vector2::new(0.0, 0.0), true);
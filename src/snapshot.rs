@@ -0,0 +1,279 @@
+use std::fmt::Display;
+
+use rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_referee::GameReferee,
+    match_stats::{MatchStats, StatsTracking},
+    orientation_controller::OrientationController,
+    rng::SimRng,
+    robot_profile::RobotProfile,
+    simulator::Simulator,
+};
+
+/// État complet d'un match à la fin d'un tick, pour le reprendre plus tard à l'identique.
+/// L'état interne des scripts Python n'en fait pas partie : ils repartent de zéro à la reprise,
+/// ce qui permet justement de reprendre avec un script modifié.
+/// Les robots sont dans l'ordre de `Simulator::robots`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SimulatorSnapshot {
+    pub tick_nb: u64,
+    // World (rapier) :
+    pub rigid_body_set: RigidBodySet,
+    pub collider_set: ColliderSet,
+    pub integration_parameters: IntegrationParameters,
+    pub island_manager: IslandManager,
+    pub broad_phase: DefaultBroadPhase,
+    pub narrow_phase: NarrowPhase,
+    pub impulse_joint_set: ImpulseJointSet,
+    pub multibody_joint_set: MultibodyJointSet,
+    pub ccd_solver: CCDSolver,
+    pub query_pipeline: QueryPipeline,
+    // Simulator :
+    pub rng: SimRng,
+    pub game_referee: GameReferee,
+    pub stats: MatchStats,
+    pub stats_tracking: StatsTracking,
+    pub ball_out_of_bounds: bool,
+    pub last_kick_time: [u64; 4],
+    pub orientation_controllers: [OrientationController; 4],
+    /// Profil des robots de chaque équipe : les corps rapier en dépendent
+    pub robot_profiles: [RobotProfile; 2],
+}
+
+#[derive(Debug)]
+pub enum RestoreError {
+    /// Le profil d'une équipe n'est plus celui du match enregistré
+    ProfileChanged {
+        team_name: String,
+        /// Champs du profil qui diffèrent
+        fields: Vec<String>,
+    },
+}
+
+impl Display for RestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RestoreError::ProfileChanged { team_name, fields } => write!(
+                f,
+                "Le profil des robots de {} a changé depuis l'enregistrement ({}) : \
+                 l'instantané ne peut être repris qu'avec le profil du match enregistré",
+                team_name,
+                fields.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RestoreError {}
+
+impl Simulator {
+    pub fn snapshot(&self) -> SimulatorSnapshot {
+        SimulatorSnapshot {
+            tick_nb: self.tick_nb,
+            rigid_body_set: self.rigid_body_set.clone(),
+            collider_set: self.collider_set.clone(),
+            integration_parameters: self.integration_parameters,
+            island_manager: self.island_manager.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            impulse_joint_set: self.impulse_joint_set.clone(),
+            multibody_joint_set: self.multibody_joint_set.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            query_pipeline: self.query_pipeline.clone(),
+            rng: self.rng,
+            game_referee: self.game_referee.clone(),
+            stats: self.stats.clone(),
+            stats_tracking: self.stats.tracking().clone(),
+            ball_out_of_bounds: self.ball_out_of_bounds,
+            last_kick_time: self.robots.clone().map(|robot| self.last_kick_time[&robot]),
+            orientation_controllers: self
+                .robots
+                .clone()
+                .map(|robot| self.orientation_controllers[&robot].clone()),
+            robot_profiles: self.robot_profiles(),
+        }
+    }
+
+    fn robot_profiles(&self) -> [RobotProfile; 2] {
+        self.team_names()
+            .map(|team_name| self.player_code[team_name].robot_profile().clone())
+    }
+
+    /// Remet le match dans l'état de `snapshot`.
+    /// Le simulateur doit avoir été construit comme celui de l'instantané (mêmes équipes dans le même ordre,
+    /// mêmes profils de robots) pour que les handles rapier désignent les mêmes objets.
+    /// Les scripts peuvent en revanche avoir changé, pas les profils : le match n'est pas restauré s'ils diffèrent.
+    pub fn restore(&mut self, snapshot: SimulatorSnapshot) -> Result<(), RestoreError> {
+        let team_names = self.team_names().map(str::to_owned);
+        for ((team_name, recorded), current) in team_names
+            .into_iter()
+            .zip(&snapshot.robot_profiles)
+            .zip(self.robot_profiles())
+        {
+            if *recorded != current {
+                return Err(RestoreError::ProfileChanged {
+                    team_name,
+                    fields: changed_fields(recorded, &current),
+                });
+            }
+        }
+
+        self.tick_nb = snapshot.tick_nb;
+        self.rigid_body_set = snapshot.rigid_body_set;
+        self.collider_set = snapshot.collider_set;
+        self.integration_parameters = snapshot.integration_parameters;
+        self.island_manager = snapshot.island_manager;
+        self.broad_phase = snapshot.broad_phase;
+        self.narrow_phase = snapshot.narrow_phase;
+        self.impulse_joint_set = snapshot.impulse_joint_set;
+        self.multibody_joint_set = snapshot.multibody_joint_set;
+        self.ccd_solver = snapshot.ccd_solver;
+        self.query_pipeline = snapshot.query_pipeline;
+        self.rng = snapshot.rng;
+        self.game_referee = snapshot.game_referee;
+
+        // robot names come from the new scripts, the figures from the snapshot
+        let mut stats = snapshot.stats;
+        for (robot_stats, robot) in stats.robots.iter_mut().zip(&self.robots) {
+            robot_stats.name = robot.to_string();
+        }
+        stats.set_tracking(snapshot.stats_tracking);
+        self.stats = stats;
+
+        self.ball_out_of_bounds = snapshot.ball_out_of_bounds;
+        for ((robot, last_kick), controller) in self
+            .robots
+            .iter()
+            .zip(snapshot.last_kick_time)
+            .zip(snapshot.orientation_controllers)
+        {
+            self.last_kick_time.insert(robot.clone(), last_kick);
+            self.orientation_controllers
+                .insert(robot.clone(), controller);
+        }
+        self.last_actions.clear();
+
+        // collisions of the previous world must not be processed in the restored one
+        while self.collision_recv.try_recv().is_ok() {}
+        while self.contact_force_recv.try_recv().is_ok() {}
+        Ok(())
+    }
+}
+
+fn changed_fields(recorded: &RobotProfile, current: &RobotProfile) -> Vec<String> {
+    let (serde_json::Value::Object(recorded), serde_json::Value::Object(current)) = (
+        serde_json::to_value(recorded).unwrap(),
+        serde_json::to_value(current).unwrap(),
+    ) else {
+        return Vec::new();
+    };
+    // `max_speed` is left out of the JSON when absent
+    let mut fields: Vec<String> = recorded
+        .keys()
+        .chain(current.keys())
+        .filter(|field| recorded.get(*field) != current.get(*field))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bots::BuiltinBot, headless, match_config::MatchConfig};
+
+    fn simulator(config: &MatchConfig) -> Simulator {
+        let [team1, team2] = headless::load_teams(config).unwrap();
+        Simulator::from_teams(team1, team2, config.settings.clone())
+    }
+
+    /// Tick, positions et vitesses de la balle puis des robots, score, statistiques et générateur aléatoire
+    fn state(simulation: &Simulator) -> (u64, Vec<f32>, [u32; 2], MatchStats, SimRng) {
+        let bodies = std::iter::once(simulation.ball_rigid_body_handle).chain(
+            simulation
+                .robots
+                .iter()
+                .map(|robot| simulation.robot_to_rigid_body_handle[robot]),
+        );
+        let figures = bodies
+            .flat_map(|handle| {
+                let body = &simulation.rigid_body_set[handle];
+                [
+                    body.translation().x,
+                    body.translation().y,
+                    body.rotation().angle(),
+                    body.linvel().x,
+                    body.linvel().y,
+                    body.angvel(),
+                ]
+            })
+            .collect();
+        (
+            simulation.tick_nb,
+            figures,
+            [
+                simulation.game_referee.score_team_left,
+                simulation.game_referee.score_team_right,
+            ],
+            simulation.stats.clone(),
+            simulation.rng,
+        )
+    }
+
+    /// L'instantané passe par du JSON, comme dans un enregistrement
+    fn through_json(snapshot: &SimulatorSnapshot) -> SimulatorSnapshot {
+        serde_json::from_str(&serde_json::to_string(snapshot).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn restored_match_continues_like_the_uninterrupted_one() {
+        let config = MatchConfig::builtin([BuiltinBot::Strong, BuiltinBot::Passing], 7);
+        let mut original = simulator(&config);
+        original.start();
+        for _ in 0..300 {
+            original.tick();
+        }
+        let snapshot = through_json(&original.snapshot());
+
+        let mut restored = simulator(&config);
+        restored.restore(snapshot).unwrap();
+        assert_eq!(state(&restored), state(&original));
+        for _ in 0..900 {
+            original.tick();
+            restored.tick();
+            assert_eq!(state(&restored), state(&original));
+        }
+    }
+
+    #[test]
+    fn restore_refuses_another_robot_profile() {
+        let config = MatchConfig::builtin([BuiltinBot::Chaser, BuiltinBot::Chaser], 7);
+        let mut original = simulator(&config);
+        original.start();
+        for _ in 0..60 {
+            original.tick();
+        }
+        let snapshot = original.snapshot();
+
+        let mut heavy = config.clone();
+        heavy.teams[1].robot_profile = Some("tests/robot_lourd.toml".to_owned());
+        let mut restored = simulator(&heavy);
+        match restored.restore(snapshot) {
+            Err(RestoreError::ProfileChanged { team_name, fields }) => {
+                assert_eq!(team_name, restored.team_names()[1]);
+                assert!(fields.iter().any(|field| field == "mass"), "{:?}", fields);
+                assert!(
+                    fields.iter().any(|field| field == "max_speed"),
+                    "{:?}",
+                    fields
+                );
+            }
+            Ok(()) => panic!("le profil lourd ne doit pas être accepté"),
+        }
+        assert_eq!(restored.tick_nb, 0);
+    }
+}
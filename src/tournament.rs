@@ -0,0 +1,529 @@
+use std::{
+    fmt::{Display, Write as _},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    headless::{HeadlessError, MatchJob, MatchResult, record_result, run_in_workers},
+    match_config::{MatchConfig, MatchSettings, TeamEntry},
+    player_action::validate_path,
+    ratings::RatingLedger,
+    recording::sanitize,
+    rng::SimRng,
+};
+
+const POINTS_WIN: u32 = 3;
+const POINTS_DRAW: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TournamentFormat {
+    /// Chaque équipe rencontre toutes les autres une fois
+    RoundRobin,
+    /// Élimination directe, les matchs nuls sont départagés à pile ou face
+    Knockout,
+}
+
+/// Une équipe inscrite au tournoi
+#[derive(Debug, Clone, Serialize)]
+pub struct Participant {
+    pub name: String,
+    pub script: String,
+}
+
+/// Un match joué pendant le tournoi. `teams` sont des index dans `Tournament::participants`
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayedMatch {
+    pub round: usize,
+    pub teams: [usize; 2],
+    /// `None` si le match n'a pas pu être joué, il ne compte alors pas au classement
+    pub result: Option<MatchResult>,
+    /// Pourquoi le match n'a pas pu être joué
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Équipe qualifiée en élimination directe, même en cas de match nul
+    pub qualified: Option<usize>,
+    pub record_path: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Standing {
+    pub team: usize,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub goals_for: u32,
+    pub goals_against: u32,
+    pub points: u32,
+}
+
+impl Standing {
+    #[inline]
+    pub fn goal_difference(&self) -> i64 {
+        self.goals_for as i64 - self.goals_against as i64
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tournament {
+    pub format: TournamentFormat,
+    pub participants: Vec<Participant>,
+    pub matches: Vec<PlayedMatch>,
+    pub standings: Vec<Standing>,
+}
+
+/// Paramètres d'un tournoi
+pub struct TournamentOptions {
+    pub format: TournamentFormat,
+    pub scripts: Vec<String>,
+    /// Règles et graine de base, la graine de chaque match est `seed + numéro du match`
+    pub settings: MatchSettings,
+    pub output_dir: PathBuf,
+    pub nb_workers: usize,
+}
+
+#[derive(Debug)]
+pub enum TournamentError {
+    NotEnoughTeams(usize),
+    Match(HeadlessError),
+    Io(String),
+}
+
+impl Display for TournamentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TournamentError::NotEnoughTeams(nb) => write!(
+                f,
+                "Il faut au moins 2 équipes pour faire un tournoi, pas {}",
+                nb
+            ),
+            TournamentError::Match(err) => write!(f, "{}", err),
+            TournamentError::Io(err_str) => {
+                write!(
+                    f,
+                    "Impossible d'écrire les résultats du tournoi : {}",
+                    err_str
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+impl From<HeadlessError> for TournamentError {
+    fn from(err: HeadlessError) -> Self {
+        TournamentError::Match(err)
+    }
+}
+
+/// Joue le tournoi complet. En championnat, tous les matchs sont joués en parallèle ;
+/// en élimination directe, ceux d'un même round, car le suivant dépend de leurs résultats.
+/// Un match qui échoue est gardé avec son erreur et le tournoi continue.
+/// Si `ledger` est donné, chaque match y est ajouté dès qu'il est terminé.
+/// `on_match_played` est appelée après chaque match, pour suivre l'avancement.
+pub fn run_tournament(
+    options: &TournamentOptions,
+    mut ledger: Option<&mut RatingLedger>,
+    on_match_played: &mut dyn FnMut(&Tournament, &PlayedMatch),
+) -> Result<Tournament, TournamentError> {
+    if options.scripts.len() < 2 {
+        return Err(TournamentError::NotEnoughTeams(options.scripts.len()));
+    }
+
+    // Check every team before playing anything, a broken script would waste the whole tournament
+    let mut participants = Vec::with_capacity(options.scripts.len());
+    for script in &options.scripts {
+        let code = validate_path(script).map_err(|err| HeadlessError::InvalidTeam {
+            script: script.clone(),
+            err,
+        })?;
+        participants.push(Participant {
+            name: code.name().to_owned(),
+            script: script.clone(),
+        });
+    }
+
+    let matchs_dir = options.output_dir.join("matchs");
+    std::fs::create_dir_all(&matchs_dir).map_err(|err| TournamentError::Io(format!("{}", err)))?;
+
+    let mut tournament = Tournament {
+        format: options.format,
+        participants,
+        matches: Vec::new(),
+        standings: Vec::new(),
+    };
+
+    match options.format {
+        TournamentFormat::RoundRobin => {
+            let schedule: Vec<(usize, [usize; 2])> =
+                round_robin_schedule(tournament.participants.len())
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(round, pairings)| {
+                        pairings.into_iter().map(move |teams| (round, teams))
+                    })
+                    .collect();
+            play_matches(
+                &mut tournament,
+                options,
+                &matchs_dir,
+                &schedule,
+                ledger.as_deref_mut(),
+                on_match_played,
+            );
+        }
+        TournamentFormat::Knockout => {
+            let mut rng = SimRng::new(options.settings.seed);
+            let mut remaining: Vec<usize> = (0..tournament.participants.len()).collect();
+            let mut round = 0;
+            while remaining.len() > 1 {
+                // the last team gets a bye when the number of teams is odd
+                let bye = (remaining.len() % 2 == 1).then(|| remaining.pop().unwrap());
+                let schedule: Vec<(usize, [usize; 2])> = remaining
+                    .chunks_exact(2)
+                    .map(|pair| (round, [pair[0], pair[1]]))
+                    .collect();
+                let first_new = tournament.matches.len();
+                play_matches(
+                    &mut tournament,
+                    options,
+                    &matchs_dir,
+                    &schedule,
+                    ledger.as_deref_mut(),
+                    on_match_played,
+                );
+
+                remaining = Vec::with_capacity(schedule.len() + 1);
+                for played in &mut tournament.matches[first_new..] {
+                    // a draw or a match that could not be played is decided by a coin toss
+                    let qualified = match played.result.as_ref().and_then(MatchResult::winner) {
+                        Some(winner) => played.teams[winner],
+                        None => played.teams[(rng.next_u64() % 2) as usize],
+                    };
+                    played.qualified = Some(qualified);
+                    remaining.push(qualified);
+                }
+                // the team with a bye plays first next round so it doesn't get two in a row
+                if let Some(bye) = bye {
+                    remaining.insert(0, bye);
+                }
+                round += 1;
+            }
+        }
+    }
+
+    tournament.standings = compute_standings(&tournament);
+    Ok(tournament)
+}
+
+/// Joue en parallèle les matchs de `schedule`, des paires (round, équipes)
+fn play_matches(
+    tournament: &mut Tournament,
+    options: &TournamentOptions,
+    matchs_dir: &Path,
+    schedule: &[(usize, [usize; 2])],
+    mut ledger: Option<&mut RatingLedger>,
+    on_match_played: &mut dyn FnMut(&Tournament, &PlayedMatch),
+) {
+    let jobs: Vec<MatchJob> = schedule
+        .iter()
+        .enumerate()
+        .map(|(n, (_, teams))| {
+            let match_nb = tournament.matches.len() + n;
+            let mut settings = options.settings.clone();
+            settings.seed = options.settings.seed.wrapping_add(match_nb as u64);
+            let file_stem = format!(
+                "{:03}_{}_vs_{}",
+                match_nb + 1,
+                sanitize(&tournament.participants[teams[0]].name),
+                sanitize(&tournament.participants[teams[1]].name)
+            );
+            MatchJob {
+                config: MatchConfig {
+                    teams: teams
+                        .iter()
+                        .map(|&team| TeamEntry {
+                            script: tournament.participants[team].script.clone(),
+                            robot_profile: None,
+                            color: None,
+                        })
+                        .collect(),
+                    settings,
+                },
+                config_path: matchs_dir.join(format!("{}.toml", file_stem)),
+                result_path: matchs_dir.join(format!("{}.json", file_stem)),
+                record_path: Some(matchs_dir.join(format!("{}.rrd", file_stem))),
+            }
+        })
+        .collect();
+
+    for ((&(round, teams), job), result) in schedule
+        .iter()
+        .zip(&jobs)
+        .zip(run_in_workers(&jobs, options.nb_workers))
+    {
+        let (result, error) = match result {
+            Ok(result) => {
+                if let Some(ledger) = ledger.as_deref_mut() {
+                    record_result(ledger, &result, &job.config);
+                }
+                (Some(result), None)
+            }
+            Err(err) => (None, Some(format!("{}", err))),
+        };
+        tournament.matches.push(PlayedMatch {
+            round,
+            teams,
+            result,
+            error,
+            qualified: None,
+            record_path: job.record_path.clone().unwrap(),
+        });
+        on_match_played(tournament, tournament.matches.last().unwrap());
+    }
+}
+
+/// Calendrier d'un championnat par la méthode du cercle :
+/// une équipe reste fixe et les autres tournent autour d'elle à chaque round.
+/// Avec un nombre impair d'équipes, une équipe fictive donne un round de repos.
+pub fn round_robin_schedule(nb_teams: usize) -> Vec<Vec<[usize; 2]>> {
+    let mut slots: Vec<Option<usize>> = (0..nb_teams).map(Some).collect();
+    if nb_teams % 2 == 1 {
+        slots.push(None);
+    }
+    let nb_slots = slots.len();
+
+    let mut rounds = Vec::with_capacity(nb_slots - 1);
+    for round in 0..nb_slots - 1 {
+        let mut pairings = Vec::with_capacity(nb_slots / 2);
+        for i in 0..nb_slots / 2 {
+            if let (Some(a), Some(b)) = (slots[i], slots[nb_slots - 1 - i]) {
+                // alternate sides so that nobody always starts on the same side
+                if round % 2 == 0 {
+                    pairings.push([a, b]);
+                } else {
+                    pairings.push([b, a]);
+                }
+            }
+        }
+        rounds.push(pairings);
+        slots[1..].rotate_right(1);
+    }
+    rounds
+}
+
+/// Classement : points, puis différence de buts, puis buts marqués
+pub fn compute_standings(tournament: &Tournament) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = (0..tournament.participants.len())
+        .map(|team| Standing {
+            team,
+            ..Default::default()
+        })
+        .collect();
+
+    for played in &tournament.matches {
+        let Some(result) = &played.result else {
+            continue;
+        };
+        for side in 0..2 {
+            let standing = &mut standings[played.teams[side]];
+            standing.played += 1;
+            standing.goals_for += result.scores[side];
+            standing.goals_against += result.scores[1 - side];
+            match result.winner() {
+                Some(winner) if winner == side => {
+                    standing.wins += 1;
+                    standing.points += POINTS_WIN;
+                }
+                Some(_) => standing.losses += 1,
+                None => {
+                    standing.draws += 1;
+                    standing.points += POINTS_DRAW;
+                }
+            }
+        }
+    }
+
+    standings.sort_by(|a, b| {
+        b.points
+            .cmp(&a.points)
+            .then(b.goal_difference().cmp(&a.goal_difference()))
+            .then(b.goals_for.cmp(&a.goals_for))
+    });
+    standings
+}
+
+impl Tournament {
+    /// Tableau du classement, pour l'afficher dans le terminal
+    pub fn standings_table(&self) -> String {
+        let mut table = format!(
+            "{:<4}{:<30}{:>4}{:>4}{:>4}{:>4}{:>5}{:>5}{:>6}{:>5}\n",
+            "#", "Équipe", "J", "G", "N", "P", "BP", "BC", "Diff", "Pts"
+        );
+        for (rank, standing) in self.standings.iter().enumerate() {
+            let _ = writeln!(
+                table,
+                "{:<4}{:<30}{:>4}{:>4}{:>4}{:>4}{:>5}{:>5}{:>6}{:>5}",
+                rank + 1,
+                self.participants[standing.team].name,
+                standing.played,
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.goals_for,
+                standing.goals_against,
+                standing.goal_difference(),
+                standing.points
+            );
+        }
+        table
+    }
+
+    /// Écrit `classement.csv`, `matchs.csv` et `tournoi.json` dans `output_dir`
+    pub fn export(&self, output_dir: &Path) -> Result<(), TournamentError> {
+        let mut standings_csv = String::from(
+            "rang,equipe,script,joues,gagnes,nuls,perdus,buts_pour,buts_contre,difference,points\n",
+        );
+        for (rank, standing) in self.standings.iter().enumerate() {
+            let participant = &self.participants[standing.team];
+            let _ = writeln!(
+                standings_csv,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                rank + 1,
+                csv_field(&participant.name),
+                csv_field(&participant.script),
+                standing.played,
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.goals_for,
+                standing.goals_against,
+                standing.goal_difference(),
+                standing.points
+            );
+        }
+
+        let mut matches_csv = String::from(
+            "round,equipe_1,equipe_2,score_1,score_2,qualifie,graine,enregistrement,erreur\n",
+        );
+        for played in &self.matches {
+            let [score_1, score_2, seed] = match &played.result {
+                Some(result) => [
+                    result.scores[0].to_string(),
+                    result.scores[1].to_string(),
+                    result.seed.to_string(),
+                ],
+                None => Default::default(),
+            };
+            let _ = writeln!(
+                matches_csv,
+                "{},{},{},{},{},{},{},{},{}",
+                played.round + 1,
+                csv_field(&self.participants[played.teams[0]].name),
+                csv_field(&self.participants[played.teams[1]].name),
+                score_1,
+                score_2,
+                played
+                    .qualified
+                    .map(|team| csv_field(&self.participants[team].name))
+                    .unwrap_or_default(),
+                seed,
+                csv_field(&played.record_path.display().to_string()),
+                played.error.as_deref().map(csv_field).unwrap_or_default()
+            );
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| TournamentError::Io(format!("{}", err)))?;
+
+        for (file_name, content) in [
+            ("classement.csv", standings_csv),
+            ("matchs.csv", matches_csv),
+            ("tournoi.json", json),
+        ] {
+            std::fs::write(output_dir.join(file_name), content)
+                .map_err(|err| TournamentError::Io(format!("{}", err)))?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_robin_every_pair_meets_once() {
+        for nb_teams in 2..=9 {
+            let mut met = vec![vec![0; nb_teams]; nb_teams];
+            for [a, b] in round_robin_schedule(nb_teams).into_iter().flatten() {
+                met[a.min(b)][a.max(b)] += 1;
+            }
+            for a in 0..nb_teams {
+                for b in a + 1..nb_teams {
+                    assert_eq!(met[a][b], 1, "{} équipes : {} contre {}", nb_teams, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_robin_nobody_plays_twice_in_a_round() {
+        for nb_teams in 2..=9 {
+            for pairings in round_robin_schedule(nb_teams) {
+                let mut playing = vec![false; nb_teams];
+                for team in pairings.into_iter().flatten() {
+                    assert!(
+                        !playing[team],
+                        "{} équipes : {} joue deux fois",
+                        nb_teams, team
+                    );
+                    playing[team] = true;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_robin_odd_count_gives_one_bye_per_round() {
+        for nb_teams in [3, 5, 7, 9] {
+            let schedule = round_robin_schedule(nb_teams);
+            assert_eq!(schedule.len(), nb_teams);
+            let mut byes = vec![0; nb_teams];
+            for pairings in &schedule {
+                assert_eq!(pairings.len(), nb_teams / 2);
+                let playing: Vec<usize> = pairings.iter().flatten().copied().collect();
+                let resting: Vec<usize> = (0..nb_teams)
+                    .filter(|team| !playing.contains(team))
+                    .collect();
+                assert_eq!(resting.len(), 1);
+                byes[resting[0]] += 1;
+            }
+            assert!(byes.iter().all(|&nb| nb == 1), "{:?}", byes);
+        }
+    }
+
+    #[test]
+    fn round_robin_even_count_has_no_bye() {
+        for nb_teams in [2, 4, 6, 8] {
+            let schedule = round_robin_schedule(nb_teams);
+            assert_eq!(schedule.len(), nb_teams - 1);
+            assert!(
+                schedule
+                    .iter()
+                    .all(|pairings| pairings.len() == nb_teams / 2)
+            );
+        }
+    }
+}
@@ -50,3 +50,47 @@ impl EguiConvertCompatibility for Complex<f32> {
         }
     }
 }
+
+/// Passage du repère du terrain (cm, origine au centre) aux points d'un dessin egui, et inversement.
+/// Comme dans le viewer, y est vers le bas.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldTransform {
+    /// Centre du terrain à l'écran
+    pub center: egui::Pos2,
+    /// Points par cm
+    pub scale: f32,
+}
+
+impl FieldTransform {
+    /// Le terrain entier (murs compris) remplit `rect`
+    pub fn fit(rect: egui::Rect, field_depth: f32) -> FieldTransform {
+        FieldTransform {
+            center: rect.center(),
+            scale: rect.width() / field_depth,
+        }
+    }
+
+    /// `bounds` (dans le repère du terrain) remplit `rect` sans déformation, centré dans l'autre sens :
+    /// c'est ainsi qu'une vue 2D du viewer affiche ses limites visibles
+    pub fn letterbox(rect: egui::Rect, bounds: egui::Rect) -> FieldTransform {
+        let scale = (rect.width() / bounds.width()).min(rect.height() / bounds.height());
+        FieldTransform {
+            center: rect.center() - bounds.center().to_vec2() * scale,
+            scale,
+        }
+    }
+
+    pub fn to_screen(&self, field_position: Vector2<f32>) -> egui::Pos2 {
+        self.center + field_position.to_egui_vec2() * self.scale
+    }
+
+    pub fn to_field(&self, screen_position: egui::Pos2) -> Vector2<f32> {
+        let offset = (screen_position - self.center) / self.scale;
+        Vector2::new(offset.x, offset.y)
+    }
+
+    /// Une vitesse à l'écran (points/s) en cm/s
+    pub fn velocity_to_field(&self, screen_velocity: egui::Vec2) -> Vector2<f32> {
+        Vector2::new(screen_velocity.x, screen_velocity.y) / self.scale
+    }
+}
@@ -7,5 +7,5 @@ def update(data):
         "target_position": (50, 50), # position en coordonnées globales (par rapport au centre du terrain) de où on veut aller
         "power": 255, # puissance donnée aux moteurs
         "target_orientation": 180, # orientation à laquelle on souhaite aller
-        "kick": False, # shoot dans la balle
+        "kick": False, # shoot dans la balle : True/False ou la puissance entre 0 et 1
     }
@@ -9,6 +9,8 @@ TEAM_NAME = "vers balle"
 # enemy1_position: (float, float)
 # enemy2_position: (float, float)
 # ball_position: (float, float)
+# orientation_error: float, degrés restant à tourner pour atteindre target_orientation (proche de 0 une fois atteinte, 0 hors du mode position)
+# robot_number: int, 1 ou 2
 def update(data):
     return {
         "target_position": data["ball_position"],
@@ -0,0 +1,15 @@
+TEAM_NAME = "vitesse"
+
+# MODE choisit le format du dictionnaire renvoyé par update :
+# "position" (par défaut) : target_position, power, target_orientation, kick
+# "velocity_robot" : velocity (avant, gauche) dans le repère du robot, angular_velocity, kick
+# "velocity_field" : velocity dans le repère du terrain, angular_velocity, kick
+# "motors" : motors, liste des 4 puissances moteurs entre -255 et 255, kick
+MODE = "velocity_robot"
+
+def update(data):
+    return {
+        "velocity": (60, 0), # cm/s, tout droit devant le robot
+        "angular_velocity": 30, # degrés par seconde, le robot tourne en rond
+        "kick": False,
+    }
@@ -0,0 +1,13 @@
+TEAM_NAME = "robot lourd"
+
+# chemin du profil matériel, relatif à ce fichier
+ROBOT_PROFILE = "robot_lourd.toml"
+
+def update(data):
+    return {
+        "target_position": data["ball_position"],
+        "power": 255,
+        "target_orientation": 0,
+        "kick": 0.6, # puissance du tir entre 0 et 1
+        "kick_angle": 15, # optionnel, degrés par rapport à l'avant du robot, limité par kick_angle_range
+    }
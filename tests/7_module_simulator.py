@@ -0,0 +1,20 @@
+import simulator
+from simulator import Input, PositionAction
+
+TEAM_NAME = "avec aides"
+
+# Le module simulator est fourni par le simulateur : rien à installer.
+# Pour la complétion dans l'éditeur, ajoutez le dossier python/ du simulateur à ses chemins de recherche.
+def update(data):
+    data = Input.from_dict(data)
+    # on reste devant notre but tant que la balle est dans notre surface de réparation
+    if simulator.in_own_goal_area(data.ball_position):
+        target = simulator.add(simulator.own_goal(), (-simulator.ENBUT_DEPTH, data.ball_position[1]))
+    else:
+        target = data.ball_position
+    return PositionAction(
+        target_position=target,
+        # target_orientation va de 0 à 360, angle_to_target de -180 à 180
+        target_orientation=simulator.angle_to_target(data.my_position, data.ball_position) % 360,
+        kick=simulator.distance(data.my_position, data.ball_position) < 20,
+    ).to_dict()
@@ -0,0 +1,18 @@
+from simulator import Action, State
+
+TEAM_NAME = "classe"
+MODE = "velocity_field"
+
+# Au lieu d'une fonction update(data), une classe Robot : une instance par robot, qui garde son état
+class Robot:
+    def setup(self):
+        # appelé une fois, au chargement du code
+        self.nb_updates = 0
+
+    def update(self, state: State) -> Action:
+        self.nb_updates += 1
+        # le robot 1 fonce vers la balle, le robot 2 reste en arrière
+        target = state.ball_position if state.robot_number == 1 else (80, state.ball_position[1])
+        velocity = (target[0] - state.my_position[0], target[1] - state.my_position[1])
+        # une faute de frappe (state.ball_postion, Action(kik=True)...) lève une erreur qui propose le bon nom
+        return Action(velocity=velocity, angular_velocity=0, kick=state.robot_number == 1)